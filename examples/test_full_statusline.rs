@@ -14,8 +14,10 @@ fn main() {
             usage: true,
             cost: true,
             burn_rate: true,
-        },
-        theme: "nerdfonts".to_string(),
+        }
+        .into(),
+        theme: "dark".to_string(),
+        ..Default::default()
     };
 
     // Create test input data
@@ -52,8 +54,10 @@ fn main() {
             usage: true,
             cost: false,
             burn_rate: false,
-        },
-        theme: "nerdfonts".to_string(),
+        }
+        .into(),
+        theme: "dark".to_string(),
+        ..Default::default()
     };
 
     let minimal_generator = StatusLineGenerator::new(minimal_config);
@@ -69,8 +73,10 @@ fn main() {
             usage: false,
             cost: true,
             burn_rate: false,
-        },
-        theme: "nerdfonts".to_string(),
+        }
+        .into(),
+        theme: "dark".to_string(),
+        ..Default::default()
     };
 
     let cost_generator = StatusLineGenerator::new(cost_config);
@@ -86,8 +92,10 @@ fn main() {
             usage: false,
             cost: false,
             burn_rate: true,
-        },
-        theme: "nerdfonts".to_string(),
+        }
+        .into(),
+        theme: "dark".to_string(),
+        ..Default::default()
     };
 
     let burn_generator = StatusLineGenerator::new(burn_config);
@@ -155,6 +155,15 @@ pub fn identify_session_blocks_with_overrides(entries: &[UsageEntry]) -> Vec<Bil
     identify_dynamic_blocks(entries, &override_manager)
 }
 
+/// Identify session blocks with the overrides of `override_manager` rather than
+/// the ones stored in the config directory
+pub fn identify_session_blocks_with_manager(
+    entries: &[UsageEntry],
+    override_manager: &BlockOverrideManager,
+) -> Vec<BillingBlock> {
+    identify_dynamic_blocks(entries, override_manager)
+}
+
 /// Identify blocks using ccusage's dual-condition triggering algorithm
 /// Blocks start when either: timeSinceBlockStart > 5h OR timeSinceLastEntry > 5h
 fn identify_dynamic_blocks(
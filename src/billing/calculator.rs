@@ -4,14 +4,23 @@ use std::collections::HashMap;
 
 /// Calculate cost for a single usage entry
 pub fn calculate_entry_cost(entry: &UsageEntry, pricing: &ModelPricing) -> f64 {
-    let input_cost = (entry.input_tokens as f64 / 1000.0) * pricing.input_cost_per_1k;
-    let output_cost = (entry.output_tokens as f64 / 1000.0) * pricing.output_cost_per_1k;
-    let cache_creation_cost =
-        (entry.cache_creation_tokens as f64 / 1000.0) * pricing.cache_creation_cost_per_1k;
-    let cache_read_cost =
-        (entry.cache_read_tokens as f64 / 1000.0) * pricing.cache_read_cost_per_1k;
-
-    input_cost + output_cost + cache_creation_cost + cache_read_cost
+    pricing.calculate_cost(entry)
+}
+
+/// Set the cost of every entry whose model has a price, resolving each model id once
+pub fn fill_costs<'a>(
+    entries: impl IntoIterator<Item = &'a mut UsageEntry>,
+    pricing_map: &HashMap<String, ModelPricing>,
+) {
+    let mut resolved: HashMap<String, Option<&ModelPricing>> = HashMap::new();
+    for entry in entries {
+        let model_pricing = *resolved
+            .entry(entry.model.clone())
+            .or_insert_with(|| ModelPricing::get_model_pricing(pricing_map, &entry.model));
+        if let Some(model_pricing) = model_pricing {
+            entry.cost = Some(model_pricing.calculate_cost(entry));
+        }
+    }
 }
 
 /// Calculate total cost for a session
@@ -135,6 +144,11 @@ pub fn calculate_burn_rate(block: &BillingBlock, entries: &[UsageEntry]) -> Opti
     })
 }
 
+/// Block cost at its end if spending continues at the current burn rate
+pub fn project_block_cost(block: &BillingBlock, rate: &BurnRate) -> f64 {
+    block.cost + rate.cost_per_hour * block.remaining_minutes.max(0) as f64 / 60.0
+}
+
 /// Format remaining time in human-readable format
 pub fn format_remaining_time(minutes: i64) -> String {
     if minutes <= 0 {
@@ -154,6 +168,7 @@ pub fn format_remaining_time(minutes: i64) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::billing::PriceTier;
     use chrono::Utc;
 
     #[test]
@@ -175,6 +190,8 @@ mod tests {
             output_cost_per_1k: 15.0,
             cache_creation_cost_per_1k: 3.75,
             cache_read_cost_per_1k: 0.3,
+            context_limit: None,
+            tiers: Vec::new(),
         };
 
         let cost = calculate_entry_cost(&entry, &pricing);
@@ -183,6 +200,73 @@ mod tests {
         assert!((cost - 10.89).abs() < 0.001);
     }
 
+    #[test]
+    fn test_tiered_entry_cost() {
+        let pricing = ModelPricing {
+            model_name: "claude-sonnet-4".to_string(),
+            input_cost_per_1k: 3.0,
+            output_cost_per_1k: 15.0,
+            cache_creation_cost_per_1k: 3.75,
+            cache_read_cost_per_1k: 0.3,
+            context_limit: None,
+            tiers: vec![PriceTier {
+                above_tokens: 200_000,
+                input_cost_per_1k: Some(6.0),
+                output_cost_per_1k: Some(22.5),
+                cache_creation_cost_per_1k: None,
+                cache_read_cost_per_1k: Some(0.6),
+            }],
+        };
+        let entry = |input_tokens, cache_creation_tokens, cache_read_tokens| UsageEntry {
+            timestamp: Utc::now(),
+            input_tokens,
+            output_tokens: 1000,
+            cache_creation_tokens,
+            cache_read_tokens,
+            model: "claude-sonnet-4".to_string(),
+            cost: None,
+            session_id: "test".to_string(),
+        };
+
+        // Exactly at the threshold the base rates still apply
+        let at = calculate_entry_cost(&entry(100_000, 50_000, 50_000), &pricing);
+        assert!((at - (300.0 + 15.0 + 187.5 + 15.0)).abs() < 1e-9);
+
+        // One token over, every token of the entry moves to the tier; unset tier rates keep the base
+        let over = calculate_entry_cost(&entry(100_001, 50_000, 50_000), &pricing);
+        assert!((over - (600.006 + 22.5 + 187.5 + 30.0)).abs() < 1e-9);
+
+        // Cache reads count towards the prompt size
+        let cached = calculate_entry_cost(&entry(1, 0, 200_000), &pricing);
+        assert!((cached - (0.006 + 22.5 + 120.0)).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_project_block_cost() {
+        let now = Utc::now();
+        let mut block = BillingBlock {
+            start_time: now - Duration::hours(2),
+            end_time: now + Duration::hours(3),
+            cost: 4.0,
+            remaining_minutes: 180,
+            is_active: true,
+            session_count: 1,
+            total_tokens: 100_000,
+            start_time_source: crate::billing::types::BlockStartSource::Auto,
+            is_gap: false,
+        };
+        let rate = BurnRate {
+            tokens_per_minute: 1000.0,
+            tokens_per_minute_for_indicator: 500.0,
+            cost_per_hour: 2.5,
+            trend: BurnRateTrend::Stable,
+        };
+
+        assert!((project_block_cost(&block, &rate) - 11.5).abs() < 1e-9);
+        block.remaining_minutes = -5;
+        assert!((project_block_cost(&block, &rate) - 4.0).abs() < 1e-9);
+    }
+
     #[test]
     fn test_format_remaining_time() {
         assert_eq!(format_remaining_time(0), "expired");
@@ -227,6 +311,8 @@ mod tests {
                 output_cost_per_1k: 15.0,
                 cache_creation_cost_per_1k: 0.0,
                 cache_read_cost_per_1k: 0.0,
+                context_limit: None,
+                tiers: Vec::new(),
             },
         );
 
@@ -0,0 +1,143 @@
+//! Context window size of the active model
+
+use super::resolver::{self, normalize};
+use super::types::DEFAULT_CONTEXT_LIMIT;
+use super::{ContextConfig, ModelPricing};
+use std::collections::HashMap;
+
+/// Where a context limit comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LimitSource {
+    /// `[context.limits]` entry
+    Config,
+    /// Size marker in the model id or name, e.g. `[1m]` or `(1M context)`
+    Variant,
+    /// `max_input_tokens` of the pricing table
+    Pricing,
+    /// `[context] default_limit` or the built-in default
+    Default,
+}
+
+/// Context window of `model_id` (falling back to `display_name` for size markers)
+///
+/// Configured limits win, then an explicit size variant, then the pricing
+/// table. Claude Code only opens the 1M window through the `[1m]` variants, so
+/// a larger table limit for a Claude model without a marker is capped at 200k.
+pub fn context_limit(
+    model_id: &str,
+    display_name: &str,
+    pricing: &HashMap<String, ModelPricing>,
+    config: &ContextConfig,
+) -> (u64, LimitSource) {
+    let configured = resolver::resolve(&config.limits, model_id)
+        .chosen()
+        .map(|candidate| *candidate.entry)
+        .filter(|&limit| limit > 0);
+    if let Some(limit) = configured {
+        return (limit, LimitSource::Config);
+    }
+
+    if let Some(limit) = size_marker(model_id).or_else(|| size_marker(display_name)) {
+        return (limit, LimitSource::Variant);
+    }
+
+    let table_limit = ModelPricing::get_model_pricing(pricing, model_id)
+        .and_then(|model_pricing| model_pricing.context_limit);
+    match table_limit.filter(|&limit| limit > 0) {
+        Some(limit) if limit > DEFAULT_CONTEXT_LIMIT && normalize(model_id).contains("claude") => {
+            (DEFAULT_CONTEXT_LIMIT, LimitSource::Pricing)
+        }
+        Some(limit) => (limit, LimitSource::Pricing),
+        None => (config.default_limit(), LimitSource::Default),
+    }
+}
+
+/// Parse `[1m]` / `[500k]` in an id, or `(1M context)` in a display name
+fn size_marker(name: &str) -> Option<u64> {
+    let lower = name.to_ascii_lowercase();
+    let size = if let Some(start) = lower.find('[') {
+        let end = lower[start..].find(']')? + start;
+        &lower[start + 1..end]
+    } else {
+        let end = lower.find(" context")?;
+        let start = lower[..end].rfind(['(', ' '])? + 1;
+        &lower[start..end]
+    };
+
+    let unit = size.chars().last()?;
+    let number = &size[..size.len() - unit.len_utf8()];
+    let multiplier = match unit {
+        'm' => 1_000_000,
+        'k' => 1_000,
+        _ => return None,
+    };
+    number
+        .parse::<f64>()
+        .ok()
+        .filter(|n| *n > 0.0)
+        .map(|n| (n * multiplier as f64) as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_limit_sources() {
+        let mut pricing = ModelPricing::fallback_pricing();
+        pricing
+            .get_mut("claude-sonnet-4-20250514")
+            .unwrap()
+            .context_limit = Some(1_000_000);
+        let mut config = ContextConfig::default();
+
+        let limit = |id: &str, name: &str, config: &ContextConfig| {
+            context_limit(id, name, &pricing, config)
+        };
+
+        assert_eq!(
+            limit("claude-sonnet-4-20250514[1m]", "Sonnet 4", &config),
+            (1_000_000, LimitSource::Variant)
+        );
+        assert_eq!(
+            limit("claude-sonnet-4", "Sonnet 4 (1M context)", &config),
+            (1_000_000, LimitSource::Variant)
+        );
+        // Without the variant marker a Claude model keeps the standard window
+        assert_eq!(
+            limit("claude-sonnet-4-20250514", "Sonnet 4", &config),
+            (200_000, LimitSource::Pricing)
+        );
+        assert_eq!(
+            limit("gpt-5-codex", "GPT-5 Codex", &config),
+            (400_000, LimitSource::Pricing)
+        );
+        assert_eq!(
+            limit("local-llama", "Llama", &config),
+            (200_000, LimitSource::Default)
+        );
+
+        config.default_limit = Some(32_000);
+        config.limits.insert("gpt-5-codex".to_string(), 272_000);
+        assert_eq!(
+            limit("gpt-5-codex-high", "GPT-5 Codex", &config),
+            (272_000, LimitSource::Config)
+        );
+        assert_eq!(
+            limit("local-llama", "Llama", &config),
+            (32_000, LimitSource::Default)
+        );
+
+        // Zero limits are ignored rather than used as a divisor
+        config.default_limit = Some(0);
+        config.limits.insert("gpt-5-codex".to_string(), 0);
+        assert_eq!(
+            limit("gpt-5-codex", "GPT-5 Codex", &config),
+            (400_000, LimitSource::Pricing)
+        );
+        assert_eq!(
+            limit("local-llama", "Llama", &config),
+            (200_000, LimitSource::Default)
+        );
+    }
+}
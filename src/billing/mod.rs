@@ -1,9 +1,13 @@
 pub mod block;
 pub mod calculator;
+pub mod limits;
 pub mod pricing;
+pub mod pricing_cache;
+pub mod resolver;
+pub mod table;
 pub mod types;
 
 pub use types::{
-    BillingBlock, BurnRate, BurnRateThresholds, BurnRateTrend, ModelPricing, SessionUsage,
-    UsageEntry,
+    BillingBlock, BlockConfig, BurnRate, BurnRateThresholds, BurnRateTrend, ContextConfig,
+    ModelPricing, PriceOverride, PriceTier, PricingConfig, SessionUsage, UsageEntry,
 };
@@ -1,17 +1,8 @@
-use once_cell::sync::Lazy;
 use serde::Deserialize;
 use std::collections::HashMap;
-use std::sync::RwLock;
 
-use super::ModelPricing;
-
-/// LiteLLM's model pricing and context window data URL
-const LITELLM_PRICING_URL: &str =
-    "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json";
-
-/// Pricing data cache
-static PRICING_CACHE: Lazy<RwLock<Option<HashMap<String, ModelPricing>>>> =
-    Lazy::new(|| RwLock::new(None));
+use super::types::LITELLM_PRICING_URL;
+use super::{resolver, ModelPricing, PriceTier};
 
 /// LiteLLM data format
 #[derive(Debug, Clone, Deserialize)]
@@ -23,75 +14,134 @@ pub struct LiteLLMPricing {
     pub cache_creation_input_token_cost: Option<f64>,
     #[serde(default)]
     pub cache_read_input_token_cost: Option<f64>,
+    /// Remaining fields, including tiered rates such as `input_cost_per_token_above_200k_tokens`
+    #[serde(flatten)]
+    pub extra: HashMap<String, serde_json::Value>,
+}
+
+/// LiteLLM base rate fields that can carry an `_above_<N>k_tokens` tier
+const TIERED_FIELDS: [&str; 4] = [
+    "input_cost_per_token",
+    "output_cost_per_token",
+    "cache_creation_input_token_cost",
+    "cache_read_input_token_cost",
+];
+
+impl LiteLLMPricing {
+    /// Prompt size limit: `max_input_tokens`, or `max_tokens` for entries without it
+    pub fn context_limit(&self) -> Option<u64> {
+        ["max_input_tokens", "max_tokens"]
+            .iter()
+            .find_map(|field| self.extra.get(*field).and_then(serde_json::Value::as_u64))
+    }
+
+    /// Long-context tiers converted to per-1k rates, sorted by threshold
+    pub fn tiers(&self) -> Vec<PriceTier> {
+        let mut tiers: Vec<PriceTier> = Vec::new();
+        for (key, value) in &self.extra {
+            let Some((field, threshold)) = key.split_once("_above_") else {
+                continue;
+            };
+            let Some(field_index) = TIERED_FIELDS.iter().position(|f| *f == field) else {
+                continue;
+            };
+            let above_tokens = match threshold
+                .strip_suffix("_tokens")
+                .and_then(|t| t.strip_suffix('k'))
+                .and_then(|t| t.parse::<u64>().ok())
+            {
+                Some(thousands) => thousands * 1000,
+                None => continue,
+            };
+            let Some(cost) = value.as_f64() else {
+                continue;
+            };
+
+            let index = match tiers.iter().position(|t| t.above_tokens == above_tokens) {
+                Some(index) => index,
+                None => {
+                    tiers.push(PriceTier {
+                        above_tokens,
+                        input_cost_per_1k: None,
+                        output_cost_per_1k: None,
+                        cache_creation_cost_per_1k: None,
+                        cache_read_cost_per_1k: None,
+                    });
+                    tiers.len() - 1
+                }
+            };
+            let tier = &mut tiers[index];
+            let rate = match field_index {
+                0 => &mut tier.input_cost_per_1k,
+                1 => &mut tier.output_cost_per_1k,
+                2 => &mut tier.cache_creation_cost_per_1k,
+                _ => &mut tier.cache_read_cost_per_1k,
+            };
+            *rate = Some(cost * 1000.0);
+        }
+        tiers.sort_by_key(|t| t.above_tokens);
+        tiers
+    }
 }
 
 impl ModelPricing {
-    /// Fetch pricing data from LiteLLM (with caching)
+    /// Fetch pricing data from LiteLLM
     pub async fn fetch_pricing() -> Result<HashMap<String, ModelPricing>, Box<dyn std::error::Error>>
     {
-        // Check cache first
-        if let Some(cached) = PRICING_CACHE.read().unwrap().as_ref() {
-            return Ok(cached.clone());
-        }
+        Self::fetch_pricing_from(LITELLM_PRICING_URL).await
+    }
 
-        // Fetch latest data
-        let response = reqwest::get(LITELLM_PRICING_URL).await?;
+    /// Fetch LiteLLM-format pricing data from `url`
+    pub async fn fetch_pricing_from(
+        url: &str,
+    ) -> Result<HashMap<String, ModelPricing>, Box<dyn std::error::Error>> {
+        let response = reqwest::get(url).await?.error_for_status()?;
         let data: HashMap<String, LiteLLMPricing> = response.json().await?;
+        Ok(Self::from_litellm(data))
+    }
 
-        // Convert to internal format, only keep Claude models with valid pricing
+    /// Convert LiteLLM entries, keeping Claude and OpenAI/Codex models with token pricing
+    fn from_litellm(data: HashMap<String, LiteLLMPricing>) -> HashMap<String, ModelPricing> {
         let mut pricing = HashMap::new();
-        let mut total_models = 0;
-        let mut claude_models = 0;
-        let mut valid_claude_models = 0;
-
         for (model_name, litellm_pricing) in data {
-            total_models += 1;
-
             // Check if it's a Claude or OpenAI/Codex model
             let is_claude = model_name.starts_with("claude-") || model_name.contains("claude");
             let is_openai = model_name.starts_with("gpt-")
                 || model_name.starts_with("o3")
                 || model_name.starts_with("o4");
+            if !is_claude && !is_openai {
+                continue;
+            }
 
-            if is_claude || is_openai {
-                claude_models += 1;
-
-                // Only process models with valid token pricing (skip image generation models etc.)
-                if let (Some(input_cost), Some(output_cost)) = (
-                    litellm_pricing.input_cost_per_token,
-                    litellm_pricing.output_cost_per_token,
-                ) {
-                    valid_claude_models += 1;
-                    pricing.insert(
-                        model_name.clone(),
-                        ModelPricing {
-                            model_name,
-                            // Convert to cost per 1k tokens
-                            input_cost_per_1k: input_cost * 1000.0,
-                            output_cost_per_1k: output_cost * 1000.0,
-                            cache_creation_cost_per_1k: litellm_pricing
-                                .cache_creation_input_token_cost
-                                .map(|c| c * 1000.0)
-                                .unwrap_or(0.0),
-                            cache_read_cost_per_1k: litellm_pricing
-                                .cache_read_input_token_cost
-                                .map(|c| c * 1000.0)
-                                .unwrap_or(0.0),
-                        },
-                    );
-                }
+            // Only process models with valid token pricing (skip image generation models etc.)
+            if let (Some(input_cost), Some(output_cost)) = (
+                litellm_pricing.input_cost_per_token,
+                litellm_pricing.output_cost_per_token,
+            ) {
+                let tiers = litellm_pricing.tiers();
+                let context_limit = litellm_pricing.context_limit();
+                pricing.insert(
+                    model_name.clone(),
+                    ModelPricing {
+                        model_name,
+                        // Convert to cost per 1k tokens
+                        input_cost_per_1k: input_cost * 1000.0,
+                        output_cost_per_1k: output_cost * 1000.0,
+                        cache_creation_cost_per_1k: litellm_pricing
+                            .cache_creation_input_token_cost
+                            .map(|c| c * 1000.0)
+                            .unwrap_or(0.0),
+                        cache_read_cost_per_1k: litellm_pricing
+                            .cache_read_input_token_cost
+                            .map(|c| c * 1000.0)
+                            .unwrap_or(0.0),
+                        context_limit,
+                        tiers,
+                    },
+                );
             }
         }
-
-        eprintln!(
-            "LiteLLM: Fetched {} total models, {} Claude/OpenAI models, {} with valid pricing",
-            total_models, claude_models, valid_claude_models
-        );
-
-        // Update cache
-        *PRICING_CACHE.write().unwrap() = Some(pricing.clone());
-
-        Ok(pricing)
+        pricing
     }
 
     /// Get pricing with fallback
@@ -121,6 +171,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.006,  // $6.00/1M tokens
                 cache_creation_cost_per_1k: 0.0,
                 cache_read_cost_per_1k: 0.0,
+                context_limit: Some(400_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -132,6 +184,35 @@ impl ModelPricing {
                 output_cost_per_1k: 0.006,  // $6.00/1M tokens
                 cache_creation_cost_per_1k: 0.0,
                 cache_read_cost_per_1k: 0.0,
+                context_limit: Some(400_000),
+                tiers: Vec::new(),
+            },
+        );
+
+        // GPT-5 models, as recorded in Codex transcripts
+        m.insert(
+            "gpt-5".to_string(),
+            ModelPricing {
+                model_name: "gpt-5".to_string(),
+                input_cost_per_1k: 0.00125, // $1.25/1M tokens
+                output_cost_per_1k: 0.01,   // $10.00/1M tokens
+                cache_creation_cost_per_1k: 0.0,
+                cache_read_cost_per_1k: 0.000125, // $0.125/1M tokens
+                context_limit: Some(400_000),
+                tiers: Vec::new(),
+            },
+        );
+
+        m.insert(
+            "gpt-5-mini".to_string(),
+            ModelPricing {
+                model_name: "gpt-5-mini".to_string(),
+                input_cost_per_1k: 0.00025, // $0.25/1M tokens
+                output_cost_per_1k: 0.002,  // $2.00/1M tokens
+                cache_creation_cost_per_1k: 0.0,
+                cache_read_cost_per_1k: 0.000025, // $0.025/1M tokens
+                context_limit: Some(400_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -144,6 +225,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.006,
                 cache_creation_cost_per_1k: 0.0,
                 cache_read_cost_per_1k: 0.0,
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -155,6 +238,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.006,
                 cache_creation_cost_per_1k: 0.0,
                 cache_read_cost_per_1k: 0.0,
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -166,6 +251,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.006,
                 cache_creation_cost_per_1k: 0.0,
                 cache_read_cost_per_1k: 0.0,
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -180,6 +267,61 @@ impl ModelPricing {
                 output_cost_per_1k: 0.015, // $0.015/1k tokens = $15/1M tokens
                 cache_creation_cost_per_1k: 0.00375, // $0.00375/1k tokens = $3.75/1M tokens
                 cache_read_cost_per_1k: 0.0003, // $0.0003/1k tokens = $0.30/1M tokens
+                context_limit: Some(200_000),
+                // Prompts above 200k tokens (1M context) are billed at premium rates
+                tiers: vec![PriceTier {
+                    above_tokens: 200_000,
+                    input_cost_per_1k: Some(0.006),   // $6/1M tokens
+                    output_cost_per_1k: Some(0.0225), // $22.50/1M tokens
+                    cache_creation_cost_per_1k: Some(0.0075), // $7.50/1M tokens
+                    cache_read_cost_per_1k: Some(0.0006), // $0.60/1M tokens
+                }],
+            },
+        );
+
+        m.insert(
+            "claude-sonnet-4-5-20250929".to_string(),
+            ModelPricing {
+                model_name: "claude-sonnet-4-5-20250929".to_string(),
+                input_cost_per_1k: 0.003,  // $0.003/1k tokens = $3/1M tokens
+                output_cost_per_1k: 0.015, // $0.015/1k tokens = $15/1M tokens
+                cache_creation_cost_per_1k: 0.00375, // $0.00375/1k tokens = $3.75/1M tokens
+                cache_read_cost_per_1k: 0.0003, // $0.0003/1k tokens = $0.30/1M tokens
+                context_limit: Some(200_000),
+                // Prompts above 200k tokens (1M context) are billed at premium rates
+                tiers: vec![PriceTier {
+                    above_tokens: 200_000,
+                    input_cost_per_1k: Some(0.006),   // $6/1M tokens
+                    output_cost_per_1k: Some(0.0225), // $22.50/1M tokens
+                    cache_creation_cost_per_1k: Some(0.0075), // $7.50/1M tokens
+                    cache_read_cost_per_1k: Some(0.0006), // $0.60/1M tokens
+                }],
+            },
+        );
+
+        m.insert(
+            "claude-haiku-4-5-20251001".to_string(),
+            ModelPricing {
+                model_name: "claude-haiku-4-5-20251001".to_string(),
+                input_cost_per_1k: 0.001,  // $0.001/1k tokens = $1/1M tokens
+                output_cost_per_1k: 0.005, // $0.005/1k tokens = $5/1M tokens
+                cache_creation_cost_per_1k: 0.00125, // $0.00125/1k tokens = $1.25/1M tokens
+                cache_read_cost_per_1k: 0.0001, // $0.0001/1k tokens = $0.10/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
+            },
+        );
+
+        m.insert(
+            "claude-opus-4-20250514".to_string(),
+            ModelPricing {
+                model_name: "claude-opus-4-20250514".to_string(),
+                input_cost_per_1k: 0.015, // $0.015/1k tokens = $15/1M tokens
+                output_cost_per_1k: 0.075, // $0.075/1k tokens = $75/1M tokens
+                cache_creation_cost_per_1k: 0.01875, // $0.01875/1k tokens = $18.75/1M tokens
+                cache_read_cost_per_1k: 0.0015, // $0.0015/1k tokens = $1.5/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -191,6 +333,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.075, // $0.075/1k tokens = $75/1M tokens
                 cache_creation_cost_per_1k: 0.01875, // $0.01875/1k tokens = $18.75/1M tokens
                 cache_read_cost_per_1k: 0.0015, // $0.0015/1k tokens = $1.5/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -202,6 +346,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.075, // $0.075/1k tokens = $75/1M tokens
                 cache_creation_cost_per_1k: 0.01875, // $0.01875/1k tokens = $18.75/1M tokens
                 cache_read_cost_per_1k: 0.0015, // $0.0015/1k tokens = $1.5/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -214,6 +360,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.015, // $0.015/1k tokens = $15/1M tokens
                 cache_creation_cost_per_1k: 0.00375, // $0.00375/1k tokens = $3.75/1M tokens
                 cache_read_cost_per_1k: 0.0003, // $0.0003/1k tokens = $0.30/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -225,6 +373,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.015, // $0.015/1k tokens = $15/1M tokens
                 cache_creation_cost_per_1k: 0.00375, // $0.00375/1k tokens = $3.75/1M tokens
                 cache_read_cost_per_1k: 0.0003, // $0.0003/1k tokens = $0.30/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -237,6 +387,8 @@ impl ModelPricing {
                 output_cost_per_1k: 0.075, // $0.075/1k tokens = $75/1M tokens
                 cache_creation_cost_per_1k: 0.01875, // $0.01875/1k tokens = $18.75/1M tokens
                 cache_read_cost_per_1k: 0.0015, // $0.0015/1k tokens = $1.50/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
@@ -248,38 +400,107 @@ impl ModelPricing {
                 output_cost_per_1k: 0.004, // $0.004/1k tokens = $4/1M tokens
                 cache_creation_cost_per_1k: 0.001, // $0.001/1k tokens = $1/1M tokens
                 cache_read_cost_per_1k: 0.00008, // $0.00008/1k tokens = $0.08/1M tokens
+                context_limit: Some(200_000),
+                tiers: Vec::new(),
             },
         );
 
         m
     }
 
-    /// Get pricing for a specific model with fuzzy matching
+    /// Get pricing for a specific model, resolving provider prefixes, dates and variants
     pub fn get_model_pricing<'a>(
         pricing_map: &'a HashMap<String, ModelPricing>,
         model_name: &str,
     ) -> Option<&'a ModelPricing> {
-        // Try exact match first
+        // Most transcript ids are pricing keys as-is
         if let Some(pricing) = pricing_map.get(model_name) {
             return Some(pricing);
         }
 
-        // Try fuzzy matching
-        let model_lower = model_name.to_lowercase();
-
-        // Look for the most specific match
-        pricing_map
-            .iter()
-            .filter(|(key, _)| {
-                let key_lower = key.to_lowercase();
-                model_lower.contains(&key_lower) || key_lower.contains(&model_lower)
-            })
-            .max_by_key(|(key, _)| key.len()) // Prefer longer (more specific) matches
-            .map(|(_, pricing)| pricing)
+        resolver::resolve(pricing_map, model_name)
+            .chosen()
+            .map(|candidate| candidate.entry)
     }
 }
 
-/// Clear the pricing cache (useful for testing)
-pub fn clear_pricing_cache() {
-    *PRICING_CACHE.write().unwrap() = None;
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fallback_prices_transcript_model_ids() {
+        let pricing = ModelPricing::fallback_pricing();
+        let resolved = |model: &str| {
+            ModelPricing::get_model_pricing(&pricing, model)
+                .map(|pricing| pricing.model_name.as_str())
+        };
+
+        // Ids as Claude Code and Codex write them to transcripts
+        assert_eq!(resolved("gpt-5"), Some("gpt-5"));
+        assert_eq!(resolved("gpt-5-codex"), Some("gpt-5-codex"));
+        assert_eq!(resolved("gpt-5-mini"), Some("gpt-5-mini"));
+        assert_eq!(
+            resolved("claude-sonnet-4-5-20250929"),
+            Some("claude-sonnet-4-5-20250929")
+        );
+        assert_eq!(
+            resolved("claude-sonnet-4-20250514"),
+            Some("claude-sonnet-4-20250514")
+        );
+        assert_eq!(
+            resolved("claude-opus-4-20250514"),
+            Some("claude-opus-4-20250514")
+        );
+        assert_eq!(
+            resolved("claude-opus-4-1-20250805"),
+            Some("claude-opus-4-1-20250805")
+        );
+        assert_eq!(
+            resolved("claude-haiku-4-5-20251001"),
+            Some("claude-haiku-4-5-20251001")
+        );
+        assert_eq!(
+            resolved("claude-3-5-haiku-20241022"),
+            Some("claude-3-5-haiku-20241022")
+        );
+        // Routed and undated ids resolve to the same prices
+        assert_eq!(
+            resolved("claude-sonnet-4-5"),
+            Some("claude-sonnet-4-5-20250929")
+        );
+        assert_eq!(
+            resolved("anthropic/claude-opus-4-20250514"),
+            Some("claude-opus-4-20250514")
+        );
+        assert_eq!(resolved("gpt-5-codex-high"), Some("gpt-5-codex"));
+    }
+
+    #[test]
+    fn test_litellm_tiers() {
+        let data: HashMap<String, LiteLLMPricing> = serde_json::from_str(
+            r#"{"claude-sonnet-4-5": {
+                "input_cost_per_token": 3e-06,
+                "output_cost_per_token": 1.5e-05,
+                "input_cost_per_token_above_200k_tokens": 6e-06,
+                "output_cost_per_token_above_200k_tokens": 2.25e-05,
+                "cache_read_input_token_cost_above_200k_tokens": 6e-07,
+                "input_cost_per_token_above_128k_tokens": 4e-06,
+                "max_input_tokens": 1000000,
+                "litellm_provider": "anthropic"
+            }}"#,
+        )
+        .unwrap();
+
+        let pricing = ModelPricing::from_litellm(data);
+        let tiers = &pricing["claude-sonnet-4-5"].tiers;
+        assert_eq!(
+            tiers.iter().map(|t| t.above_tokens).collect::<Vec<_>>(),
+            vec![128_000, 200_000]
+        );
+        assert!((tiers[1].input_cost_per_1k.unwrap() - 0.006).abs() < 1e-12);
+        assert!((tiers[1].cache_read_cost_per_1k.unwrap() - 0.0006).abs() < 1e-12);
+        assert_eq!(tiers[1].cache_creation_cost_per_1k, None);
+        assert_eq!(tiers[0].output_cost_per_1k, None);
+    }
 }
@@ -0,0 +1,299 @@
+use super::{ModelPricing, PricingConfig};
+use crate::utils::resolve_config_dir;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const CACHE_FILE: &str = ".pricing_cache.json";
+
+/// Bumped whenever the on-disk layout changes
+const CACHE_VERSION: u32 = 3;
+
+/// A fetch is not attempted again within this window after the previous attempt,
+/// so a slow or unreachable pricing server does not delay every statusline
+const FETCH_BACKOFF: Duration = Duration::from_secs(5 * 60);
+
+/// Upper bound on a single pricing download
+const FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Pricing table fetched from LiteLLM, kept on disk between runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PricingCache {
+    version: u32,
+    pub fetched_at: DateTime<Utc>,
+    /// URL the table was fetched from; a cache for another URL is never fresh
+    pub url: String,
+    pub models: HashMap<String, ModelPricing>,
+}
+
+/// Where the pricing table of this invocation came from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PricingSource {
+    /// On-disk cache within its TTL
+    Fresh,
+    /// On-disk cache past its TTL; a refresh should be started in the background
+    Stale,
+    /// On-disk cache used as-is because the network is off limits
+    Offline,
+    /// Downloaded during this invocation
+    Fetched,
+    /// Built-in table
+    Fallback,
+}
+
+/// Pricing table together with its origin
+#[derive(Debug, Clone)]
+pub struct LoadedPricing {
+    pub models: HashMap<String, ModelPricing>,
+    pub source: PricingSource,
+}
+
+impl PricingCache {
+    /// Location of the cache under the config directory
+    pub fn default_path() -> PathBuf {
+        resolve_config_dir().join(CACHE_FILE)
+    }
+
+    /// Load the cache; a missing, corrupt or outdated file yields `None`
+    pub fn load(path: &Path) -> Option<Self> {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|content| serde_json::from_str::<PricingCache>(&content).ok())
+            .filter(|cache| cache.version == CACHE_VERSION)
+    }
+
+    /// Write the cache, replacing the old file atomically
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let temp = path.with_extension(format!("json.{}", std::process::id()));
+        fs::write(&temp, serde_json::to_vec(self)?)?;
+        fs::rename(&temp, path)
+    }
+
+    /// Whether the cache was fetched from `url` less than `ttl` ago
+    pub fn is_fresh(&self, url: &str, ttl: Duration, now: DateTime<Utc>) -> bool {
+        let age = now.signed_duration_since(self.fetched_at);
+        self.url == url && age.to_std().is_ok_and(|age| age < ttl)
+    }
+}
+
+/// Resolve the pricing table without waiting on the network when a cache exists
+///
+/// A fresh cache is used directly and a stale one is returned as-is, leaving the
+/// refresh to the caller. Without a cache the table is fetched once; when that
+/// fails the built-in table is used and the next attempt waits out a back-off.
+/// In offline mode the network is never touched.
+pub fn load(config: &PricingConfig, path: &Path) -> LoadedPricing {
+    let cached = PricingCache::load(path);
+
+    if config.offline {
+        return match cached {
+            Some(cache) => LoadedPricing {
+                models: cache.models,
+                source: PricingSource::Offline,
+            },
+            None => fallback(),
+        };
+    }
+
+    match cached {
+        Some(cache) => {
+            let source = if cache.is_fresh(config.url(), config.ttl(), Utc::now()) {
+                PricingSource::Fresh
+            } else {
+                PricingSource::Stale
+            };
+            LoadedPricing {
+                models: cache.models,
+                source,
+            }
+        }
+        None if claim_fetch(path) => match refresh(config, path) {
+            Ok(models) => LoadedPricing {
+                models,
+                source: PricingSource::Fetched,
+            },
+            Err(_) => fallback(),
+        },
+        None => fallback(),
+    }
+}
+
+/// Download the pricing table from the configured URL and store it at `path`
+pub fn refresh(
+    config: &PricingConfig,
+    path: &Path,
+) -> Result<HashMap<String, ModelPricing>, Box<dyn std::error::Error>> {
+    if config.offline {
+        return Err("pricing refresh is disabled in offline mode".into());
+    }
+
+    let url = config.url();
+    let rt = tokio::runtime::Runtime::new()?;
+    let models = rt.block_on(async {
+        tokio::time::timeout(FETCH_TIMEOUT, ModelPricing::fetch_pricing_from(url))
+            .await
+            .map_err(|_| format!("timed out fetching {}", url))?
+    })?;
+
+    PricingCache {
+        version: CACHE_VERSION,
+        fetched_at: Utc::now(),
+        url: url.to_string(),
+        models: models.clone(),
+    }
+    .save(path)?;
+    Ok(models)
+}
+
+/// Refresh a stale cache in a detached `ccline --refresh-pricing` process
+///
+/// The statusline exits long before a download finishes, so the refresh runs in
+/// its own process and the next invocation picks up the new cache.
+pub fn spawn_refresh(config: &PricingConfig, path: &Path) {
+    if !claim_fetch(path) {
+        return;
+    }
+    let Ok(exe) = std::env::current_exe() else {
+        return;
+    };
+    let _ = std::process::Command::new(exe)
+        .arg("--refresh-pricing")
+        .env("CCLINE_PRICING_URL", config.url())
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn();
+}
+
+/// Record a fetch attempt, returning false when another one started recently
+fn claim_fetch(path: &Path) -> bool {
+    let marker = path.with_extension("json.fetch");
+    let recent = fs::metadata(&marker)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .is_some_and(|elapsed| elapsed < FETCH_BACKOFF);
+    if recent {
+        return false;
+    }
+
+    if let Some(parent) = marker.parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    let _ = fs::write(&marker, b"");
+    true
+}
+
+fn fallback() -> LoadedPricing {
+    LoadedPricing {
+        models: ModelPricing::fallback_pricing(),
+        source: PricingSource::Fallback,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
+
+    /// Serve `body` as JSON to a single request on a local port
+    fn serve_once(body: &'static str) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/prices.json", listener.local_addr().unwrap());
+        std::thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut request = [0u8; 1024];
+            let _ = stream.read(&mut request);
+            let response = format!(
+                "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                body.len(),
+                body
+            );
+            stream.write_all(response.as_bytes()).unwrap();
+        });
+        url
+    }
+
+    #[test]
+    fn test_fetch_cache_and_offline() {
+        let root = std::env::temp_dir().join(format!("ccline-pricing-{}", std::process::id()));
+        let path = root.join(CACHE_FILE);
+        let url = serve_once(
+            r#"{"claude-test-1":{"input_cost_per_token":0.000002,"output_cost_per_token":0.00001},"dall-e-3":{"output_cost_per_pixel":0.1}}"#,
+        );
+        let config = PricingConfig {
+            url: Some(url.clone()),
+            ..PricingConfig::default()
+        };
+
+        // Offline without a cache never fetches
+        let offline = PricingConfig {
+            offline: true,
+            ..config.clone()
+        };
+        assert_eq!(load(&offline, &path).source, PricingSource::Fallback);
+
+        // First online load downloads from the stub and writes the cache
+        let loaded = load(&config, &path);
+        assert_eq!(loaded.source, PricingSource::Fetched);
+        assert_eq!(loaded.models.len(), 1);
+        assert!((loaded.models["claude-test-1"].input_cost_per_1k - 0.002).abs() < 1e-12);
+
+        // The stub only answers once, so these are served from disk
+        assert_eq!(load(&config, &path).source, PricingSource::Fresh);
+        assert_eq!(load(&offline, &path).source, PricingSource::Offline);
+
+        // Past its TTL, or for another URL, the cache is stale but still used
+        let mut cache = PricingCache::load(&path).unwrap();
+        cache.fetched_at = Utc::now() - chrono::Duration::hours(25);
+        cache.save(&path).unwrap();
+        let stale = load(&config, &path);
+        assert_eq!(stale.source, PricingSource::Stale);
+        assert!(stale.models.contains_key("claude-test-1"));
+        let moved = PricingConfig {
+            url: Some("http://127.0.0.1:9/other.json".to_string()),
+            ttl_hours: Some(48),
+            ..config.clone()
+        };
+        assert_eq!(load(&moved, &path).source, PricingSource::Stale);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_failed_fetch_backs_off() {
+        let root = std::env::temp_dir().join(format!("ccline-pricing-fail-{}", std::process::id()));
+        let path = root.join(CACHE_FILE);
+        // Nothing listens on a port the OS just released
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+        let config = PricingConfig {
+            url: Some(format!("http://127.0.0.1:{}/prices.json", port)),
+            ..PricingConfig::default()
+        };
+
+        assert_eq!(load(&config, &path).source, PricingSource::Fallback);
+        assert!(!claim_fetch(&path));
+        assert!(refresh(
+            &PricingConfig {
+                offline: true,
+                ..config.clone()
+            },
+            &path
+        )
+        .is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
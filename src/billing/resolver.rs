@@ -0,0 +1,359 @@
+//! Matching model ids from transcripts to pricing table keys
+
+use std::fmt;
+
+/// How a pricing key matched the requested model id, best first
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum MatchKind {
+    /// The key is the model id itself
+    Exact,
+    /// Key and model id are the same after normalization
+    Normalized,
+    /// The model id is a more specific variant of the key, e.g. `gpt-5-codex-high`
+    Prefix,
+    /// The normalized model id contains the key on `-` boundaries
+    Contains,
+}
+
+impl MatchKind {
+    /// Name used in machine-readable output
+    pub fn name(self) -> &'static str {
+        match self {
+            MatchKind::Exact => "exact",
+            MatchKind::Normalized => "normalized",
+            MatchKind::Prefix => "prefix",
+            MatchKind::Contains => "contains",
+        }
+    }
+}
+
+impl fmt::Display for MatchKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MatchKind::Exact => write!(f, "exact key"),
+            MatchKind::Normalized => write!(f, "same model after normalization"),
+            MatchKind::Prefix => write!(f, "model id extends this key"),
+            MatchKind::Contains => write!(f, "model id contains this key"),
+        }
+    }
+}
+
+/// A key that matched, with its value and the reason
+#[derive(Debug, Clone)]
+pub struct Candidate<'a, T> {
+    pub key: &'a str,
+    pub entry: &'a T,
+    pub kind: MatchKind,
+}
+
+/// Every key matching a model id, ranked best first
+#[derive(Debug, Clone)]
+pub struct Resolution<'a, T> {
+    pub model: String,
+    pub normalized: String,
+    pub candidates: Vec<Candidate<'a, T>>,
+}
+
+impl<'a, T> Resolution<'a, T> {
+    /// The candidate that is used
+    pub fn chosen(&self) -> Option<&Candidate<'a, T>> {
+        self.candidates.first()
+    }
+}
+
+/// Reduce a model id to its bare model name
+///
+/// Lowercases, drops provider and route prefixes (`anthropic/`, `bedrock/`,
+/// `us.anthropic.`, ARN paths), Vertex `@version` and Bedrock `-v1:0` suffixes,
+/// context markers such as `[1m]`, `-latest` and trailing release dates, and
+/// writes dotted versions with dashes (`claude-3.5-sonnet` → `claude-3-5-sonnet`).
+pub fn normalize(model: &str) -> String {
+    let mut id = model.trim().to_ascii_lowercase();
+
+    if let Some(pos) = id.rfind('/') {
+        id.drain(..=pos);
+    }
+    if let Some(pos) = id.find("anthropic.") {
+        id.drain(..pos + "anthropic.".len());
+    }
+    for marker in ['@', '[', ':'] {
+        if let Some(pos) = id.find(marker) {
+            id.truncate(pos);
+        }
+    }
+    if let Some(stripped) = strip_numbered_suffix(&id, "-v") {
+        id = stripped;
+    }
+    if let Some(stripped) = id.strip_suffix("-latest") {
+        id = stripped.to_string();
+    }
+    id = strip_date(&id).to_string();
+
+    // Dots between digits are version separators
+    let chars: Vec<char> = id.chars().collect();
+    chars
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let between_digits = i > 0
+                && chars[i - 1].is_ascii_digit()
+                && chars.get(i + 1).is_some_and(|n| n.is_ascii_digit());
+            if c == '.' && between_digits {
+                '-'
+            } else {
+                c
+            }
+        })
+        .collect()
+}
+
+/// Rank every key of a model-keyed table (prices, context limits) that matches `model`
+///
+/// Candidates are ordered by match kind, then keys without a provider prefix,
+/// then the key closest in length to the model id, then the key itself, so the
+/// result never depends on map iteration order.
+pub fn resolve<'a, T: 'a>(
+    table: impl IntoIterator<Item = (&'a String, &'a T)>,
+    model: &str,
+) -> Resolution<'a, T> {
+    let normalized = normalize(model);
+    let mut candidates: Vec<Candidate<'a, T>> = table
+        .into_iter()
+        .filter_map(|(key, entry)| {
+            let kind = if key == model {
+                MatchKind::Exact
+            } else {
+                let key_normalized = normalize(key);
+                if key_normalized.is_empty() || normalized.is_empty() {
+                    return None;
+                } else if key_normalized == normalized {
+                    MatchKind::Normalized
+                } else if extends(&normalized, &key_normalized) {
+                    MatchKind::Prefix
+                } else if contains_words(&normalized, &key_normalized) {
+                    MatchKind::Contains
+                } else {
+                    return None;
+                }
+            };
+            Some(Candidate {
+                key: key.as_str(),
+                entry,
+                kind,
+            })
+        })
+        .collect();
+
+    candidates.sort_by(|a, b| {
+        let rank = |c: &Candidate<T>| {
+            (
+                c.kind,
+                c.key.contains('/'),
+                c.key.len().abs_diff(model.len()),
+            )
+        };
+        rank(a).cmp(&rank(b)).then_with(|| a.key.cmp(b.key))
+    });
+
+    Resolution {
+        model: model.to_string(),
+        normalized,
+        candidates,
+    }
+}
+
+/// Parts that name a setting of a model rather than another model, e.g. the
+/// reasoning effort in `gpt-5-codex-high`
+const VARIANT_SUFFIXES: &[&str] = &["minimal", "low", "medium", "high", "xhigh", "thinking"];
+
+/// `id` is `base` followed by variant parts only
+///
+/// Any other part names a different model: a newer version
+/// (`claude-opus-4-5` is not `claude-opus-4`) or another size (`gpt-5-nano`
+/// is not `gpt-5`).
+fn extends(id: &str, base: &str) -> bool {
+    id.strip_prefix(base)
+        .and_then(|rest| rest.strip_prefix('-'))
+        .is_some_and(only_variants)
+}
+
+/// `needle` occurs in `haystack` as whole `-`-separated parts, followed by
+/// nothing but variant parts
+fn contains_words(haystack: &str, needle: &str) -> bool {
+    haystack.match_indices(needle).any(|(start, _)| {
+        let rest = &haystack[start + needle.len()..];
+        (start == 0 || haystack[..start].ends_with('-'))
+            && (rest.is_empty() || rest.strip_prefix('-').is_some_and(only_variants))
+    })
+}
+
+fn only_variants(parts: &str) -> bool {
+    parts
+        .split('-')
+        .all(|part| VARIANT_SUFFIXES.contains(&part))
+}
+
+/// Strip a trailing `<prefix><digits>`, e.g. Bedrock's `-v1`
+fn strip_numbered_suffix(id: &str, prefix: &str) -> Option<String> {
+    let pos = id.rfind(prefix)?;
+    let digits = &id[pos + prefix.len()..];
+    (!digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit()))
+        .then(|| id[..pos].to_string())
+}
+
+/// Strip a trailing `-YYYYMMDD` or `-YYYY-MM-DD`
+fn strip_date(id: &str) -> &str {
+    let bytes = id.as_bytes();
+    let matches = |pattern: &[u8]| {
+        bytes.len() > pattern.len()
+            && bytes[bytes.len() - pattern.len()..]
+                .iter()
+                .zip(pattern)
+                .all(|(&b, &p)| {
+                    if p == b'9' {
+                        b.is_ascii_digit()
+                    } else {
+                        b == p
+                    }
+                })
+    };
+
+    for pattern in [&b"-2999-99-99"[..], &b"-29999999"[..]] {
+        if matches(pattern) {
+            return &id[..id.len() - pattern.len()];
+        }
+    }
+    id
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::billing::ModelPricing;
+    use std::collections::HashMap;
+
+    fn table(keys: &[&str]) -> HashMap<String, ModelPricing> {
+        keys.iter()
+            .map(|key| {
+                (
+                    key.to_string(),
+                    ModelPricing {
+                        model_name: key.to_string(),
+                        input_cost_per_1k: 0.0,
+                        output_cost_per_1k: 0.0,
+                        cache_creation_cost_per_1k: 0.0,
+                        cache_read_cost_per_1k: 0.0,
+                        context_limit: None,
+                        tiers: Vec::new(),
+                    },
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("claude-sonnet-4-20250514"), "claude-sonnet-4");
+        assert_eq!(
+            normalize("anthropic/claude-3.5-sonnet"),
+            "claude-3-5-sonnet"
+        );
+        assert_eq!(
+            normalize("arn:aws:bedrock:us-east-1:123:inference-profile/us.anthropic.claude-sonnet-4-20250514-v1:0"),
+            "claude-sonnet-4"
+        );
+        assert_eq!(
+            normalize("vertex_ai/claude-3-5-sonnet@20240620"),
+            "claude-3-5-sonnet"
+        );
+        assert_eq!(normalize("claude-sonnet-4-5[1m]"), "claude-sonnet-4-5");
+        assert_eq!(normalize("claude-3-5-haiku-latest"), "claude-3-5-haiku");
+        assert_eq!(normalize("o4-mini-2025-04-16"), "o4-mini");
+        assert_eq!(normalize("gpt-4.1"), "gpt-4-1");
+    }
+
+    #[test]
+    fn test_newer_versions_are_not_variants() {
+        let pricing = table(&["claude-opus-4-20250514", "claude-opus-4-1", "gpt-5"]);
+        let chosen = |model: &str| resolve(&pricing, model).chosen().map(|c| c.key);
+
+        assert_eq!(chosen("claude-opus-4-5-20251101"), None);
+        assert_eq!(chosen("claude-opus-4-5"), None);
+        assert_eq!(chosen("us.anthropic.claude-opus-4-5-20251101-v1:0"), None);
+        assert_eq!(chosen("claude-opus-4-1-20250805"), Some("claude-opus-4-1"));
+        assert_eq!(
+            chosen("claude-opus-4-thinking"),
+            Some("claude-opus-4-20250514")
+        );
+        assert_eq!(chosen("gpt-5-nano"), None);
+        assert_eq!(chosen("azure-gpt-5"), Some("gpt-5"));
+        assert_eq!(chosen("azure-gpt-5-nano"), None);
+    }
+
+    #[test]
+    fn test_resolve_ranks_and_breaks_ties() {
+        let pricing = table(&[
+            "claude-sonnet-4-20250514",
+            "anthropic/claude-sonnet-4-20250514",
+            "claude-sonnet-4-5",
+            "claude-sonnet-4-5-20250929",
+            "gpt-5-codex",
+            "gpt-5",
+        ]);
+
+        let chosen = |model: &str| {
+            let resolution = resolve(&pricing, model);
+            resolution.chosen().map(|c| (c.key.to_string(), c.kind))
+        };
+
+        assert_eq!(
+            chosen("claude-sonnet-4-20250514"),
+            Some(("claude-sonnet-4-20250514".to_string(), MatchKind::Exact))
+        );
+        // Provider-less keys win over prefixed ones with the same meaning
+        assert_eq!(
+            chosen("bedrock/us.anthropic.claude-sonnet-4-20250514-v1:0"),
+            Some((
+                "claude-sonnet-4-20250514".to_string(),
+                MatchKind::Normalized
+            ))
+        );
+        // Equal-rank keys are ordered by length distance, then by name
+        assert_eq!(
+            chosen("claude-sonnet-4-5-20250929[1m]").unwrap().0,
+            "claude-sonnet-4-5-20250929"
+        );
+        assert_eq!(
+            chosen("gpt-5-codex-high"),
+            Some(("gpt-5-codex".to_string(), MatchKind::Prefix))
+        );
+        // A shorter model id never picks a longer, different model
+        assert_eq!(chosen("claude-sonnet"), None);
+        assert_eq!(chosen("llama-3"), None);
+        // Nor does a newer version or another size pick an older or bigger one
+        assert_eq!(chosen("gpt-5-nano"), None);
+        assert_eq!(chosen("gpt-5-mini-high"), None);
+        assert_eq!(chosen("gpt-5-codex-mini"), None);
+        assert_eq!(
+            chosen("gpt-5-codex-high-thinking").unwrap().0,
+            "gpt-5-codex"
+        );
+
+        // The ranking is the same whatever the map's iteration order
+        for _ in 0..5 {
+            let shuffled: HashMap<_, _> = pricing.clone().into_iter().collect();
+            let keys: Vec<&str> = resolve(&shuffled, "claude-sonnet-4")
+                .candidates
+                .iter()
+                .map(|c| c.key)
+                .collect();
+            assert_eq!(
+                keys,
+                vec![
+                    "claude-sonnet-4-20250514",
+                    "anthropic/claude-sonnet-4-20250514"
+                ]
+            );
+        }
+    }
+}
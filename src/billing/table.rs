@@ -0,0 +1,451 @@
+use super::pricing_cache::{LoadedPricing, PricingSource};
+use super::resolver::Resolution;
+use super::{ModelPricing, PricingConfig};
+use crate::output::{self, csv_field, round_cost, Csv, OutputFormat};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Where an effective model price comes from
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceSource {
+    /// LiteLLM table, scaled by the configured multiplier
+    LiteLlm(PricingSource),
+    /// Built-in table, scaled by the configured multiplier
+    Builtin,
+    /// `[pricing.models]` entry replacing some of the fetched prices
+    Override,
+    /// `[pricing.models]` entry for a model missing from the fetched table
+    Custom,
+    /// `[pricing.aliases]` entry priced as the named model
+    Alias(String),
+}
+
+impl fmt::Display for PriceSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PriceSource::LiteLlm(PricingSource::Fresh) => write!(f, "litellm (cached)"),
+            PriceSource::LiteLlm(PricingSource::Stale) => write!(f, "litellm (stale cache)"),
+            PriceSource::LiteLlm(PricingSource::Offline) => write!(f, "litellm (offline cache)"),
+            PriceSource::LiteLlm(PricingSource::Fetched) => write!(f, "litellm (downloaded)"),
+            PriceSource::LiteLlm(PricingSource::Fallback) | PriceSource::Builtin => {
+                write!(f, "built-in")
+            }
+            PriceSource::Override => write!(f, "override"),
+            PriceSource::Custom => write!(f, "custom"),
+            PriceSource::Alias(target) => write!(f, "alias of {}", target),
+        }
+    }
+}
+
+/// Effective model prices after applying the `[pricing]` configuration
+#[derive(Debug, Clone, Default)]
+pub struct PricingTable {
+    models: HashMap<String, ModelPricing>,
+    sources: HashMap<String, PriceSource>,
+    unresolved_aliases: Vec<(String, String)>,
+}
+
+impl PricingTable {
+    /// Scale the loaded prices by the multiplier, then apply model overrides and aliases
+    ///
+    /// Overrides are taken as final prices and are not scaled. Aliases are resolved
+    /// in key order against the table built so far, overrides included.
+    pub fn build(loaded: LoadedPricing, config: &PricingConfig) -> Self {
+        let base = match loaded.source {
+            PricingSource::Fallback => PriceSource::Builtin,
+            source => PriceSource::LiteLlm(source),
+        };
+        let multiplier = config.multiplier();
+
+        let mut table = PricingTable::default();
+        for (name, mut pricing) in loaded.models {
+            pricing.scale(multiplier);
+            table.sources.insert(name.clone(), base.clone());
+            table.models.insert(name, pricing);
+        }
+
+        for (name, price) in &config.models {
+            let source = match table.models.get_mut(name) {
+                Some(pricing) => {
+                    price.apply(pricing);
+                    PriceSource::Override
+                }
+                None => {
+                    let mut pricing = ModelPricing {
+                        model_name: name.clone(),
+                        input_cost_per_1k: 0.0,
+                        output_cost_per_1k: 0.0,
+                        cache_creation_cost_per_1k: 0.0,
+                        cache_read_cost_per_1k: 0.0,
+                        context_limit: None,
+                        tiers: Vec::new(),
+                    };
+                    price.apply(&mut pricing);
+                    table.models.insert(name.clone(), pricing);
+                    PriceSource::Custom
+                }
+            };
+            table.sources.insert(name.clone(), source);
+        }
+
+        for (alias, target) in &config.aliases {
+            match ModelPricing::get_model_pricing(&table.models, target).cloned() {
+                Some(pricing) => {
+                    table.sources.insert(
+                        alias.clone(),
+                        PriceSource::Alias(pricing.model_name.clone()),
+                    );
+                    table.models.insert(alias.clone(), pricing);
+                }
+                None => table
+                    .unresolved_aliases
+                    .push((alias.clone(), target.clone())),
+            }
+        }
+
+        table
+    }
+
+    pub fn models(&self) -> &HashMap<String, ModelPricing> {
+        &self.models
+    }
+
+    pub fn into_models(self) -> HashMap<String, ModelPricing> {
+        self.models
+    }
+
+    pub fn source(&self, model: &str) -> Option<&PriceSource> {
+        self.sources.get(model)
+    }
+
+    /// Aliases whose target matched no priced model, as `(alias, target)`
+    pub fn unresolved_aliases(&self) -> &[(String, String)] {
+        &self.unresolved_aliases
+    }
+
+    /// Every priced model id sorted by name, with its price and source
+    pub fn entries(&self) -> Vec<(&str, &ModelPricing, &PriceSource)> {
+        let mut names: Vec<&String> = self.models.keys().collect();
+        names.sort();
+        names
+            .into_iter()
+            .map(|name| (name.as_str(), &self.models[name], &self.sources[name]))
+            .collect()
+    }
+}
+
+/// `--show-pricing` in machine-readable form
+#[derive(Debug, Clone, Serialize)]
+pub struct PricingOutput {
+    /// Factor applied to fetched and built-in prices
+    pub multiplier: f64,
+    pub models: Vec<PriceRecord>,
+}
+
+/// Price of one model in USD per million tokens
+#[derive(Debug, Clone, Serialize)]
+pub struct PriceRecord {
+    pub model: String,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+    pub context_limit: Option<u64>,
+    pub source: String,
+    /// Rates above a prompt size, with the base rate filled in where a tier keeps it
+    pub tiers: Vec<TierRecord>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct TierRecord {
+    pub above_tokens: u64,
+    pub input_per_mtok: f64,
+    pub output_per_mtok: f64,
+    pub cache_write_per_mtok: f64,
+    pub cache_read_per_mtok: f64,
+}
+
+impl PriceRecord {
+    pub fn new(model: &str, pricing: &ModelPricing, source: &PriceSource) -> Self {
+        let per_million = |per_1k: f64| round_cost(per_1k * 1000.0);
+        Self {
+            model: model.to_string(),
+            input_per_mtok: per_million(pricing.input_cost_per_1k),
+            output_per_mtok: per_million(pricing.output_cost_per_1k),
+            cache_write_per_mtok: per_million(pricing.cache_creation_cost_per_1k),
+            cache_read_per_mtok: per_million(pricing.cache_read_cost_per_1k),
+            context_limit: pricing.context_limit,
+            source: source.to_string(),
+            tiers: pricing
+                .tiers
+                .iter()
+                .map(|tier| TierRecord {
+                    above_tokens: tier.above_tokens,
+                    input_per_mtok: per_million(
+                        tier.input_cost_per_1k.unwrap_or(pricing.input_cost_per_1k),
+                    ),
+                    output_per_mtok: per_million(
+                        tier.output_cost_per_1k
+                            .unwrap_or(pricing.output_cost_per_1k),
+                    ),
+                    cache_write_per_mtok: per_million(
+                        tier.cache_creation_cost_per_1k
+                            .unwrap_or(pricing.cache_creation_cost_per_1k),
+                    ),
+                    cache_read_per_mtok: per_million(
+                        tier.cache_read_cost_per_1k
+                            .unwrap_or(pricing.cache_read_cost_per_1k),
+                    ),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl PricingOutput {
+    pub fn new(table: &PricingTable, multiplier: f64) -> Self {
+        let models = table
+            .entries()
+            .into_iter()
+            .map(|(name, pricing, source)| PriceRecord::new(name, pricing, source))
+            .collect();
+        Self { multiplier, models }
+    }
+
+    /// Text in `format`, or `None` for [`OutputFormat::Table`]
+    ///
+    /// CSV has a row per model followed by a row per tier, which fills in `above_tokens`.
+    pub fn render(&self, format: OutputFormat) -> Option<String> {
+        output::render_document(format, "pricing", self, || {
+            let mut csv = Csv::new([
+                "model",
+                "above_tokens",
+                "input_per_mtok",
+                "output_per_mtok",
+                "cache_write_per_mtok",
+                "cache_read_per_mtok",
+                "context_limit",
+                "source",
+            ]);
+            for record in &self.models {
+                csv.push([
+                    record.model.clone(),
+                    String::new(),
+                    record.input_per_mtok.to_string(),
+                    record.output_per_mtok.to_string(),
+                    record.cache_write_per_mtok.to_string(),
+                    record.cache_read_per_mtok.to_string(),
+                    csv_field(record.context_limit),
+                    record.source.clone(),
+                ]);
+                for tier in &record.tiers {
+                    csv.push([
+                        record.model.clone(),
+                        tier.above_tokens.to_string(),
+                        tier.input_per_mtok.to_string(),
+                        tier.output_per_mtok.to_string(),
+                        tier.cache_write_per_mtok.to_string(),
+                        tier.cache_read_per_mtok.to_string(),
+                        csv_field(record.context_limit),
+                        record.source.clone(),
+                    ]);
+                }
+            }
+            csv
+        })
+    }
+}
+
+/// `ccline pricing resolve` in machine-readable form
+#[derive(Debug, Clone, Serialize)]
+pub struct ResolutionOutput {
+    /// Model id as given
+    pub model: String,
+    pub normalized: String,
+    /// Key of the price entry that is used; `None` leaves the model uncosted
+    pub chosen: Option<String>,
+    /// Every matching price entry, best first
+    pub candidates: Vec<CandidateRecord>,
+}
+
+/// A price entry matching the model id, with the reason it matched
+#[derive(Debug, Clone, Serialize)]
+pub struct CandidateRecord {
+    /// `exact`, `normalized`, `prefix` or `contains`
+    #[serde(rename = "match")]
+    pub match_kind: &'static str,
+    #[serde(flatten)]
+    pub price: PriceRecord,
+}
+
+impl ResolutionOutput {
+    pub fn new(table: &PricingTable, resolution: &Resolution<'_, ModelPricing>) -> Self {
+        let candidates = resolution
+            .candidates
+            .iter()
+            .filter_map(|candidate| {
+                let source = table.source(candidate.key)?;
+                Some(CandidateRecord {
+                    match_kind: candidate.kind.name(),
+                    price: PriceRecord::new(candidate.key, candidate.entry, source),
+                })
+            })
+            .collect();
+        Self {
+            model: resolution.model.clone(),
+            normalized: resolution.normalized.clone(),
+            chosen: resolution.chosen().map(|chosen| chosen.key.to_string()),
+            candidates,
+        }
+    }
+
+    /// Text in `format`, or `None` for [`OutputFormat::Table`]
+    ///
+    /// CSV has a row per candidate, best first, without tiers.
+    pub fn render(&self, format: OutputFormat) -> Option<String> {
+        output::render_document(format, "resolution", self, || {
+            let mut csv = Csv::new([
+                "rank",
+                "key",
+                "match",
+                "input_per_mtok",
+                "output_per_mtok",
+                "cache_write_per_mtok",
+                "cache_read_per_mtok",
+                "context_limit",
+                "source",
+            ]);
+            for (i, candidate) in self.candidates.iter().enumerate() {
+                let price = &candidate.price;
+                csv.push([
+                    (i + 1).to_string(),
+                    price.model.clone(),
+                    candidate.match_kind.to_string(),
+                    price.input_per_mtok.to_string(),
+                    price.output_per_mtok.to_string(),
+                    price.cache_write_per_mtok.to_string(),
+                    price.cache_read_per_mtok.to_string(),
+                    csv_field(price.context_limit),
+                    price.source.clone(),
+                ]);
+            }
+            csv
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::billing::{resolver, PriceOverride};
+
+    fn close(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-12
+    }
+
+    #[test]
+    fn test_overrides_aliases_and_multiplier() {
+        let loaded = LoadedPricing {
+            models: ModelPricing::fallback_pricing(),
+            source: PricingSource::Fallback,
+        };
+        let mut config = PricingConfig {
+            multiplier: Some(0.5),
+            ..PricingConfig::default()
+        };
+        config.models.insert(
+            "claude-opus-4-1".to_string(),
+            PriceOverride {
+                output: Some(60.0),
+                ..PriceOverride::default()
+            },
+        );
+        config.models.insert(
+            "llama-gateway".to_string(),
+            PriceOverride {
+                input: Some(1.0),
+                output: Some(2.0),
+                ..PriceOverride::default()
+            },
+        );
+        config.aliases.insert(
+            "arn:aws:bedrock:us-east-1:123:inference-profile/us.anthropic.claude-sonnet-4"
+                .to_string(),
+            "claude-sonnet-4-20250514".to_string(),
+        );
+        config
+            .aliases
+            .insert("internal-model".to_string(), "no-such-model".to_string());
+
+        let table = PricingTable::build(loaded, &config);
+        let models = table.models();
+
+        // Fetched prices are scaled, overridden fields are taken as-is
+        let sonnet = &models["claude-sonnet-4-20250514"];
+        assert!(close(sonnet.input_cost_per_1k, 0.0015));
+        let opus = &models["claude-opus-4-1"];
+        assert!(close(opus.input_cost_per_1k, 0.0075));
+        assert!(close(opus.output_cost_per_1k, 0.06));
+        assert_eq!(
+            table.source("claude-opus-4-1"),
+            Some(&PriceSource::Override)
+        );
+
+        // Unknown models are added with unset prices at zero
+        let custom = &models["llama-gateway"];
+        assert!(close(custom.input_cost_per_1k, 0.001));
+        assert!(close(custom.cache_read_cost_per_1k, 0.0));
+        assert_eq!(table.source("llama-gateway"), Some(&PriceSource::Custom));
+
+        // Aliases resolve to an exact key holding the target's price
+        let arn = "arn:aws:bedrock:us-east-1:123:inference-profile/us.anthropic.claude-sonnet-4";
+        let aliased = ModelPricing::get_model_pricing(models, arn).unwrap();
+        assert!(close(aliased.input_cost_per_1k, 0.0015));
+        assert_eq!(
+            table.source(arn).unwrap().to_string(),
+            "alias of claude-sonnet-4-20250514"
+        );
+        assert_eq!(
+            table.unresolved_aliases(),
+            &[("internal-model".to_string(), "no-such-model".to_string())]
+        );
+        assert_eq!(table.source("o3").unwrap().to_string(), "built-in");
+    }
+
+    #[test]
+    fn test_resolution_output() {
+        let loaded = LoadedPricing {
+            models: ModelPricing::fallback_pricing(),
+            source: PricingSource::Fallback,
+        };
+        let table = PricingTable::build(loaded, &PricingConfig::default());
+
+        let resolution = resolver::resolve(table.models(), "gpt-5-codex-high");
+        let output = ResolutionOutput::new(&table, &resolution);
+        let json: serde_json::Value =
+            serde_json::from_str(&output.render(OutputFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["kind"], "resolution");
+        assert_eq!(json["model"], "gpt-5-codex-high");
+        assert_eq!(json["chosen"], "gpt-5-codex");
+        assert_eq!(json["candidates"][0]["model"], "gpt-5-codex");
+        assert_eq!(json["candidates"][0]["match"], "prefix");
+        assert_eq!(json["candidates"][0]["source"], "built-in");
+        assert!(output.render(OutputFormat::Table).is_none());
+
+        let csv = output.render(OutputFormat::Csv).unwrap();
+        let mut lines = csv.lines();
+        assert_eq!(
+            lines.next().unwrap(),
+            "rank,key,match,input_per_mtok,output_per_mtok,cache_write_per_mtok,cache_read_per_mtok,context_limit,source"
+        );
+        assert!(lines.next().unwrap().starts_with("1,gpt-5-codex,prefix,"));
+
+        let resolution = resolver::resolve(table.models(), "my-gateway-model");
+        let output = ResolutionOutput::new(&table, &resolution);
+        let line: serde_json::Value =
+            serde_json::from_str(&output.render(OutputFormat::Ndjson).unwrap()).unwrap();
+        assert_eq!(line["chosen"], serde_json::Value::Null);
+        assert_eq!(line["candidates"], serde_json::json!([]));
+    }
+}
@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 /// Session usage data aggregated from transcript files
 #[derive(Debug, Clone, Default)]
@@ -15,7 +16,7 @@ pub struct SessionUsage {
 }
 
 /// Single usage record from a transcript entry
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UsageEntry {
     pub timestamp: DateTime<Utc>,
     pub input_tokens: u32,
@@ -54,6 +55,16 @@ pub enum BlockStartSource {
     Fixed,
 }
 
+impl BlockStartSource {
+    pub fn name(&self) -> &'static str {
+        match self {
+            BlockStartSource::Auto => "auto",
+            BlockStartSource::Manual => "manual",
+            BlockStartSource::Fixed => "fixed",
+        }
+    }
+}
+
 /// Burn rate calculation
 #[derive(Debug, Clone)]
 pub struct BurnRate {
@@ -72,7 +83,8 @@ pub enum BurnRateTrend {
 }
 
 /// Burn rate thresholds for indicator display
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct BurnRateThresholds {
     pub high: f64,   // Default 5000 tokens/minute
     pub medium: f64, // Default 2000 tokens/minute
@@ -108,6 +120,162 @@ impl BurnRateThresholds {
     }
 }
 
+/// Where pricing comes from and how long a fetched copy stays fresh
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PricingConfig {
+    /// Hours a fetched pricing table is used before it is refreshed (default 24)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ttl_hours: Option<u64>,
+    /// Never touch the network; use the on-disk cache or the built-in table
+    pub offline: bool,
+    /// LiteLLM-format pricing JSON to fetch instead of the upstream file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    /// Factor applied to every fetched or built-in price, e.g. 0.8 for a 20% discount
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub multiplier: Option<f64>,
+    /// Model ids priced as another model, e.g. a Bedrock ARN mapped to a `claude-*` key
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub aliases: BTreeMap<String, String>,
+    /// Per-model prices replacing or adding to the fetched table
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub models: BTreeMap<String, PriceOverride>,
+}
+
+/// Prices for one model in USD per million tokens; unset fields keep the fetched price
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PriceOverride {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub input: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_creation: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cache_read: Option<f64>,
+}
+
+impl PriceOverride {
+    /// Apply the set fields on top of `base`
+    ///
+    /// An overridden rate applies at every prompt size, so it also replaces the
+    /// matching long-context tier rates.
+    pub fn apply(&self, base: &mut ModelPricing) {
+        let per_1k = |per_million: f64| per_million / 1000.0;
+        if let Some(input) = self.input {
+            base.input_cost_per_1k = per_1k(input);
+            base.tiers
+                .iter_mut()
+                .for_each(|t| t.input_cost_per_1k = None);
+        }
+        if let Some(output) = self.output {
+            base.output_cost_per_1k = per_1k(output);
+            base.tiers
+                .iter_mut()
+                .for_each(|t| t.output_cost_per_1k = None);
+        }
+        if let Some(cache_creation) = self.cache_creation {
+            base.cache_creation_cost_per_1k = per_1k(cache_creation);
+            base.tiers
+                .iter_mut()
+                .for_each(|t| t.cache_creation_cost_per_1k = None);
+        }
+        if let Some(cache_read) = self.cache_read {
+            base.cache_read_cost_per_1k = per_1k(cache_read);
+            base.tiers
+                .iter_mut()
+                .for_each(|t| t.cache_read_cost_per_1k = None);
+        }
+    }
+}
+
+impl PricingConfig {
+    pub fn multiplier(&self) -> f64 {
+        self.multiplier.unwrap_or(1.0)
+    }
+
+    pub fn ttl(&self) -> std::time::Duration {
+        std::time::Duration::from_secs(self.ttl_hours.unwrap_or(DEFAULT_PRICING_TTL_HOURS) * 3600)
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_deref().unwrap_or(LITELLM_PRICING_URL)
+    }
+}
+
+/// Context window sizes and the usage segment's thresholds
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContextConfig {
+    /// Limit for models nothing else is known about (default 200000)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_limit: Option<u64>,
+    /// Per-model limits, matched like pricing keys
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub limits: BTreeMap<String, u64>,
+    /// Usage percentage at which the usage segment turns to the warning style (default 60)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<f64>,
+    /// Usage percentage at which the usage segment turns to the critical style (default 75)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub critical: Option<f64>,
+    /// Share of the context window at which Claude Code auto-compacts (default 0.8)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compact_ratio: Option<f64>,
+    /// Show the tokens left until auto-compact in the usage segment's default format
+    pub show_until_compact: bool,
+}
+
+impl ContextConfig {
+    /// Configured default window; a zero limit is ignored
+    pub fn default_limit(&self) -> u64 {
+        self.default_limit
+            .filter(|&limit| limit > 0)
+            .unwrap_or(DEFAULT_CONTEXT_LIMIT)
+    }
+
+    pub fn warning(&self) -> f64 {
+        self.warning.unwrap_or(DEFAULT_CONTEXT_WARNING)
+    }
+
+    pub fn critical(&self) -> f64 {
+        self.critical.unwrap_or(DEFAULT_CONTEXT_CRITICAL)
+    }
+
+    pub fn compact_ratio(&self) -> f64 {
+        self.compact_ratio.unwrap_or(DEFAULT_COMPACT_RATIO)
+    }
+
+    /// Tokens of a `limit`-sized window that can be used before auto-compact
+    pub fn compact_at(&self, limit: u64) -> u64 {
+        (limit as f64 * self.compact_ratio()).round() as u64
+    }
+}
+
+/// Settings of the billing block segment
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BlockConfig {
+    /// Spend per 5-hour block in USD; a projection above it is flagged
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub budget: Option<f64>,
+}
+
+/// Context window assumed when nothing else is known
+pub const DEFAULT_CONTEXT_LIMIT: u64 = 200_000;
+
+const DEFAULT_CONTEXT_WARNING: f64 = 60.0;
+const DEFAULT_CONTEXT_CRITICAL: f64 = 75.0;
+const DEFAULT_COMPACT_RATIO: f64 = 0.8;
+
+/// LiteLLM's model pricing and context window data URL
+pub const LITELLM_PRICING_URL: &str =
+    "https://raw.githubusercontent.com/BerriAI/litellm/main/model_prices_and_context_window.json";
+
+const DEFAULT_PRICING_TTL_HOURS: u64 = 24;
+
 /// Model pricing information
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModelPricing {
@@ -116,25 +284,103 @@ pub struct ModelPricing {
     pub output_cost_per_1k: f64,
     pub cache_creation_cost_per_1k: f64,
     pub cache_read_cost_per_1k: f64,
+    /// Maximum prompt size in tokens, when the pricing source knows it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context_limit: Option<u64>,
+    /// Higher rates for long prompts, sorted by ascending threshold
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<PriceTier>,
+}
+
+/// Rates charged for a request whose prompt is larger than `above_tokens`
+///
+/// Unset rates fall back to the model's base rate.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PriceTier {
+    pub above_tokens: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input_cost_per_1k: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output_cost_per_1k: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_creation_cost_per_1k: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cache_read_cost_per_1k: Option<f64>,
 }
 
 impl ModelPricing {
     /// Calculate cost for a usage entry
+    ///
+    /// The prompt size (input plus cache creation and cache read tokens) selects
+    /// the highest tier it exceeds, and that tier's rates apply to every token of
+    /// the entry, as providers bill long-context requests.
     pub fn calculate_cost(&self, entry: &UsageEntry) -> f64 {
-        let input_cost = (entry.input_tokens as f64 / 1000.0) * self.input_cost_per_1k;
-        let output_cost = (entry.output_tokens as f64 / 1000.0) * self.output_cost_per_1k;
-        let cache_creation_cost =
-            (entry.cache_creation_tokens as f64 / 1000.0) * self.cache_creation_cost_per_1k;
-        let cache_read_cost =
-            (entry.cache_read_tokens as f64 / 1000.0) * self.cache_read_cost_per_1k;
+        let prompt_tokens = entry.input_tokens as u64
+            + entry.cache_creation_tokens as u64
+            + entry.cache_read_tokens as u64;
+        let tier = self
+            .tiers
+            .iter()
+            .rev()
+            .find(|tier| prompt_tokens > tier.above_tokens);
+        let rate = |base: f64, tiered: Option<f64>| tier.and(tiered).unwrap_or(base);
+
+        let input_cost = (entry.input_tokens as f64 / 1000.0)
+            * rate(
+                self.input_cost_per_1k,
+                tier.and_then(|t| t.input_cost_per_1k),
+            );
+        let output_cost = (entry.output_tokens as f64 / 1000.0)
+            * rate(
+                self.output_cost_per_1k,
+                tier.and_then(|t| t.output_cost_per_1k),
+            );
+        let cache_creation_cost = (entry.cache_creation_tokens as f64 / 1000.0)
+            * rate(
+                self.cache_creation_cost_per_1k,
+                tier.and_then(|t| t.cache_creation_cost_per_1k),
+            );
+        let cache_read_cost = (entry.cache_read_tokens as f64 / 1000.0)
+            * rate(
+                self.cache_read_cost_per_1k,
+                tier.and_then(|t| t.cache_read_cost_per_1k),
+            );
 
         input_cost + output_cost + cache_creation_cost + cache_read_cost
     }
+
+    /// Multiply every rate, tiered ones included, by `factor`
+    pub fn scale(&mut self, factor: f64) {
+        self.input_cost_per_1k *= factor;
+        self.output_cost_per_1k *= factor;
+        self.cache_creation_cost_per_1k *= factor;
+        self.cache_read_cost_per_1k *= factor;
+        for tier in &mut self.tiers {
+            for rate in [
+                &mut tier.input_cost_per_1k,
+                &mut tier.output_cost_per_1k,
+                &mut tier.cache_creation_cost_per_1k,
+                &mut tier.cache_read_cost_per_1k,
+            ]
+            .into_iter()
+            .flatten()
+            {
+                *rate *= factor;
+            }
+        }
+    }
 }
 
 impl SessionUsage {
     /// Calculate total cost given pricing
+    ///
+    /// Entries are priced one by one so long-context tiers apply per request;
+    /// without entries the totals are priced at the base rates.
     pub fn calculate_cost(&self, pricing: &ModelPricing) -> f64 {
+        if !self.entries.is_empty() {
+            return self.entries.iter().map(|e| pricing.calculate_cost(e)).sum();
+        }
+
         let input_cost = (self.total_input_tokens as f64 / 1000.0) * pricing.input_cost_per_1k;
         let output_cost = (self.total_output_tokens as f64 / 1000.0) * pricing.output_cost_per_1k;
         let cache_creation_cost =
@@ -1,4 +1,8 @@
-use clap::Parser;
+use crate::config::ProviderKind;
+use crate::output::OutputFormat;
+use crate::report::{ReportOptions, ReportTimezone, SessionSort, WeekStart};
+use chrono::NaiveDate;
+use clap::{Args, Parser, Subcommand};
 
 #[derive(Parser, Debug)]
 #[command(name = "CCometixLine (ccline)")]
@@ -14,26 +18,57 @@ use clap::Parser;
     )
 )]
 pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// Configuration file path
-    #[arg(short, long)]
+    #[arg(short, long, global = true)]
     pub config: Option<String>,
 
-    /// Theme selection
-    #[arg(short, long, default_value = "dark")]
-    pub theme: String,
+    /// Theme selection (default: dark)
+    #[arg(short, long)]
+    pub theme: Option<String>,
+
+    /// Maximum statusline width in columns (0 disables truncation; defaults to $COLUMNS or the terminal size)
+    #[arg(long, value_name = "COLUMNS")]
+    pub width: Option<usize>,
+
+    /// Output of reports, pricing resolve, --show-pricing and the block commands: table, json, ndjson or csv
+    #[arg(long, global = true, value_name = "FORMAT", default_value = "table")]
+    pub format: OutputFormat,
+
+    /// Never fetch pricing over the network; use the cached or built-in table
+    #[arg(long, global = true)]
+    pub offline: bool,
+
+    /// Download the pricing table now and store it in the pricing cache
+    #[arg(long, conflicts_with = "offline")]
+    pub refresh_pricing: bool,
+
+    /// Print the effective price of every model and where it comes from
+    #[arg(long)]
+    pub show_pricing: bool,
 
     /// Enable TUI configuration mode
     #[arg(long)]
     pub configure: bool,
 
-    /// Print default configuration
+    /// Print the effective configuration
     #[arg(long)]
     pub print_config: bool,
 
+    /// With --print-config, annotate every value with the layer it came from
+    #[arg(long, requires = "print_config")]
+    pub resolved: bool,
+
     /// Validate configuration file
     #[arg(long)]
     pub validate: bool,
 
+    /// Discard the transcript index and re-parse every transcript file
+    #[arg(long)]
+    pub rebuild_index: bool,
+
     /// Update to the latest version
     #[arg(long)]
     pub update: bool,
@@ -55,7 +90,173 @@ pub struct Cli {
     pub show_block_status: bool,
 }
 
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// Inspect model pricing
+    Pricing {
+        #[command(subcommand)]
+        action: PricingCommand,
+    },
+    /// Summarize token usage and cost across all transcripts
+    Report {
+        #[command(subcommand)]
+        kind: ReportCommand,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PricingCommand {
+    /// Show which price entry a model id resolves to and why
+    Resolve {
+        /// Model id as it appears in transcripts, e.g. `us.anthropic.claude-sonnet-4-20250514-v1:0`
+        model: String,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ReportCommand {
+    /// Usage per day
+    Daily(ReportArgs),
+    /// Usage per week
+    Weekly(WeeklyArgs),
+    /// Usage per calendar month with month-over-month changes
+    Monthly(ReportArgs),
+    /// Usage per session with its project directory and active span
+    Sessions(SessionsArgs),
+    /// Usage per 5-hour billing block, including the gaps between blocks
+    Blocks(BlocksArgs),
+}
+
+impl ReportCommand {
+    pub fn args(&self) -> &ReportArgs {
+        match self {
+            ReportCommand::Daily(args) | ReportCommand::Monthly(args) => args,
+            ReportCommand::Weekly(weekly) => &weekly.report,
+            ReportCommand::Sessions(sessions) => &sessions.report,
+            ReportCommand::Blocks(blocks) => &blocks.report,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct ReportArgs {
+    /// First day to include (YYYY-MM-DD or YYYYMMDD)
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub since: Option<NaiveDate>,
+
+    /// Last day to include (YYYY-MM-DD or YYYYMMDD)
+    #[arg(long, value_name = "DATE", value_parser = parse_date)]
+    pub until: Option<NaiveDate>,
+
+    /// Timezone that days are counted in: local, utc or an offset such as +09:00
+    #[arg(long, value_name = "TZ", default_value = "local")]
+    pub timezone: ReportTimezone,
+
+    /// Only include usage of one provider (claude or codex)
+    #[arg(long, value_parser = parse_provider)]
+    pub provider: Option<ProviderKind>,
+
+    /// Add a row per provider and model under every period
+    #[arg(long)]
+    pub breakdown: bool,
+}
+
+impl ReportArgs {
+    pub fn options(&self, week_start: WeekStart) -> ReportOptions {
+        ReportOptions {
+            since: self.since,
+            until: self.until,
+            timezone: self.timezone,
+            provider: self.provider,
+            week_start,
+        }
+    }
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct WeeklyArgs {
+    #[command(flatten)]
+    pub report: ReportArgs,
+
+    /// First day of the week (default: `[report] week_start`, or monday)
+    #[arg(long, value_name = "DAY")]
+    pub week_start: Option<WeekStart>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct SessionsArgs {
+    #[command(flatten)]
+    pub report: ReportArgs,
+
+    /// Order by last, first, duration, tokens, cost or project
+    #[arg(long, value_name = "KEY", default_value = "last")]
+    pub sort: SessionSort,
+
+    /// Reverse the sort order
+    #[arg(long)]
+    pub reverse: bool,
+
+    /// Only sessions whose project directory contains this text
+    #[arg(long, value_name = "PATH")]
+    pub project: Option<String>,
+}
+
+#[derive(Args, Debug, Clone)]
+pub struct BlocksArgs {
+    #[command(flatten)]
+    pub report: ReportArgs,
+
+    /// Only blocks that started in the last N days
+    #[arg(long, value_name = "N")]
+    pub days: Option<i64>,
+
+    /// Redraw the active block in place until interrupted
+    #[arg(long)]
+    pub live: bool,
+
+    /// Seconds between `--live` refreshes
+    #[arg(
+        long,
+        value_name = "SECS",
+        default_value_t = 5,
+        value_parser = clap::value_parser!(u64).range(1..),
+        requires = "live"
+    )]
+    pub interval: u64,
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y%m%d"))
+        .map_err(|_| format!("invalid date `{}` (expected YYYY-MM-DD)", value))
+}
+
+fn parse_provider(value: &str) -> Result<ProviderKind, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "claude" => Ok(ProviderKind::Claude),
+        "codex" => Ok(ProviderKind::Codex),
+        _ => Err(format!(
+            "unknown provider `{}` (expected claude or codex)",
+            value
+        )),
+    }
+}
+
 impl Cli {
+    /// Whether the command that runs honours `--format`
+    pub fn supports_format(&self) -> bool {
+        match &self.command {
+            // `ccline report` and `ccline pricing resolve`
+            Some(_) => true,
+            None => {
+                self.show_pricing
+                    || self.set_block_start.is_some()
+                    || self.clear_block_start
+                    || self.show_block_status
+            }
+        }
+    }
+
     pub fn parse_args() -> Self {
         Self::parse()
     }
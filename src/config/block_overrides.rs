@@ -4,6 +4,7 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::PathBuf;
 
+use crate::output::{self, csv_field, timestamp, Csv, OutputFormat};
 use crate::utils::resolve_config_dir;
 
 /// Block override configuration for a specific date
@@ -30,6 +31,74 @@ impl BlockOverride {
     }
 }
 
+/// An override together with the date it applies to
+#[derive(Debug, Clone, Serialize)]
+pub struct DatedOverride {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub block: BlockOverride,
+}
+
+/// `--show-block-status` in machine-readable form
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockStatus {
+    pub config_path: PathBuf,
+    pub today: NaiveDate,
+    /// Override for `today`, if one is set
+    pub today_override: Option<BlockOverride>,
+    /// Every stored override, oldest date first
+    pub overrides: Vec<DatedOverride>,
+}
+
+impl BlockStatus {
+    /// Text in `format`, or `None` for [`OutputFormat::Table`]; CSV lists the overrides
+    pub fn render(&self, format: OutputFormat) -> Option<String> {
+        output::render_document(format, "block_status", self, || {
+            let mut csv = Csv::new(["date", "start_time", "source", "created_at", "notes"]);
+            for dated in &self.overrides {
+                csv.push([
+                    dated.date.to_string(),
+                    timestamp(dated.block.start_time),
+                    dated.block.source.clone(),
+                    timestamp(dated.block.created_at),
+                    dated.block.notes.clone().unwrap_or_default(),
+                ]);
+            }
+            csv
+        })
+    }
+}
+
+/// Outcome of `--set-block-start` or `--clear-block-start`
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockStartChange {
+    /// `set` or `clear`
+    pub action: &'static str,
+    pub date: NaiveDate,
+    /// New start time; `None` when clearing
+    pub start_time: Option<DateTime<Utc>>,
+    /// `false` when clearing a date that had no override
+    pub changed: bool,
+    pub config_path: PathBuf,
+}
+
+impl BlockStartChange {
+    /// Text in `format`, or `None` for [`OutputFormat::Table`]
+    pub fn render(&self, format: OutputFormat) -> Option<String> {
+        output::render_document(format, "block_start", self, || {
+            let mut csv = Csv::new(["action", "date", "start_time", "changed", "config_path"]);
+            csv.push([
+                self.action.to_string(),
+                self.date.to_string(),
+                csv_field(self.start_time.map(timestamp)),
+                self.changed.to_string(),
+                self.config_path.display().to_string(),
+            ]);
+            csv
+        })
+    }
+}
+
 /// Error types for block override operations
 #[derive(Debug)]
 pub enum BlockOverrideError {
@@ -265,6 +334,28 @@ impl BlockOverrideManager {
         self.overrides.len()
     }
 
+    /// Overrides for `--show-block-status --format`
+    pub fn status(&self, today: NaiveDate) -> BlockStatus {
+        let mut overrides: Vec<DatedOverride> = self
+            .overrides
+            .iter()
+            .filter_map(|(date, block)| {
+                let date = NaiveDate::parse_from_str(date, "%Y-%m-%d").ok()?;
+                Some(DatedOverride {
+                    date,
+                    block: block.clone(),
+                })
+            })
+            .collect();
+        overrides.sort_by_key(|dated| dated.date);
+        BlockStatus {
+            config_path: self.config_path.clone(),
+            today,
+            today_override: self.get_override(today).cloned(),
+            overrides,
+        }
+    }
+
     /// Get all override dates (for debugging/display)
     pub fn get_all_dates(&self) -> Vec<String> {
         self.overrides.keys().cloned().collect()
@@ -1,30 +1,73 @@
-use super::types::{Config, SegmentsConfig};
+use super::types::{default_segments, Config};
+use crate::billing::{BlockConfig, BurnRateThresholds, ContextConfig, PricingConfig};
+use crate::core::bar::BarConfig;
+use crate::core::icons::IconPack;
+use crate::core::render::{RenderConfig, RenderMode};
+use crate::core::theme::ColorMode;
+use crate::report::ReportConfig;
+use std::collections::BTreeMap;
 
 pub const DEFAULT_CONFIG: Config = Config {
     theme: String::new(), // Set to "dark" at runtime
-    segments: SegmentsConfig {
-        directory: true,
-        git: true,
-        model: true,
-        usage: true,
-        cost: true,
-        burn_rate: true,
+    color_mode: ColorMode::Auto,
+    icons: IconPack::Auto,
+    segments: Vec::new(), // Set to every segment type at runtime
+    render: RenderConfig {
+        mode: RenderMode::Plain,
+        separator: None,
+        powerline_arrow: None,
+        capsule_left: None,
+        capsule_right: None,
+        width: None,
+        timeout_ms: None,
+        placeholder: None,
     },
+    burn_rate: BurnRateThresholds {
+        high: 5000.0,
+        medium: 2000.0,
+    },
+    pricing: PricingConfig {
+        ttl_hours: None,
+        offline: false,
+        url: None,
+        multiplier: None,
+        aliases: BTreeMap::new(),
+        models: BTreeMap::new(),
+    },
+    context: ContextConfig {
+        default_limit: None,
+        limits: BTreeMap::new(),
+        warning: None,
+        critical: None,
+        compact_ratio: None,
+        show_until_compact: false,
+    },
+    block: BlockConfig { budget: None },
+    bar: BarConfig {
+        width: None,
+        filled: None,
+        empty: None,
+        colors: None,
+    },
+    report: ReportConfig { week_start: None },
+    themes: BTreeMap::new(),
 };
 
 impl Default for Config {
     fn default() -> Self {
-        let cost_features_enabled = std::env::var("CCLINE_DISABLE_COST").is_err();
         Config {
             theme: "dark".to_string(),
-            segments: SegmentsConfig {
-                directory: true,
-                git: true,
-                model: true,
-                usage: true,
-                cost: cost_features_enabled,
-                burn_rate: cost_features_enabled,
-            },
+            color_mode: ColorMode::Auto,
+            icons: IconPack::Auto,
+            segments: default_segments(),
+            render: RenderConfig::default(),
+            burn_rate: BurnRateThresholds::default(),
+            pricing: PricingConfig::default(),
+            context: ContextConfig::default(),
+            block: BlockConfig::default(),
+            bar: BarConfig::default(),
+            report: ReportConfig::default(),
+            themes: BTreeMap::new(),
         }
     }
 }
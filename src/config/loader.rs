@@ -1,16 +1,603 @@
-use super::types::Config;
-use std::path::Path;
+use super::types::{Config, SegmentsConfig};
+use crate::utils::resolve_config_dir;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+use toml::Value;
 
-pub struct ConfigLoader;
+/// File name of the user configuration inside `resolve_config_dir()`
+pub const USER_CONFIG_FILE: &str = "config.toml";
+
+/// File name of per-project configuration, searched upwards from the workspace
+pub const PROJECT_CONFIG_FILE: &str = ".ccline.toml";
+
+/// Environment variables mapped onto configuration keys
+const ENV_OVERRIDES: &[(&str, &str, EnvKind)] = &[
+    ("CCLINE_THEME", "theme", EnvKind::String),
+    ("CCLINE_ICONS", "icons", EnvKind::String),
+    ("CCLINE_BURN_HIGH", "burn_rate.high", EnvKind::Float),
+    ("CCLINE_BURN_MEDIUM", "burn_rate.medium", EnvKind::Float),
+    ("CCLINE_OFFLINE", "pricing.offline", EnvKind::Bool),
+    ("CCLINE_PRICING_URL", "pricing.url", EnvKind::String),
+];
+
+/// How an environment variable's text is converted into a TOML value
+enum EnvKind {
+    String,
+    Float,
+    /// `1`/`true`/`yes`/`on` or `0`/`false`/`no`/`off`
+    Bool,
+}
+
+impl EnvKind {
+    fn parse(&self, raw: String) -> Option<Value> {
+        match self {
+            EnvKind::String => Some(Value::String(raw)),
+            EnvKind::Float => raw.trim().parse::<f64>().ok().map(Value::Float),
+            EnvKind::Bool => match raw.trim().to_ascii_lowercase().as_str() {
+                "1" | "true" | "yes" | "on" => Some(Value::Boolean(true)),
+                "0" | "false" | "no" | "off" => Some(Value::Boolean(false)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Origin of an effective configuration value
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConfigSource {
+    /// Built-in default
+    Default,
+    /// User configuration file
+    User(PathBuf),
+    /// Per-project `.ccline.toml`
+    Project(PathBuf),
+    /// Environment variable
+    Env(String),
+    /// Command-line flag
+    Cli(String),
+}
+
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "default"),
+            ConfigSource::User(path) => write!(f, "user file {}", path.display()),
+            ConfigSource::Project(path) => write!(f, "project file {}", path.display()),
+            ConfigSource::Env(name) => write!(f, "env {}", name),
+            ConfigSource::Cli(flag) => write!(f, "cli {}", flag),
+        }
+    }
+}
+
+/// Error types for configuration loading
+#[derive(Debug)]
+pub enum ConfigError {
+    FileAccess(PathBuf, std::io::Error),
+    Parse(PathBuf, String),
+    Invalid(String),
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::FileAccess(path, e) => {
+                write!(f, "Failed to read {}: {}", path.display(), e)
+            }
+            ConfigError::Parse(path, msg) => {
+                write!(f, "Failed to parse {}: {}", path.display(), msg)
+            }
+            ConfigError::Invalid(msg) => write!(f, "Invalid configuration: {}", msg),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+/// Effective configuration together with the origin of every value
+#[derive(Debug, Clone)]
+pub struct ResolvedConfig {
+    pub config: Config,
+    /// Dotted key path -> layer that supplied the value
+    pub sources: BTreeMap<String, ConfigSource>,
+}
+
+impl ResolvedConfig {
+    /// Render the effective configuration as flat `key = value` lines annotated with their source
+    pub fn to_annotated_toml(&self) -> String {
+        let value = Value::try_from(&self.config).unwrap_or(Value::Table(Default::default()));
+        let mut leaves = Vec::new();
+        flatten_leaves(&value, "", &mut leaves);
+
+        let mut lines = Vec::new();
+        for (key, value) in leaves {
+            let source = self
+                .sources
+                .get(&key)
+                .cloned()
+                .unwrap_or(ConfigSource::Default);
+            lines.push(format!("{} = {}  # {}", key, value, source));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Multi-layer configuration loader
+///
+/// Layers are applied in order, each deep-merged over the previous one:
+/// 1. Built-in defaults
+/// 2. User file (`--config` path, or `config.toml` under `resolve_config_dir()`)
+/// 3. Nearest `.ccline.toml` walking up from the workspace directory
+/// 4. Environment variables
+/// 5. Command-line flags
+#[derive(Debug, Default)]
+pub struct ConfigLoader {
+    config_path: Option<PathBuf>,
+    workspace_dir: Option<PathBuf>,
+    cli_overrides: Vec<(String, Value, String)>,
+}
 
 impl ConfigLoader {
-    pub fn load() -> Config {
-        // Return default config for now, implement multi-layer loading later
-        Config::default()
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Use an explicit user configuration file instead of the default location
+    pub fn with_config_path<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.config_path = Some(path.into());
+        self
+    }
+
+    /// Directory from which the project configuration search starts
+    pub fn with_workspace_dir<P: Into<PathBuf>>(mut self, dir: P) -> Self {
+        self.workspace_dir = Some(dir.into());
+        self
+    }
+
+    /// Override a dotted key path from a command-line flag
+    pub fn with_cli_override<V: Into<Value>>(mut self, key: &str, value: V, flag: &str) -> Self {
+        self.cli_overrides
+            .push((key.to_string(), value.into(), flag.to_string()));
+        self
+    }
+
+    /// Path of the user configuration file this loader reads
+    pub fn user_config_path(&self) -> PathBuf {
+        self.config_path
+            .clone()
+            .unwrap_or_else(|| resolve_config_dir().join(USER_CONFIG_FILE))
+    }
+
+    /// Path of the nearest project configuration file, if any
+    pub fn project_config_path(&self) -> Option<PathBuf> {
+        self.workspace_dir.as_deref().and_then(find_project_config)
+    }
+
+    /// Resolve all layers into the effective configuration
+    pub fn resolve(&self) -> Result<ResolvedConfig, ConfigError> {
+        let mut merged =
+            Value::try_from(Config::default()).map_err(|e| ConfigError::Invalid(e.to_string()))?;
+        let mut sources = BTreeMap::new();
+
+        let user_path = self.user_config_path();
+        if self.config_path.is_some() || user_path.exists() {
+            let layer = read_layer(&user_path)?;
+            merge_layer(
+                &mut merged,
+                layer,
+                &ConfigSource::User(user_path),
+                &mut sources,
+            );
+        }
+
+        if let Some(project_path) = self.project_config_path() {
+            if project_path != self.user_config_path() {
+                let layer = read_layer(&project_path)?;
+                merge_layer(
+                    &mut merged,
+                    layer,
+                    &ConfigSource::Project(project_path),
+                    &mut sources,
+                );
+            }
+        }
+
+        for (key, value, var) in env_overrides(|name| std::env::var(name).ok()) {
+            let mut layer = Value::Table(Default::default());
+            set_path(&mut layer, &key, value);
+            merge_layer(&mut merged, layer, &ConfigSource::Env(var), &mut sources);
+        }
+
+        for (key, value, flag) in &self.cli_overrides {
+            let mut layer = Value::Table(Default::default());
+            set_path(&mut layer, key, value.clone());
+            merge_layer(
+                &mut merged,
+                layer,
+                &ConfigSource::Cli(flag.clone()),
+                &mut sources,
+            );
+        }
+
+        let config: Config = merged
+            .try_into()
+            .map_err(|e: toml::de::Error| ConfigError::Invalid(e.message().to_string()))?;
+
+        Ok(ResolvedConfig { config, sources })
+    }
+
+    /// Resolve the effective configuration, falling back to defaults on error
+    pub fn load(&self) -> Config {
+        match self.resolve() {
+            Ok(resolved) => resolved.config,
+            Err(e) => {
+                eprintln!("Warning: {}; using default configuration", e);
+                Config::default()
+            }
+        }
+    }
+
+    /// Load a single configuration file merged over the built-in defaults
+    pub fn load_from_path<P: AsRef<Path>>(path: P) -> Result<Config, ConfigError> {
+        let path = path.as_ref();
+        let layer = read_layer(path)?;
+        merge_over_defaults(layer).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+    }
+
+    /// Parse configuration source text merged over the built-in defaults
+    pub fn load_from_str(content: &str) -> Result<Config, ConfigError> {
+        let layer = content
+            .parse::<toml::Table>()
+            .map(Value::Table)
+            .map_err(|e| ConfigError::Invalid(e.message().to_string()))?;
+        merge_over_defaults(layer).map_err(ConfigError::Invalid)
+    }
+}
+
+fn merge_over_defaults(layer: Value) -> Result<Config, String> {
+    let mut merged = Value::try_from(Config::default()).map_err(|e| e.to_string())?;
+    merge_layer(
+        &mut merged,
+        layer,
+        &ConfigSource::Default,
+        &mut BTreeMap::new(),
+    );
+    merged
+        .try_into()
+        .map_err(|e: toml::de::Error| e.message().to_string())
+}
+
+/// Read and parse a TOML layer from disk
+fn read_layer(path: &Path) -> Result<Value, ConfigError> {
+    let content =
+        fs::read_to_string(path).map_err(|e| ConfigError::FileAccess(path.to_path_buf(), e))?;
+    content
+        .parse::<toml::Table>()
+        .map(Value::Table)
+        .map_err(|e| ConfigError::Parse(path.to_path_buf(), e.message().to_string()))
+}
+
+/// Find the nearest project configuration file in `start` or its ancestors
+fn find_project_config(start: &Path) -> Option<PathBuf> {
+    start
+        .ancestors()
+        .map(|dir| dir.join(PROJECT_CONFIG_FILE))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Collect configuration overrides from environment variables
+fn env_overrides<F>(lookup: F) -> Vec<(String, Value, String)>
+where
+    F: Fn(&str) -> Option<String>,
+{
+    let mut overrides = Vec::new();
+
+    for (var, key, kind) in ENV_OVERRIDES {
+        let value = lookup(var)
+            .filter(|v| !v.is_empty())
+            .and_then(|raw| kind.parse(raw));
+        if let Some(value) = value {
+            overrides.push((key.to_string(), value, var.to_string()));
+        }
+    }
+
+    if lookup("CCLINE_DISABLE_COST").is_some() {
+        for key in ["segments.cost", "segments.burn_rate"] {
+            overrides.push((
+                key.to_string(),
+                Value::Boolean(false),
+                "CCLINE_DISABLE_COST".to_string(),
+            ));
+        }
+    }
+
+    overrides
+}
+
+/// Deep-merge `overlay` into `base`, recording the source of every replaced leaf
+fn merge_layer(
+    base: &mut Value,
+    overlay: Value,
+    source: &ConfigSource,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    merge_at(base, overlay, "", source, sources);
+}
+
+fn merge_at(
+    base: &mut Value,
+    overlay: Value,
+    prefix: &str,
+    source: &ConfigSource,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    match (base, overlay) {
+        (Value::Table(base_table), Value::Table(overlay_table)) => {
+            for (key, value) in overlay_table {
+                let path = join_key(prefix, &key);
+                match base_table.get_mut(&key) {
+                    Some(existing) if existing.is_table() && value.is_table() => {
+                        merge_at(existing, value, &path, source, sources);
+                    }
+                    Some(existing) if path == "segments" && is_segment_toggles(&value) => {
+                        apply_segment_toggles(existing, &value);
+                        record_leaves(existing, &path, source, sources);
+                    }
+                    _ => {
+                        record_leaves(&value, &path, source, sources);
+                        base_table.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => {
+            record_leaves(&overlay, prefix, source, sources);
+            *base = overlay;
+        }
+    }
+}
+
+/// Whether a layer's `segments` value is a valid legacy table of switches
+fn is_segment_toggles(value: &Value) -> bool {
+    value.is_table() && SegmentsConfig::deserialize(value.clone()).is_ok()
+}
+
+/// Apply a legacy `[segments]` table of switches to an ordered segment list
+///
+/// Matching entries get their `enabled` flag set; switched-on types missing
+/// from the list are appended. A list that is not an array is replaced.
+fn apply_segment_toggles(list: &mut Value, toggles: &Value) {
+    let Value::Table(switches) = toggles else {
+        return;
+    };
+    let Value::Array(entries) = list else {
+        *list = toggles.clone();
+        return;
+    };
+
+    for (kind, enabled) in switches {
+        let enabled = enabled.as_bool().unwrap_or(true);
+        let mut found = false;
+        for entry in entries.iter_mut() {
+            if let Value::Table(entry) = entry {
+                if entry.get("type").and_then(Value::as_str) == Some(kind.as_str()) {
+                    entry.insert("enabled".to_string(), Value::Boolean(enabled));
+                    found = true;
+                }
+            }
+        }
+        if !found && enabled {
+            let mut entry = toml::Table::new();
+            entry.insert("type".to_string(), Value::String(kind.clone()));
+            entries.push(Value::Table(entry));
+        }
+    }
+}
+
+fn record_leaves(
+    value: &Value,
+    path: &str,
+    source: &ConfigSource,
+    sources: &mut BTreeMap<String, ConfigSource>,
+) {
+    // A replaced subtree drops any provenance recorded for its old leaves
+    let nested = format!("{}.", path);
+    sources.retain(|key, _| key != path && !key.starts_with(&nested));
+
+    let mut leaves = Vec::new();
+    flatten_leaves(value, path, &mut leaves);
+    for (key, _) in leaves {
+        sources.insert(key, source.clone());
+    }
+}
+
+/// Flatten a TOML value into dotted key paths; arrays are treated as leaves
+fn flatten_leaves(value: &Value, prefix: &str, out: &mut Vec<(String, Value)>) {
+    match value {
+        Value::Table(table) if !table.is_empty() || prefix.is_empty() => {
+            for (key, child) in table {
+                flatten_leaves(child, &join_key(prefix, key), out);
+            }
+        }
+        _ => out.push((prefix.to_string(), value.clone())),
+    }
+}
+
+/// Set a dotted key path inside a table, creating intermediate tables
+fn set_path(root: &mut Value, path: &str, value: Value) {
+    let mut current = root;
+    let mut parts = path.split('.').peekable();
+    while let Some(part) = parts.next() {
+        let Value::Table(table) = current else {
+            return;
+        };
+        if parts.peek().is_none() {
+            table.insert(part.to_string(), value);
+            return;
+        }
+        current = table
+            .entry(part.to_string())
+            .or_insert_with(|| Value::Table(Default::default()));
+    }
+}
+
+/// Append a key to a dotted path, quoting it when it is not a valid TOML bare key
+fn join_key(prefix: &str, key: &str) -> String {
+    let bare = !key.is_empty()
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-');
+    let key = if bare {
+        key.to_string()
+    } else {
+        format!("{:?}", key)
+    };
+
+    if prefix.is_empty() {
+        key
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::SegmentKind;
+    use crate::core::segments::CostWindow;
+
+    fn defaults() -> Value {
+        Value::try_from(Config::default()).unwrap()
+    }
+
+    #[test]
+    fn test_merge_layer_precedence() {
+        let mut merged = defaults();
+        let mut sources = BTreeMap::new();
+
+        let user: Value = toml::from_str("theme = \"light\"\n[segments]\ngit = false\n").unwrap();
+        let user_source = ConfigSource::User(PathBuf::from("/home/user/config.toml"));
+        merge_layer(&mut merged, user, &user_source, &mut sources);
+
+        let project: Value = toml::from_str("theme = \"nord\"\n").unwrap();
+        let project_source = ConfigSource::Project(PathBuf::from("/work/.ccline.toml"));
+        merge_layer(&mut merged, project, &project_source, &mut sources);
+
+        let config: Config = merged.try_into().unwrap();
+        assert_eq!(config.theme, "nord");
+        let enabled = |kind: SegmentKind| {
+            config
+                .segments
+                .iter()
+                .any(|entry| entry.kind == kind && entry.enabled)
+        };
+        assert!(!enabled(SegmentKind::Git));
+        assert!(enabled(SegmentKind::Model));
+
+        assert_eq!(sources.get("theme"), Some(&project_source));
+        assert_eq!(sources.get("segments"), Some(&user_source));
+        assert_eq!(sources.get("color_mode"), None);
+    }
+
+    #[test]
+    fn test_segment_list_and_legacy_toggles() {
+        let mut merged = defaults();
+        let mut sources = BTreeMap::new();
+
+        let user: Value = toml::from_str(
+            "[[segments]]\ntype = \"cost\"\nwindow = \"today\"\n\n[[segments]]\ntype = \"model\"\n\n[[segments]]\ntype = \"cost\"\nwindow = \"block\"\n",
+        )
+        .unwrap();
+        merge_layer(&mut merged, user, &ConfigSource::Default, &mut sources);
+
+        let env: Value = toml::from_str("[segments]\ncost = false\ngit = true\n").unwrap();
+        merge_layer(&mut merged, env, &ConfigSource::Default, &mut sources);
+
+        let config: Config = merged.try_into().unwrap();
+        let kinds: Vec<(SegmentKind, bool)> = config
+            .segments
+            .iter()
+            .map(|entry| (entry.kind, entry.enabled))
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![
+                (SegmentKind::Cost, false),
+                (SegmentKind::Model, true),
+                (SegmentKind::Cost, false),
+                (SegmentKind::Git, true),
+                (SegmentKind::Update, true),
+            ]
+        );
+
+        let hidden: Config = toml::from_str(
+            "theme = \"dark\"\n\n[[segments]]\ntype = \"update\"\nenabled = false\n\n[[segments]]\ntype = \"model\"\n",
+        )
+        .unwrap();
+        assert_eq!(hidden.segments.len(), 2);
+        assert!(!hidden.segments[0].enabled);
+        assert_eq!(config.segments[2].window, Some(CostWindow::Block));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let overrides = env_overrides(|name| match name {
+            "CCLINE_THEME" => Some("light".to_string()),
+            "CCLINE_ICONS" => Some("plain-ascii".to_string()),
+            "CCLINE_DISABLE_COST" => Some("1".to_string()),
+            "CCLINE_BURN_HIGH" => Some("8000".to_string()),
+            "CCLINE_BURN_MEDIUM" => Some("not-a-number".to_string()),
+            "CCLINE_OFFLINE" => Some("1".to_string()),
+            _ => None,
+        });
+
+        let keys: Vec<&str> = overrides.iter().map(|(k, _, _)| k.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                "theme",
+                "icons",
+                "burn_rate.high",
+                "pricing.offline",
+                "segments.cost",
+                "segments.burn_rate"
+            ]
+        );
+        assert_eq!(overrides[2].1, Value::Float(8000.0));
+        assert_eq!(overrides[3].1, Value::Boolean(true));
+        assert!(env_overrides(|_| None).is_empty());
+    }
+
+    #[test]
+    fn test_set_path_creates_tables() {
+        let mut root = Value::Table(Default::default());
+        set_path(&mut root, "segments.cost", Value::Boolean(false));
+        assert_eq!(root["segments"]["cost"], Value::Boolean(false));
+    }
+
+    #[test]
+    fn test_find_project_config_walks_up() {
+        let root = std::env::temp_dir().join(format!("ccline-loader-{}", std::process::id()));
+        let nested = root.join("a").join("b");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(root.join(PROJECT_CONFIG_FILE), "theme = \"light\"\n").unwrap();
+
+        assert_eq!(
+            find_project_config(&nested),
+            Some(root.join(PROJECT_CONFIG_FILE))
+        );
+
+        let config = ConfigLoader::load_from_path(root.join(PROJECT_CONFIG_FILE)).unwrap();
+        assert_eq!(config.theme, "light");
+
+        let _ = fs::remove_dir_all(&root);
     }
 
-    pub fn load_from_path<P: AsRef<Path>>(_path: P) -> Result<Config, Box<dyn std::error::Error>> {
-        // Load config from file
-        Ok(Config::default())
+    #[test]
+    fn test_join_key_quotes_non_bare_keys() {
+        assert_eq!(join_key("segments", "burn_rate"), "segments.burn_rate");
+        assert_eq!(join_key("pricing", "gpt-4.1"), "pricing.\"gpt-4.1\"");
     }
 }
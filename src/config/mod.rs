@@ -3,9 +3,11 @@ pub mod defaults;
 pub mod loader;
 pub mod tui;
 pub mod types;
+pub mod validate;
 
 pub use block_overrides::*;
 pub use defaults::DEFAULT_CONFIG;
-pub use loader::ConfigLoader;
+pub use loader::{ConfigError, ConfigLoader, ConfigSource, ResolvedConfig};
 pub use tui::run_configuration_wizard;
 pub use types::*;
+pub use validate::{validate_file, Diagnostic};
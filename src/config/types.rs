@@ -1,19 +1,188 @@
-use serde::{Deserialize, Serialize};
+use crate::billing::{BlockConfig, BurnRateThresholds, ContextConfig, PricingConfig};
+use crate::core::bar::BarConfig;
+use crate::core::icons::IconPack;
+use crate::core::render::{Align, RenderConfig};
+use crate::core::segments::CostWindow;
+use crate::core::theme::{Color, ColorMode, ThemeConfig};
+use crate::report::ReportConfig;
+use serde::de::{self, value::MapAccessDeserializer, value::SeqAccessDeserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
 use std::io;
 
 #[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Config {
     pub theme: String,
-    pub segments: SegmentsConfig,
+    #[serde(default)]
+    pub color_mode: ColorMode,
+    /// Icon pack for segment glyphs; `auto` picks one from the terminal
+    #[serde(default)]
+    pub icons: IconPack,
+    /// Segments in display order; also accepts the legacy `[segments]` table of booleans
+    #[serde(
+        default = "default_segments",
+        deserialize_with = "deserialize_segments"
+    )]
+    pub segments: Vec<SegmentConfig>,
+    /// Layout mode and separator glyphs
+    #[serde(default)]
+    pub render: RenderConfig,
+    #[serde(default)]
+    pub burn_rate: BurnRateThresholds,
+    /// Pricing source, cache lifetime and offline mode
+    #[serde(default)]
+    pub pricing: PricingConfig,
+    /// Context window sizes and warning thresholds for the usage segment
+    #[serde(default)]
+    pub context: ContextConfig,
+    /// Budget of the billing block segment
+    #[serde(default)]
+    pub block: BlockConfig,
+    /// Width, glyphs and colors of progress bars
+    #[serde(default)]
+    pub bar: BarConfig,
+    /// Defaults of `ccline report`
+    #[serde(default)]
+    pub report: ReportConfig,
+    /// User-defined themes, selectable by name through `theme`
+    #[serde(default)]
+    pub themes: BTreeMap<String, ThemeConfig>,
+}
+
+/// Segment types that can appear in `[[segments]]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SegmentKind {
+    Model,
+    Directory,
+    Git,
+    Usage,
+    Cost,
+    BurnRate,
+    Block,
+    Update,
+}
+
+impl SegmentKind {
+    /// Every segment type, in the default display order
+    pub const ALL: [SegmentKind; 8] = [
+        SegmentKind::Model,
+        SegmentKind::Directory,
+        SegmentKind::Git,
+        SegmentKind::Usage,
+        SegmentKind::Cost,
+        SegmentKind::BurnRate,
+        SegmentKind::Block,
+        SegmentKind::Update,
+    ];
+
+    /// Name used in configuration files and as the theme style key
+    pub fn name(self) -> &'static str {
+        match self {
+            SegmentKind::Model => "model",
+            SegmentKind::Directory => "directory",
+            SegmentKind::Git => "git",
+            SegmentKind::Usage => "usage",
+            SegmentKind::Cost => "cost",
+            SegmentKind::BurnRate => "burn_rate",
+            SegmentKind::Block => "block",
+            SegmentKind::Update => "update",
+        }
+    }
+
+    /// Priority used when the line is too wide; lower priorities are dropped first
+    pub fn default_priority(self) -> u32 {
+        match self {
+            SegmentKind::Model => 100,
+            SegmentKind::Directory => 90,
+            SegmentKind::Git => 80,
+            SegmentKind::Usage => 70,
+            SegmentKind::Cost => 60,
+            SegmentKind::BurnRate => 40,
+            SegmentKind::Block => 30,
+            SegmentKind::Update => 20,
+        }
+    }
+
+    /// Whether the segment is part of the default line; the rest are opt-in
+    pub fn enabled_by_default(self) -> bool {
+        self != SegmentKind::Block
+    }
+}
+
+/// One entry of the ordered `[[segments]]` array
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct SegmentConfig {
+    #[serde(rename = "type")]
+    pub kind: SegmentKind,
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub enabled: bool,
+    /// Replaces the segment's leading icon (an empty string removes it)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon: Option<String>,
+    /// Foreground color overriding the theme style
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<Color>,
+    /// Output template using the segment's variables, e.g. `"{icon} {session_cost:$.2}"`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub format: Option<String>,
+    /// Drop the segment when it renders nothing
+    #[serde(default = "default_true", skip_serializing_if = "is_true")]
+    pub hide_when_empty: bool,
+    /// Cost window to show (cost segments only); all windows when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub window: Option<CostWindow>,
+    /// Drop order when the line does not fit the terminal; defaults per segment type
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<u32>,
+    /// Row of the statusline the segment is drawn on, starting at 1
+    #[serde(default = "default_line", skip_serializing_if = "is_first_line")]
+    pub line: usize,
+    /// Side of its row the segment sits on; right alignment needs a known width
+    #[serde(default, skip_serializing_if = "Align::is_left")]
+    pub align: Align,
+    /// Deadline in milliseconds, overriding `[render] timeout_ms`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+}
+
+impl SegmentConfig {
+    pub fn new(kind: SegmentKind) -> Self {
+        Self {
+            kind,
+            enabled: true,
+            icon: None,
+            color: None,
+            format: None,
+            hide_when_empty: true,
+            window: None,
+            priority: None,
+            line: 1,
+            align: Align::Left,
+            timeout_ms: None,
+        }
+    }
+
+    /// Configured priority, or the segment type's default
+    pub fn priority(&self) -> u32 {
+        self.priority
+            .unwrap_or_else(|| self.kind.default_priority())
+    }
 }
 
+/// Legacy `[segments]` table of on/off switches
 #[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
 pub struct SegmentsConfig {
+    #[serde(default = "default_true")]
     pub directory: bool,
+    #[serde(default = "default_true")]
     pub git: bool,
+    #[serde(default = "default_true")]
     pub model: bool,
+    #[serde(default = "default_true")]
     pub usage: bool,
     #[serde(default = "default_true")]
     pub cost: bool,
@@ -21,16 +190,107 @@ pub struct SegmentsConfig {
     pub burn_rate: bool,
 }
 
+impl From<SegmentsConfig> for Vec<SegmentConfig> {
+    fn from(legacy: SegmentsConfig) -> Self {
+        SegmentKind::ALL
+            .iter()
+            .map(|&kind| {
+                let enabled = match kind {
+                    SegmentKind::Model => legacy.model,
+                    SegmentKind::Directory => legacy.directory,
+                    SegmentKind::Git => legacy.git,
+                    SegmentKind::Usage => legacy.usage,
+                    SegmentKind::Cost => legacy.cost,
+                    SegmentKind::BurnRate => legacy.burn_rate,
+                    SegmentKind::Block => false,
+                    SegmentKind::Update => true,
+                };
+                SegmentConfig {
+                    enabled,
+                    ..SegmentConfig::new(kind)
+                }
+            })
+            .collect()
+    }
+}
+
+pub(crate) fn default_segments() -> Vec<SegmentConfig> {
+    SegmentKind::ALL
+        .iter()
+        .map(|&kind| SegmentConfig {
+            enabled: kind.enabled_by_default(),
+            ..SegmentConfig::new(kind)
+        })
+        .collect()
+}
+
+/// Accept either the `[[segments]]` array or the legacy boolean table
+fn deserialize_segments<'de, D>(deserializer: D) -> Result<Vec<SegmentConfig>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    struct SegmentsVisitor;
+
+    impl<'de> de::Visitor<'de> for SegmentsVisitor {
+        type Value = Vec<SegmentConfig>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("an array of segment tables or a table of segment switches")
+        }
+
+        fn visit_seq<A: de::SeqAccess<'de>>(self, seq: A) -> Result<Self::Value, A::Error> {
+            let mut segments: Vec<SegmentConfig> =
+                Vec::deserialize(SeqAccessDeserializer::new(seq))?;
+            // Update notices stay on unless the list turns them off explicitly
+            if !segments
+                .iter()
+                .any(|entry| entry.kind == SegmentKind::Update)
+            {
+                segments.push(SegmentConfig::new(SegmentKind::Update));
+            }
+            Ok(segments)
+        }
+
+        fn visit_map<A: de::MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+            SegmentsConfig::deserialize(MapAccessDeserializer::new(map)).map(Into::into)
+        }
+    }
+
+    deserializer.deserialize_any(SegmentsVisitor)
+}
+
 fn default_true() -> bool {
     true
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+fn is_true(value: &bool) -> bool {
+    *value
+}
+
+fn default_line() -> usize {
+    1
+}
+
+fn is_first_line(line: &usize) -> bool {
+    *line == 1
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ProviderKind {
     Claude,
     Codex,
 }
 
+impl ProviderKind {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProviderKind::Claude => "claude",
+            ProviderKind::Codex => "codex",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Model {
     pub display_name: String,
@@ -444,6 +704,9 @@ pub struct TranscriptEntry {
     pub timestamp: Option<String>,
     #[serde(default)]
     pub payload: Option<TranscriptPayload>,
+    /// Working directory of the session, recorded on Claude transcript lines
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
@@ -454,6 +717,9 @@ pub struct TranscriptPayload {
     pub info: Option<TokenCountInfo>,
     #[serde(default)]
     pub model: Option<String>,
+    /// Working directory, carried by Codex `session_meta` and `turn_context` payloads
+    #[serde(default)]
+    pub cwd: Option<String>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
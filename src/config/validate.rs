@@ -0,0 +1,919 @@
+use super::loader::ConfigLoader;
+use crate::billing::ContextConfig;
+use crate::core::icons::IconPack;
+use crate::core::template::Template;
+use crate::core::theme::{Color, BUILTIN_THEMES};
+use crate::report::WeekStart;
+use std::fmt;
+use std::fs;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use toml_edit::{ImDocument, Item, TableLike, Value};
+
+/// Expected shape of a configuration value
+enum Schema {
+    Bool,
+    String,
+    /// Positive number (integer or float)
+    Threshold,
+    /// Non-negative integer
+    Count,
+    /// Integer greater than zero
+    PositiveCount,
+    /// Non-negative number (integer or float)
+    Price,
+    /// Color spec accepted by `Color::parse`
+    Color,
+    /// Segment format string accepted by `Template::parse`
+    Template,
+    /// String restricted to a fixed set of values
+    OneOf(&'static [&'static str]),
+    /// Table with arbitrary keys whose values all share one schema
+    Map(&'static Schema),
+    /// Array whose values all share one schema
+    List(&'static Schema),
+    /// Array of tables matching `entry`, or a single table matching `table`
+    ListOrTable {
+        entry: &'static Schema,
+        table: &'static Schema,
+    },
+    /// Table with a fixed set of keys; `kind` names the keys in diagnostics
+    Table {
+        kind: &'static str,
+        fields: &'static [(&'static str, Schema)],
+    },
+}
+
+const SEGMENTS_SCHEMA: Schema = Schema::Table {
+    kind: "segment",
+    fields: &[
+        ("directory", Schema::Bool),
+        ("git", Schema::Bool),
+        ("model", Schema::Bool),
+        ("usage", Schema::Bool),
+        ("cost", Schema::Bool),
+        ("burn_rate", Schema::Bool),
+    ],
+};
+
+const SEGMENT_TYPES: &[&str] = &[
+    "model",
+    "directory",
+    "git",
+    "usage",
+    "cost",
+    "burn_rate",
+    "block",
+    "update",
+];
+
+const SEGMENT_ENTRY_SCHEMA: Schema = Schema::Table {
+    kind: "segment option",
+    fields: &[
+        ("type", Schema::OneOf(SEGMENT_TYPES)),
+        ("enabled", Schema::Bool),
+        ("icon", Schema::String),
+        ("color", Schema::Color),
+        ("format", Schema::Template),
+        ("hide_when_empty", Schema::Bool),
+        ("window", Schema::OneOf(&["session", "today", "block"])),
+        ("priority", Schema::Count),
+        ("line", Schema::Count),
+        ("align", Schema::OneOf(&["left", "right"])),
+        ("timeout_ms", Schema::Count),
+    ],
+};
+
+const BURN_RATE_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[("high", Schema::Threshold), ("medium", Schema::Threshold)],
+};
+
+const PRICING_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[
+        ("ttl_hours", Schema::Count),
+        ("offline", Schema::Bool),
+        ("url", Schema::String),
+        ("multiplier", Schema::Threshold),
+        ("aliases", Schema::Map(&Schema::String)),
+        ("models", Schema::Map(&PRICE_OVERRIDE_SCHEMA)),
+    ],
+};
+
+const PRICE_OVERRIDE_SCHEMA: Schema = Schema::Table {
+    kind: "price key",
+    fields: &[
+        ("input", Schema::Price),
+        ("output", Schema::Price),
+        ("cache_creation", Schema::Price),
+        ("cache_read", Schema::Price),
+    ],
+};
+
+const CONTEXT_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[
+        ("default_limit", Schema::PositiveCount),
+        ("limits", Schema::Map(&Schema::PositiveCount)),
+        ("warning", Schema::Threshold),
+        ("critical", Schema::Threshold),
+        ("compact_ratio", Schema::Threshold),
+        ("show_until_compact", Schema::Bool),
+    ],
+};
+
+const BLOCK_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[("budget", Schema::Threshold)],
+};
+
+const REPORT_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[("week_start", Schema::OneOf(WeekStart::NAMES))],
+};
+
+const BAR_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[
+        ("width", Schema::Count),
+        ("filled", Schema::String),
+        ("empty", Schema::String),
+        ("colors", Schema::List(&Schema::Color)),
+    ],
+};
+
+const RENDER_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[
+        (
+            "mode",
+            Schema::OneOf(&["plain", "powerline", "capsule", "minimal"]),
+        ),
+        ("separator", Schema::String),
+        ("powerline_arrow", Schema::String),
+        ("capsule_left", Schema::String),
+        ("capsule_right", Schema::String),
+        ("width", Schema::Count),
+        ("timeout_ms", Schema::Count),
+        ("placeholder", Schema::String),
+    ],
+};
+
+const STYLE_SCHEMA: Schema = Schema::Table {
+    kind: "style key",
+    fields: &[
+        ("fg", Schema::Color),
+        ("bg", Schema::Color),
+        ("icon", Schema::Color),
+        ("bold", Schema::Bool),
+        ("italic", Schema::Bool),
+    ],
+};
+
+const THEME_SCHEMA: Schema = Schema::Table {
+    kind: "theme key",
+    fields: &[
+        ("base", Schema::String),
+        ("model", STYLE_SCHEMA),
+        ("directory", STYLE_SCHEMA),
+        ("git", STYLE_SCHEMA),
+        ("usage", STYLE_SCHEMA),
+        ("cost", STYLE_SCHEMA),
+        ("burn_rate", STYLE_SCHEMA),
+        ("block", STYLE_SCHEMA),
+        ("update", STYLE_SCHEMA),
+        ("separator", STYLE_SCHEMA),
+        ("warning", STYLE_SCHEMA),
+        ("critical", STYLE_SCHEMA),
+    ],
+};
+
+const CONFIG_SCHEMA: Schema = Schema::Table {
+    kind: "key",
+    fields: &[
+        ("theme", Schema::String),
+        (
+            "color_mode",
+            Schema::OneOf(&["auto", "16", "256", "truecolor"]),
+        ),
+        ("icons", Schema::OneOf(IconPack::NAMES)),
+        (
+            "segments",
+            Schema::ListOrTable {
+                entry: &SEGMENT_ENTRY_SCHEMA,
+                table: &SEGMENTS_SCHEMA,
+            },
+        ),
+        ("render", RENDER_SCHEMA),
+        ("burn_rate", BURN_RATE_SCHEMA),
+        ("pricing", PRICING_SCHEMA),
+        ("context", CONTEXT_SCHEMA),
+        ("block", BLOCK_SCHEMA),
+        ("bar", BAR_SCHEMA),
+        ("report", REPORT_SCHEMA),
+        ("themes", Schema::Map(&THEME_SCHEMA)),
+    ],
+};
+
+/// A single validation problem with its location in the source file
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub path: PathBuf,
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number (in characters)
+    pub column: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}:{}: error: {}",
+            self.path.display(),
+            self.line,
+            self.column,
+            self.message
+        )
+    }
+}
+
+/// Validate a configuration file on disk
+pub fn validate_file<P: AsRef<Path>>(path: P) -> Result<Vec<Diagnostic>, std::io::Error> {
+    let path = path.as_ref();
+    let content = fs::read_to_string(path)?;
+    Ok(validate_str(&content, path))
+}
+
+/// Validate configuration source text; `path` is only used for reporting
+pub fn validate_str(content: &str, path: &Path) -> Vec<Diagnostic> {
+    let mut validator = Validator {
+        content,
+        path,
+        diagnostics: Vec::new(),
+    };
+
+    let document = match ImDocument::parse(content) {
+        Ok(document) => document,
+        Err(e) => {
+            validator.report(e.span(), e.message().trim_end().to_string());
+            return validator.diagnostics;
+        }
+    };
+
+    let root = document.as_table();
+    validator.check_table(root, "", &CONFIG_SCHEMA);
+    validator.check_burn_rate_order(root);
+    validator.check_context_thresholds(root);
+    validator.check_theme_names(root);
+    validator.check_segment_entries(root);
+
+    // Anything the schema walk did not catch still has to deserialize into `Config`
+    if validator.diagnostics.is_empty() {
+        if let Err(e) = ConfigLoader::load_from_str(content) {
+            validator.report(None, e.to_string());
+        }
+    }
+
+    validator.diagnostics
+}
+
+struct Validator<'a> {
+    content: &'a str,
+    path: &'a Path,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Validator<'_> {
+    fn check_table(&mut self, table: &dyn TableLike, prefix: &str, schema: &Schema) {
+        let Schema::Table { kind, fields } = schema else {
+            return;
+        };
+
+        for (key, item) in table.iter() {
+            let path = join_path(prefix, key);
+            let key_span = table.key(key).and_then(|k| k.span());
+
+            match fields.iter().find(|(name, _)| *name == key) {
+                Some((_, field_schema)) => self.check_item(item, &path, key_span, field_schema),
+                None => {
+                    let expected: Vec<&str> = fields.iter().map(|(name, _)| *name).collect();
+                    let location = if prefix.is_empty() {
+                        String::new()
+                    } else {
+                        format!(" in [{}]", prefix)
+                    };
+                    self.report(
+                        key_span.or_else(|| item.span()),
+                        format!(
+                            "unknown {} `{}`{} (expected one of: {})",
+                            kind,
+                            key,
+                            location,
+                            expected.join(", ")
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn check_item(
+        &mut self,
+        item: &Item,
+        path: &str,
+        key_span: Option<Range<usize>>,
+        schema: &Schema,
+    ) {
+        let span = item.span().or(key_span);
+
+        if let Schema::Map(value_schema) = schema {
+            match item.as_table_like() {
+                Some(table) => {
+                    for (key, value) in table.iter() {
+                        let value_key_span = table.key(key).and_then(|k| k.span());
+                        self.check_item(value, &join_path(path, key), value_key_span, value_schema);
+                    }
+                }
+                None => self.report(
+                    span,
+                    format!("`{}` must be a table, found {}", path, item.type_name()),
+                ),
+            }
+            return;
+        }
+
+        if let Schema::List(value_schema) = schema {
+            match item.as_array() {
+                Some(array) => {
+                    for (i, value) in array.iter().enumerate() {
+                        let value_item = Item::Value(value.clone());
+                        let value_path = format!("{}[{}]", path, i);
+                        self.check_item(&value_item, &value_path, value.span(), value_schema);
+                    }
+                }
+                None => self.report(
+                    span,
+                    format!("`{}` must be an array, found {}", path, item.type_name()),
+                ),
+            }
+            return;
+        }
+
+        if let Schema::ListOrTable { entry, table } = schema {
+            if let Some(tables) = item.as_array_of_tables() {
+                for (i, entry_table) in tables.iter().enumerate() {
+                    self.check_table(entry_table, &format!("{}[{}]", path, i), entry);
+                }
+            } else if let Some(array) = item.as_array() {
+                for (i, value) in array.iter().enumerate() {
+                    let entry_path = format!("{}[{}]", path, i);
+                    match value.as_inline_table() {
+                        Some(entry_table) => self.check_table(entry_table, &entry_path, entry),
+                        None => self.report(
+                            value.span(),
+                            format!(
+                                "`{}` must be a table, found {}",
+                                entry_path,
+                                value.type_name()
+                            ),
+                        ),
+                    }
+                }
+            } else if let Some(legacy) = item.as_table_like() {
+                self.check_table(legacy, path, table);
+            } else {
+                self.report(
+                    span,
+                    format!(
+                        "`{}` must be an array of tables or a table, found {}",
+                        path,
+                        item.type_name()
+                    ),
+                );
+            }
+            return;
+        }
+
+        if let Schema::Table { .. } = schema {
+            match item.as_table_like() {
+                Some(table) => self.check_table(table, path, schema),
+                None => self.report(
+                    span,
+                    format!("`{}` must be a table, found {}", path, item.type_name()),
+                ),
+            }
+            return;
+        }
+
+        let Some(value) = item.as_value() else {
+            self.report(
+                span,
+                format!(
+                    "`{}` must be {}, found {}",
+                    path,
+                    expected_name(schema),
+                    item.type_name()
+                ),
+            );
+            return;
+        };
+
+        match (schema, value) {
+            (Schema::Bool, Value::Boolean(_)) | (Schema::String, Value::String(_)) => {}
+            (Schema::Color, Value::String(spec)) => {
+                if let Err(e) = Color::parse(spec.value()) {
+                    self.report(span, format!("`{}`: {}", path, e));
+                }
+            }
+            (Schema::Template, Value::String(format)) => {
+                if let Err(e) = Template::parse(format.value()) {
+                    // Point at the offending character inside the quoted string
+                    let at = span.clone().map(|s| {
+                        let start = s.start + 1 + e.offset;
+                        start..start
+                    });
+                    self.report(at.or(span), format!("`{}`: {}", path, e.message));
+                }
+            }
+            (Schema::OneOf(allowed), Value::String(choice)) => {
+                if !allowed.contains(&choice.value().as_str()) {
+                    self.report(
+                        span,
+                        format!(
+                            "`{}` must be one of: {}, found \"{}\"",
+                            path,
+                            allowed.join(", "),
+                            choice.value()
+                        ),
+                    );
+                }
+            }
+            (Schema::Threshold, Value::Integer(_) | Value::Float(_)) => {
+                let number = value
+                    .as_float()
+                    .or_else(|| value.as_integer().map(|i| i as f64))
+                    .unwrap_or_default();
+                if !number.is_finite() || number <= 0.0 {
+                    self.report(
+                        span,
+                        format!("`{}` must be a positive number, found {}", path, number),
+                    );
+                }
+            }
+            (Schema::Price, Value::Integer(_) | Value::Float(_)) => {
+                let number = value
+                    .as_float()
+                    .or_else(|| value.as_integer().map(|i| i as f64))
+                    .unwrap_or_default();
+                if !number.is_finite() || number < 0.0 {
+                    self.report(
+                        span,
+                        format!("`{}` must be a non-negative number, found {}", path, number),
+                    );
+                }
+            }
+            (Schema::PositiveCount, Value::Integer(count)) => {
+                if *count.value() <= 0 {
+                    self.report(
+                        span,
+                        format!(
+                            "`{}` must be a positive integer, found {}",
+                            path,
+                            count.value()
+                        ),
+                    );
+                }
+            }
+            (Schema::Count, Value::Integer(count)) => {
+                if *count.value() < 0 {
+                    self.report(
+                        span,
+                        format!(
+                            "`{}` must be a non-negative integer, found {}",
+                            path,
+                            count.value()
+                        ),
+                    );
+                }
+            }
+            _ => self.report(
+                span,
+                format!(
+                    "`{}` must be {}, found {}",
+                    path,
+                    expected_name(schema),
+                    value.type_name()
+                ),
+            ),
+        }
+    }
+
+    /// The medium burn-rate threshold has to stay below the high one
+    fn check_burn_rate_order(&mut self, root: &dyn TableLike) {
+        let Some(burn_rate) = root.get("burn_rate").and_then(|item| item.as_table_like()) else {
+            return;
+        };
+
+        let number = |key: &str| {
+            burn_rate.get(key).and_then(|item| {
+                item.as_float()
+                    .or_else(|| item.as_integer().map(|i| i as f64))
+            })
+        };
+
+        if let (Some(high), Some(medium)) = (number("high"), number("medium")) {
+            if medium >= high {
+                let span = burn_rate.get("medium").and_then(|item| item.span());
+                self.report(
+                    span,
+                    format!(
+                        "`burn_rate.medium` ({}) must be lower than `burn_rate.high` ({})",
+                        medium, high
+                    ),
+                );
+            }
+        }
+    }
+
+    /// The warning threshold has to stay below the critical one, and compaction
+    /// cannot happen past the end of the window
+    fn check_context_thresholds(&mut self, root: &dyn TableLike) {
+        let Some(context) = root.get("context").and_then(|item| item.as_table_like()) else {
+            return;
+        };
+
+        let number = |key: &str| {
+            context.get(key).and_then(|item| {
+                item.as_float()
+                    .or_else(|| item.as_integer().map(|i| i as f64))
+            })
+        };
+
+        // A threshold that is left out takes its default, which must still be ordered
+        let defaults = ContextConfig::default();
+        let described = |key: &str, default: f64| match number(key) {
+            Some(value) => (value, value.to_string()),
+            None => (default, format!("{}, the default", default)),
+        };
+        if number("warning").is_some() || number("critical").is_some() {
+            let (warning, warning_text) = described("warning", defaults.warning());
+            let (critical, critical_text) = described("critical", defaults.critical());
+            if warning >= critical {
+                let key = if number("warning").is_some() {
+                    "warning"
+                } else {
+                    "critical"
+                };
+                let span = context.get(key).and_then(|item| item.span());
+                self.report(
+                    span,
+                    format!(
+                        "`context.warning` ({}) must be lower than `context.critical` ({})",
+                        warning_text, critical_text
+                    ),
+                );
+            }
+        }
+        if let Some(ratio) = number("compact_ratio").filter(|ratio| *ratio > 1.0) {
+            let span = context.get("compact_ratio").and_then(|item| item.span());
+            self.report(
+                span,
+                format!("`context.compact_ratio` ({}) must be at most 1", ratio),
+            );
+        }
+    }
+
+    /// `theme` and every `base` must name a built-in theme or one defined in this file
+    fn check_theme_names(&mut self, root: &dyn TableLike) {
+        let themes = root.get("themes").and_then(|item| item.as_table_like());
+        let known = |name: &str| {
+            BUILTIN_THEMES.contains(&name) || themes.is_some_and(|t| t.contains_key(name))
+        };
+
+        let mut references = Vec::new();
+        if let Some(item) = root.get("theme") {
+            references.push(("theme".to_string(), item));
+        }
+        if let Some(themes) = themes {
+            for (name, theme) in themes.iter() {
+                if let Some(base) = theme.as_table_like().and_then(|t| t.get("base")) {
+                    references.push((format!("themes.{}.base", name), base));
+                }
+            }
+        }
+
+        for (path, item) in references {
+            let Some(name) = item.as_str() else {
+                continue;
+            };
+            if !known(name) {
+                self.report(
+                    item.span(),
+                    format!(
+                        "`{}` refers to unknown theme \"{}\" (built-in themes: {})",
+                        path,
+                        name,
+                        BUILTIN_THEMES.join(", ")
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Every `[[segments]]` entry needs a `type`; `window` only makes sense for cost
+    fn check_segment_entries(&mut self, root: &dyn TableLike) {
+        let Some(segments) = root.get("segments") else {
+            return;
+        };
+
+        let mut entries: Vec<(&dyn TableLike, Option<Range<usize>>)> = Vec::new();
+        if let Some(tables) = segments.as_array_of_tables() {
+            entries.extend(tables.iter().map(|t| (t as &dyn TableLike, t.span())));
+        } else if let Some(array) = segments.as_array() {
+            entries.extend(
+                array
+                    .iter()
+                    .filter_map(|v| v.as_inline_table())
+                    .map(|t| (t as &dyn TableLike, t.span())),
+            );
+        }
+
+        for (i, (entry, span)) in entries.into_iter().enumerate() {
+            let kind = entry.get("type").and_then(|item| item.as_str());
+            if entry.get("type").is_none() {
+                self.report(
+                    span,
+                    format!(
+                        "`segments[{}]` is missing `type` (expected one of: {})",
+                        i,
+                        SEGMENT_TYPES.join(", ")
+                    ),
+                );
+            }
+            if let (Some(window), Some(kind)) = (entry.get("window"), kind) {
+                if kind != "cost" {
+                    self.report(
+                        window.span().or(entry.key("window").and_then(|k| k.span())),
+                        format!(
+                            "`segments[{}].window` only applies to cost segments, not `{}`",
+                            i, kind
+                        ),
+                    );
+                }
+            }
+            if let Some(line) = entry.get("line") {
+                if line.as_integer() == Some(0) {
+                    self.report(
+                        line.span().or(entry.key("line").and_then(|k| k.span())),
+                        format!("`segments[{}].line` counts from 1", i),
+                    );
+                }
+            }
+        }
+    }
+
+    fn report(&mut self, span: Option<Range<usize>>, message: String) {
+        let offset = span.map(|s| s.start).unwrap_or(0);
+        let (line, column) = line_column(self.content, offset);
+        self.diagnostics.push(Diagnostic {
+            path: self.path.to_path_buf(),
+            line,
+            column,
+            message,
+        });
+    }
+}
+
+fn expected_name(schema: &Schema) -> &'static str {
+    match schema {
+        Schema::Bool => "a boolean",
+        Schema::String => "a string",
+        Schema::Threshold => "a positive number",
+        Schema::Count => "a non-negative integer",
+        Schema::PositiveCount => "a positive integer",
+        Schema::Price => "a non-negative number",
+        Schema::Color | Schema::Template | Schema::OneOf(_) => "a string",
+        Schema::Map(_) => "a table",
+        Schema::List(_) => "an array",
+        Schema::ListOrTable { .. } => "an array of tables or a table",
+        Schema::Table { .. } => "a table",
+    }
+}
+
+fn join_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Convert a byte offset into a 1-based (line, column) pair
+fn line_column(content: &str, offset: usize) -> (usize, usize) {
+    let offset = offset.min(content.len());
+    let before = &content[..offset];
+    let line = before.matches('\n').count() + 1;
+    let line_start = before.rfind('\n').map(|i| i + 1).unwrap_or(0);
+    let column = before[line_start..].chars().count() + 1;
+    (line, column)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn validate(content: &str) -> Vec<Diagnostic> {
+        validate_str(content, Path::new("config.toml"))
+    }
+
+    #[test]
+    fn test_valid_config_has_no_diagnostics() {
+        let content = "theme = \"dark\"\n\n[segments]\ngit = false\n\n[render]\nmode = \"powerline\"\npowerline_arrow = \"\u{e0b0}\"\n\n[burn_rate]\nhigh = 6000\nmedium = 2500.5\n";
+        assert!(validate(content).is_empty());
+    }
+
+    #[test]
+    fn test_unknown_segment_is_located() {
+        let diagnostics = validate("[segments]\ngit = true\ngti = false\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 1));
+        assert!(diagnostics[0].message.contains("unknown segment `gti`"));
+    }
+
+    #[test]
+    fn test_wrong_type_is_located() {
+        let diagnostics = validate("theme = \"dark\"\n[segments]\ncost = \"yes\"\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (3, 8));
+        assert!(diagnostics[0]
+            .message
+            .contains("`segments.cost` must be a boolean, found string"));
+    }
+
+    #[test]
+    fn test_bad_thresholds() {
+        let diagnostics = validate("[burn_rate]\nhigh = 1000\nmedium = -5\n");
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.message.contains("must be a positive number")));
+
+        let diagnostics = validate("[burn_rate]\nhigh = 1000\nmedium = 3000\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 3);
+        assert!(diagnostics[0].message.contains("must be lower than"));
+
+        let diagnostics = validate("[context]\nwarning = 80\ncritical = 70\ncompact_ratio = 1.5\n");
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0].message.contains("`context.warning` (80)"));
+        assert_eq!(diagnostics[1].line, 4);
+
+        // Thresholds that are left out are compared at their defaults (60 and 75)
+        let diagnostics = validate("[context]\nwarning = 80\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 2);
+        assert!(diagnostics[0]
+            .message
+            .contains("`context.critical` (75, the default)"));
+        let diagnostics = validate("[context]\ncritical = 50\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert!(diagnostics[0]
+            .message
+            .contains("`context.warning` (60, the default)"));
+        assert!(validate("[context]\nwarning = 70\n").is_empty());
+        assert!(validate("[context]\ncritical = 90\n").is_empty());
+
+        // A zero context window would divide by zero
+        let diagnostics =
+            validate("[context]\ndefault_limit = 0\n\n[context.limits]\n\"gpt-5\" = 0\n");
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .all(|d| d.message.contains("must be a positive integer, found 0")));
+    }
+
+    #[test]
+    fn test_bar_colors_are_checked() {
+        assert!(validate("[bar]\nwidth = 8\ncolors = [\"green\", \"#ff0000\"]\n").is_empty());
+
+        let diagnostics = validate("[bar]\ncolors = [\"green\", \"nope\"]\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 20));
+        assert!(diagnostics[0]
+            .message
+            .contains("`bar.colors[1]`: unknown color"));
+    }
+
+    #[test]
+    fn test_syntax_error_is_reported() {
+        let diagnostics = validate("theme = \"dark\"\nsegments = [\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(diagnostics[0].line, 3);
+    }
+
+    #[test]
+    fn test_theme_definitions() {
+        let content = "theme = \"mine\"\ncolor_mode = \"256\"\n\n[themes.mine]\nbase = \"nord\"\nmodel = { fg = \"#88c0d0\", bold = true }\n";
+        assert!(validate(content).is_empty());
+
+        let diagnostics = validate("[themes.mine]\ngit = { fg = \"#12345\" }\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].column), (2, 14));
+        assert!(diagnostics[0].message.contains("themes.mine.git.fg"));
+    }
+
+    #[test]
+    fn test_unknown_theme_and_color_mode() {
+        let diagnostics = validate("theme = \"drak\"\ncolor_mode = \"24bit\"\nicons = \"emoji\"\n");
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.line == 1 && d.message.contains("unknown theme \"drak\"")));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.line == 2 && d.message.contains("must be one of")));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.line == 3 && d.message.contains("unicode-emoji, plain-ascii")));
+    }
+
+    #[test]
+    fn test_segment_entries() {
+        let content = "[[segments]]\ntype = \"model\"\nicon = \"M\"\n\n[[segments]]\ntype = \"cost\"\nwindow = \"today\"\ncolor = \"yellow\"\n";
+        assert!(validate(content).is_empty());
+
+        let diagnostics = validate(
+            "[[segments]]\ntype = \"modle\"\n\n[[segments]]\nicon = \"x\"\n\n[[segments]]\ntype = \"git\"\nwindow = \"today\"\n",
+        );
+        assert_eq!(diagnostics.len(), 3);
+        assert_eq!(diagnostics[0].line, 2);
+        assert!(diagnostics[0]
+            .message
+            .contains("`segments[0].type` must be one of"));
+        assert!(diagnostics[1]
+            .message
+            .contains("`segments[1]` is missing `type`"));
+        assert_eq!(diagnostics[2].line, 9);
+        assert!(diagnostics[2]
+            .message
+            .contains("only applies to cost segments"));
+    }
+
+    #[test]
+    fn test_line_and_align() {
+        let content = "[[segments]]\ntype = \"model\"\n\n[[segments]]\ntype = \"cost\"\nline = 2\nalign = \"right\"\n";
+        assert!(validate(content).is_empty());
+
+        let diagnostics = validate("[[segments]]\ntype = \"cost\"\nline = 0\nalign = \"center\"\n");
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.line == 3 && d.message.contains("counts from 1")));
+        assert!(diagnostics
+            .iter()
+            .any(|d| d.line == 4 && d.message.contains("must be one of: left, right")));
+    }
+
+    #[test]
+    fn test_pricing_section() {
+        let content = "[pricing]\nttl_hours = 12\noffline = true\nmultiplier = 0.8\n\n[pricing.aliases]\n\"arn:aws:bedrock:us-east-1:1:model\" = \"claude-sonnet-4\"\n\n[pricing.models.gateway-llama]\ninput = 0\noutput = 1.5\n";
+        assert!(validate(content).is_empty());
+
+        let diagnostics = validate(
+            "[pricing]\nmultiplier = 0\n\n[pricing.models.x]\ninput = -1\ncache_write = 2\n",
+        );
+        assert_eq!(diagnostics.len(), 3);
+        assert!(diagnostics[0]
+            .message
+            .contains("`pricing.multiplier` must be a positive number"));
+        assert!(diagnostics[1]
+            .message
+            .contains("`pricing.models.x.input` must be a non-negative number"));
+        assert!(diagnostics[2]
+            .message
+            .contains("unknown price key `cache_write`"));
+    }
+
+    #[test]
+    fn test_width_and_priority() {
+        let content = "[render]\nwidth = 80\n\n[[segments]]\ntype = \"git\"\npriority = 5\n";
+        assert!(validate(content).is_empty());
+
+        let diagnostics =
+            validate("[render]\nwidth = -1\n\n[[segments]]\ntype = \"git\"\npriority = \"low\"\n");
+        assert_eq!(diagnostics.len(), 2);
+        assert!(diagnostics[0]
+            .message
+            .contains("`render.width` must be a non-negative integer"));
+        assert!(diagnostics[1]
+            .message
+            .contains("`segments[0].priority` must be a non-negative integer, found string"));
+    }
+
+    #[test]
+    fn test_line_column_counts_characters() {
+        assert_eq!(line_column("a = 1\nb = \"é\" x", 15), (2, 9));
+    }
+}
@@ -0,0 +1,154 @@
+//! Inline progress bars shared by segments
+
+use crate::core::theme::{Color, ColorMode, Style};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_WIDTH: usize = 10;
+const DEFAULT_FILLED: &str = "▰";
+const DEFAULT_EMPTY: &str = "▱";
+
+/// Look of the progress bars drawn by segments (`{bar}` variables)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BarConfig {
+    /// Number of cells (default 10)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    /// Glyph for a filled cell (default `▰`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub filled: Option<String>,
+    /// Glyph for an empty cell (default `▱`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub empty: Option<String>,
+    /// Gradient stops from an empty to a full bar (default green, yellow, red);
+    /// an empty list leaves the bar in the segment's color
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub colors: Option<Vec<Color>>,
+}
+
+impl BarConfig {
+    pub fn width(&self) -> usize {
+        self.width.unwrap_or(DEFAULT_WIDTH)
+    }
+
+    pub fn filled(&self) -> &str {
+        self.filled.as_deref().unwrap_or(DEFAULT_FILLED)
+    }
+
+    pub fn empty(&self) -> &str {
+        self.empty.as_deref().unwrap_or(DEFAULT_EMPTY)
+    }
+
+    pub fn colors(&self) -> Vec<Color> {
+        self.colors
+            .clone()
+            .unwrap_or_else(|| vec![Color::Ansi(2), Color::Ansi(3), Color::Ansi(1)])
+    }
+}
+
+/// Progress bar renderer bound to the terminal's color depth
+#[derive(Debug, Clone)]
+pub struct Bar {
+    width: usize,
+    filled: String,
+    empty: String,
+    colors: Vec<Color>,
+    mode: ColorMode,
+}
+
+impl Default for Bar {
+    fn default() -> Self {
+        Bar::new(&BarConfig::default(), ColorMode::Ansi16)
+    }
+}
+
+impl Bar {
+    pub fn new(config: &BarConfig, mode: ColorMode) -> Self {
+        Self {
+            width: config.width(),
+            filled: config.filled().to_string(),
+            empty: config.empty().to_string(),
+            colors: config.colors(),
+            mode,
+        }
+    }
+
+    /// Draw a bar filled to `fraction` (clamped to 0..=1)
+    ///
+    /// Each filled cell takes the gradient color at its position, so a nearly
+    /// full bar runs through every stop while a short one keeps the first.
+    /// Empty cells are left in the surrounding segment's style.
+    pub fn render(&self, fraction: f64) -> String {
+        let fraction = if fraction.is_finite() {
+            fraction.clamp(0.0, 1.0)
+        } else {
+            0.0
+        };
+        let filled = ((fraction * self.width as f64).round() as usize).min(self.width);
+
+        let mut bar = String::new();
+        for cell in 0..filled {
+            match gradient(&self.colors, (cell as f64 + 0.5) / self.width as f64) {
+                Some(color) => {
+                    let style = Style {
+                        fg: Some(color),
+                        ..Style::default()
+                    };
+                    bar.push_str(&style.paint(&self.filled, self.mode));
+                }
+                None => bar.push_str(&self.filled),
+            }
+        }
+        bar.push_str(&self.empty.repeat(self.width - filled));
+        bar
+    }
+}
+
+/// Color at `position` (0..=1) along evenly spaced `stops`
+fn gradient(stops: &[Color], position: f64) -> Option<Color> {
+    match stops {
+        [] => None,
+        [only] => Some(*only),
+        _ => {
+            let scaled = position.clamp(0.0, 1.0) * (stops.len() - 1) as f64;
+            let index = (scaled.floor() as usize).min(stops.len() - 2);
+            Some(stops[index].mix(stops[index + 1], scaled - index as f64))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::width::display_width;
+
+    #[test]
+    fn test_bar_fill_and_gradient() {
+        let plain = Bar::new(
+            &BarConfig {
+                width: Some(6),
+                colors: Some(Vec::new()),
+                ..BarConfig::default()
+            },
+            ColorMode::TrueColor,
+        );
+        assert_eq!(plain.render(0.5), "▰▰▰▱▱▱");
+        assert_eq!(plain.render(1.7), "▰▰▰▰▰▰");
+        assert_eq!(plain.render(f64::NAN), "▱▱▱▱▱▱");
+
+        let colored = Bar::new(
+            &BarConfig {
+                width: Some(4),
+                filled: Some("#".to_string()),
+                empty: Some(".".to_string()),
+                colors: Some(vec![Color::Rgb(0, 255, 0), Color::Rgb(255, 0, 0)]),
+            },
+            ColorMode::TrueColor,
+        );
+        let bar = colored.render(0.5);
+        assert_eq!(display_width(&bar), 4);
+        assert!(bar.starts_with("\x1b[38;2;32;223;0m#\x1b[0m"));
+        assert!(bar.ends_with(".."));
+        assert!(colored.render(1.0).contains("\x1b[38;2;223;32;0m#"));
+    }
+}
@@ -0,0 +1,177 @@
+use crate::utils::resolve_config_dir;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+const CACHE_DIR: &str = "segment_cache";
+/// Single shared cache file of earlier versions, removed when found
+const LEGACY_FILE: &str = ".segment_cache.json";
+/// Session caches not written for this long are removed
+const MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Last rendered output of every segment of one session, shown when a segment
+/// misses its deadline
+///
+/// Each session has its own file, so concurrent sessions never overwrite each
+/// other's outputs, and files of sessions idle for a week are removed.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SegmentCache {
+    #[serde(default)]
+    outputs: BTreeMap<String, String>,
+    #[serde(skip)]
+    dirty: bool,
+    #[serde(skip)]
+    path: PathBuf,
+    /// Whether the session had no cache file yet
+    #[serde(skip)]
+    created: bool,
+}
+
+impl SegmentCache {
+    /// Load the cache of `session` from the config directory; a missing or
+    /// corrupt file yields an empty cache
+    pub fn load(session: &str) -> Self {
+        Self::load_from(&resolve_config_dir().join(CACHE_DIR), session)
+    }
+
+    fn load_from(dir: &Path, session: &str) -> Self {
+        let path = dir.join(format!("{}.json", file_stem(session)));
+        let content = std::fs::read_to_string(&path).ok();
+        let created = content.is_none();
+        let cache: Self = content
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default();
+        Self {
+            path,
+            created,
+            ..cache
+        }
+    }
+
+    /// Write the cache back when any output changed
+    ///
+    /// The first save of a session also removes the caches of idle sessions.
+    pub fn save(&self) -> Result<(), std::io::Error> {
+        if !self.dirty {
+            return Ok(());
+        }
+
+        if let Some(dir) = self.path.parent() {
+            std::fs::create_dir_all(dir)?;
+            if self.created {
+                prune(dir, SystemTime::now() - MAX_AGE);
+                if let Some(config_dir) = dir.parent() {
+                    let _ = std::fs::remove_file(config_dir.join(LEGACY_FILE));
+                }
+            }
+        }
+        // Write to a sibling file first so concurrent readers never see a partial file
+        let temp = self
+            .path
+            .with_extension(format!("json.{}", std::process::id()));
+        std::fs::write(&temp, serde_json::to_string(self)?)?;
+        std::fs::rename(&temp, &self.path)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.outputs.get(key).map(String::as_str)
+    }
+
+    pub fn set(&mut self, key: String, output: String) {
+        if self.outputs.get(&key) != Some(&output) {
+            self.outputs.insert(key, output);
+            self.dirty = true;
+        }
+    }
+}
+
+/// `session` with every character that is unsafe in a file name replaced
+fn file_stem(session: &str) -> String {
+    let stem: String = session
+        .chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    if stem.is_empty() {
+        "default".to_string()
+    } else {
+        stem
+    }
+}
+
+/// Remove the files in `dir` that were last written before `cutoff`
+fn prune(dir: &Path, cutoff: SystemTime) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.flatten() {
+        let expired = entry
+            .metadata()
+            .and_then(|metadata| metadata.modified())
+            .is_ok_and(|modified| modified < cutoff);
+        if expired {
+            let _ = std::fs::remove_file(entry.path());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_marks_changes_only() {
+        let mut cache = SegmentCache::default();
+        cache.set("git".to_string(), "main ✓".to_string());
+        assert!(cache.dirty);
+        assert_eq!(cache.get("git"), Some("main ✓"));
+
+        let mut reloaded: SegmentCache =
+            serde_json::from_str(&serde_json::to_string(&cache).unwrap()).unwrap();
+        assert!(!reloaded.dirty);
+        reloaded.set("git".to_string(), "main ✓".to_string());
+        assert!(!reloaded.dirty);
+    }
+
+    #[test]
+    fn test_sessions_have_separate_files() {
+        let root =
+            std::env::temp_dir().join(format!("ccline-segment-cache-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&root);
+        let dir = root.join(CACHE_DIR);
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(root.join(LEGACY_FILE), "{}").unwrap();
+        std::fs::write(dir.join("idle.json"), "{}").unwrap();
+
+        let mut first = SegmentCache::load_from(&dir, "a/b:1");
+        let mut second = SegmentCache::load_from(&dir, "c");
+        first.set("0:model".to_string(), "Sonnet".to_string());
+        second.set("0:model".to_string(), "Opus".to_string());
+        first.save().unwrap();
+        second.save().unwrap();
+
+        assert_eq!(
+            SegmentCache::load_from(&dir, "a/b:1").get("0:model"),
+            Some("Sonnet")
+        );
+        assert_eq!(
+            SegmentCache::load_from(&dir, "c").get("0:model"),
+            Some("Opus")
+        );
+        assert!(dir.join("a_b_1.json").exists());
+        assert!(!root.join(LEGACY_FILE).exists());
+
+        // Only files older than the cutoff are removed
+        assert!(dir.join("idle.json").exists());
+        prune(&dir, SystemTime::now() + Duration::from_secs(60));
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,229 @@
+use crate::billing::{
+    block::{find_active_block, identify_session_blocks_with_overrides},
+    calculator::fill_costs,
+    pricing_cache::{self, PricingCache, PricingSource},
+    table::PricingTable,
+    BillingBlock, ModelPricing, PricingConfig, UsageEntry,
+};
+use crate::config::InputData;
+use crate::utils::data_loader::DataLoader;
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+type PricingLoader = Box<dyn Fn(&PricingConfig) -> HashMap<String, ModelPricing> + Send + Sync>;
+type EntriesLoader = Box<dyn Fn() -> Vec<UsageEntry> + Send + Sync>;
+
+/// Data shared by every segment during one statusline invocation
+///
+/// Transcript entries, pricing and billing blocks are computed on first use and
+/// memoized, so segments running in parallel share a single load. Concurrent
+/// callers of the same accessor wait for the first one to finish.
+pub struct RenderContext {
+    input: InputData,
+    pricing_config: PricingConfig,
+    load_pricing: PricingLoader,
+    load_entries: EntriesLoader,
+    pricing: OnceLock<HashMap<String, ModelPricing>>,
+    local_pricing: OnceLock<HashMap<String, ModelPricing>>,
+    entries: OnceLock<Vec<UsageEntry>>,
+    blocks: OnceLock<Vec<BillingBlock>>,
+    active_block: OnceLock<Option<usize>>,
+}
+
+impl RenderContext {
+    pub fn new(input: InputData) -> Self {
+        Self {
+            input,
+            pricing_config: PricingConfig::default(),
+            load_pricing: Box::new(load_pricing),
+            load_entries: Box::new(|| DataLoader::new().load_all_projects()),
+            pricing: OnceLock::new(),
+            local_pricing: OnceLock::new(),
+            entries: OnceLock::new(),
+            blocks: OnceLock::new(),
+            active_block: OnceLock::new(),
+        }
+    }
+
+    /// Use `config` to locate, cache and refresh pricing
+    pub fn with_pricing_config(mut self, config: PricingConfig) -> Self {
+        self.pricing_config = config;
+        self
+    }
+
+    /// Replace the pricing and transcript loaders, to observe how often they run
+    #[cfg(test)]
+    fn with_loaders(mut self, pricing: PricingLoader, entries: EntriesLoader) -> Self {
+        self.load_pricing = pricing;
+        self.load_entries = entries;
+        self
+    }
+
+    /// Session payload read from stdin
+    pub fn input(&self) -> &InputData {
+        &self.input
+    }
+
+    /// Model pricing from the on-disk cache, LiteLLM or the built-in table,
+    /// with the configured overrides, aliases and multiplier applied
+    ///
+    /// A stale cache is used as-is while a detached process refreshes it.
+    pub fn pricing(&self) -> &HashMap<String, ModelPricing> {
+        self.pricing
+            .get_or_init(|| (self.load_pricing)(&self.pricing_config))
+    }
+
+    /// Pricing from the on-disk cache or the built-in table, never fetched
+    ///
+    /// Used where a stale price does no harm, such as context limits. Once
+    /// [`RenderContext::pricing`] has loaded, its table is returned instead.
+    pub fn local_pricing(&self) -> &HashMap<String, ModelPricing> {
+        if let Some(pricing) = self.pricing.get() {
+            return pricing;
+        }
+        self.local_pricing.get_or_init(|| {
+            let offline = PricingConfig {
+                offline: true,
+                ..self.pricing_config.clone()
+            };
+            (self.load_pricing)(&offline)
+        })
+    }
+
+    /// Usage entries from every known project, with costs filled in
+    pub fn entries(&self) -> &[UsageEntry] {
+        self.entries.get_or_init(|| {
+            let pricing = self.pricing();
+            let mut entries = (self.load_entries)();
+            fill_costs(&mut entries, pricing);
+            entries
+        })
+    }
+
+    /// Billing blocks across all entries, honouring block start overrides
+    pub fn blocks(&self) -> &[BillingBlock] {
+        self.blocks
+            .get_or_init(|| identify_session_blocks_with_overrides(self.entries()))
+    }
+
+    /// Block that is still running, if any
+    pub fn active_block(&self) -> Option<&BillingBlock> {
+        let blocks = self.blocks();
+        let index = self.active_block.get_or_init(|| {
+            find_active_block(blocks)
+                .and_then(|active| blocks.iter().position(|block| std::ptr::eq(block, active)))
+        });
+        index.map(|i| &blocks[i])
+    }
+}
+
+fn load_pricing(config: &PricingConfig) -> HashMap<String, ModelPricing> {
+    let path = PricingCache::default_path();
+    let loaded = pricing_cache::load(config, &path);
+    if loaded.source == PricingSource::Stale {
+        pricing_cache::spawn_refresh(config, &path);
+    }
+    PricingTable::build(loaded, config).into_models()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::segments::{BlockSegment, BurnRateSegment, CostSegment, Segment};
+    use chrono::{Duration, Utc};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_loads_once_across_segments_and_threads() {
+        let pricing_calls = Arc::new(AtomicUsize::new(0));
+        let entry_calls = Arc::new(AtomicUsize::new(0));
+        let input = InputData::from_value(serde_json::json!({
+            "model": {"id": "claude-sonnet-4", "display_name": "Sonnet 4"},
+            "workspace": {"current_dir": "/tmp"},
+            "transcript_path": "/tmp/session-1.jsonl"
+        }))
+        .unwrap();
+
+        let ctx = RenderContext::new(input).with_loaders(
+            Box::new({
+                let calls = Arc::clone(&pricing_calls);
+                move |_| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    HashMap::new()
+                }
+            }),
+            Box::new({
+                let calls = Arc::clone(&entry_calls);
+                move || {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    // Give concurrent callers time to pile up on the first load
+                    std::thread::sleep(std::time::Duration::from_millis(20));
+                    vec![UsageEntry {
+                        timestamp: Utc::now() - Duration::minutes(5),
+                        input_tokens: 1000,
+                        output_tokens: 100,
+                        cache_creation_tokens: 0,
+                        cache_read_tokens: 0,
+                        model: "claude-sonnet-4".to_string(),
+                        cost: None,
+                        session_id: "session-1".to_string(),
+                    }]
+                }
+            }),
+        );
+
+        let segments: Vec<Box<dyn Segment>> = vec![
+            Box::new(CostSegment::new(true)),
+            Box::new(BurnRateSegment::new(true)),
+            Box::new(BlockSegment::new(true)),
+        ];
+        std::thread::scope(|scope| {
+            for segment in segments {
+                let ctx = &ctx;
+                scope.spawn(move || segment.variables(ctx));
+            }
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    assert_eq!(ctx.entries().len(), 1);
+                    assert!(ctx.active_block().is_some());
+                });
+            }
+        });
+
+        assert_eq!(pricing_calls.load(Ordering::SeqCst), 1);
+        assert_eq!(entry_calls.load(Ordering::SeqCst), 1);
+        // Accessors hand out the memoized values
+        assert!(std::ptr::eq(ctx.entries(), ctx.entries()));
+        assert!(std::ptr::eq(ctx.blocks(), ctx.blocks()));
+    }
+
+    #[test]
+    fn test_local_pricing_never_fetches() {
+        let input = InputData::from_value(serde_json::json!({
+            "model": {"id": "claude-sonnet-4", "display_name": "Sonnet 4"},
+            "workspace": {"current_dir": "/tmp"},
+            "transcript_path": "/tmp/session-1.jsonl"
+        }))
+        .unwrap();
+        let offline_flags = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let ctx = RenderContext::new(input).with_loaders(
+            Box::new({
+                let flags = Arc::clone(&offline_flags);
+                move |config| {
+                    flags.lock().unwrap().push(config.offline);
+                    HashMap::new()
+                }
+            }),
+            Box::new(Vec::new),
+        );
+
+        ctx.local_pricing();
+        ctx.local_pricing();
+        assert_eq!(*offline_flags.lock().unwrap(), vec![true]);
+
+        // Once the full table is loaded it is shared
+        assert!(std::ptr::eq(ctx.pricing(), ctx.local_pricing()));
+        assert_eq!(*offline_flags.lock().unwrap(), vec![true, false]);
+    }
+}
@@ -0,0 +1,252 @@
+use serde::{Deserialize, Serialize};
+
+/// Every glyph the statusline draws, looked up through [`IconPack::glyph`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Icon {
+    Model,
+    Directory,
+    Git,
+    GitClean,
+    GitDirty,
+    GitConflicts,
+    Ahead,
+    Behind,
+    Usage,
+    UsageWarning,
+    UsageCritical,
+    Cost,
+    BurnHigh,
+    BurnMedium,
+    BurnNormal,
+    Block,
+    UpdateReady,
+    Updating,
+    Updated,
+}
+
+impl Icon {
+    /// Every icon, in declaration order
+    pub const ALL: [Icon; 19] = [
+        Icon::Model,
+        Icon::Directory,
+        Icon::Git,
+        Icon::GitClean,
+        Icon::GitDirty,
+        Icon::GitConflicts,
+        Icon::Ahead,
+        Icon::Behind,
+        Icon::Usage,
+        Icon::UsageWarning,
+        Icon::UsageCritical,
+        Icon::Cost,
+        Icon::BurnHigh,
+        Icon::BurnMedium,
+        Icon::BurnNormal,
+        Icon::Block,
+        Icon::UpdateReady,
+        Icon::Updating,
+        Icon::Updated,
+    ];
+}
+
+/// Set of glyphs used for segment icons
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum IconPack {
+    /// Pick a pack from the environment (see [`IconPack::resolve`])
+    #[default]
+    Auto,
+    /// Nerd Font private-use glyphs
+    NerdFont,
+    /// Standard Unicode symbols and emoji
+    UnicodeEmoji,
+    /// ASCII only
+    PlainAscii,
+    /// No icons at all
+    None,
+}
+
+impl IconPack {
+    pub const NAMES: &'static [&'static str] =
+        &["auto", "nerd-font", "unicode-emoji", "plain-ascii", "none"];
+
+    /// Resolve `Auto` into a concrete pack
+    ///
+    /// Terminals that cannot show Unicode (`TERM=dumb`, the Linux console, or a
+    /// non-UTF-8 locale) get `plain-ascii`. Installed fonts cannot be detected,
+    /// so Nerd Font glyphs are only picked for terminals that bundle them
+    /// (WezTerm, kitty and Ghostty); other terminals get `unicode-emoji`.
+    pub fn resolve(self) -> IconPack {
+        self.resolve_with(|name| std::env::var(name).ok())
+    }
+
+    fn resolve_with<F>(self, lookup: F) -> IconPack
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if self != IconPack::Auto {
+            return self;
+        }
+
+        let term = lookup("TERM").unwrap_or_default();
+        if term == "dumb" || term == "linux" {
+            return IconPack::PlainAscii;
+        }
+        let term_program = lookup("TERM_PROGRAM").unwrap_or_default();
+        let bundles_nerd_font = term == "xterm-kitty"
+            || term == "xterm-ghostty"
+            || matches!(term_program.as_str(), "WezTerm" | "ghostty");
+
+        // The first locale variable that is set decides the character set
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| lookup(name).filter(|value| !value.is_empty()));
+        match locale {
+            Some(locale) if !locale.to_lowercase().replace('-', "").contains("utf8") => {
+                IconPack::PlainAscii
+            }
+            _ if bundles_nerd_font => IconPack::NerdFont,
+            _ => IconPack::UnicodeEmoji,
+        }
+    }
+
+    /// Glyph for `icon` in this pack; empty when the pack has none
+    pub fn glyph(self, icon: Icon) -> &'static str {
+        match self {
+            IconPack::Auto | IconPack::NerdFont => nerd_font(icon),
+            IconPack::UnicodeEmoji => unicode_emoji(icon),
+            IconPack::PlainAscii => plain_ascii(icon),
+            IconPack::None => "",
+        }
+    }
+}
+
+fn nerd_font(icon: Icon) -> &'static str {
+    match icon {
+        Icon::Model => "\u{e26d}",
+        Icon::Directory => "\u{f024b}",
+        Icon::Git => "\u{f02a2}",
+        Icon::GitClean => "✓",
+        Icon::GitDirty => "●",
+        Icon::GitConflicts => "⚠",
+        Icon::Ahead => "↑",
+        Icon::Behind => "↓",
+        Icon::Usage => "\u{f49b}",
+        Icon::UsageWarning => "\u{f071}",
+        Icon::UsageCritical => "\u{f06a}",
+        Icon::Cost => "\u{f155}",
+        Icon::BurnHigh => "\u{ef76}",
+        Icon::BurnMedium => "\u{f0e7}",
+        Icon::BurnNormal => "\u{f0e4}",
+        Icon::Block => "\u{f252}",
+        Icon::UpdateReady => "\u{f06b0}",
+        Icon::Updating => "\u{f01da}",
+        Icon::Updated => "\u{f058}",
+    }
+}
+
+fn unicode_emoji(icon: Icon) -> &'static str {
+    match icon {
+        Icon::Model => "🤖",
+        Icon::Directory => "📁",
+        Icon::Git => "🌿",
+        Icon::GitClean => "✓",
+        Icon::GitDirty => "●",
+        Icon::GitConflicts => "⚠",
+        Icon::Ahead => "↑",
+        Icon::Behind => "↓",
+        Icon::Usage => "📊",
+        Icon::UsageWarning => "⚠",
+        Icon::UsageCritical => "🚨",
+        Icon::Cost => "💰",
+        Icon::BurnHigh => "🔥",
+        Icon::BurnMedium => "⚡",
+        Icon::BurnNormal => "📈",
+        Icon::Block => "⌛",
+        Icon::UpdateReady => "🆕",
+        Icon::Updating => "⏳",
+        Icon::Updated => "✅",
+    }
+}
+
+fn plain_ascii(icon: Icon) -> &'static str {
+    match icon {
+        Icon::Model => "model:",
+        Icon::Directory => "dir:",
+        Icon::Git => "git:",
+        Icon::GitClean => "ok",
+        Icon::GitDirty => "*",
+        Icon::GitConflicts => "!",
+        Icon::Ahead => "+",
+        Icon::Behind => "-",
+        Icon::Usage => "ctx:",
+        Icon::UsageWarning => "ctx!:",
+        Icon::UsageCritical => "ctx!!:",
+        Icon::Cost => "cost:",
+        Icon::BurnHigh => "!!",
+        Icon::BurnMedium => "!",
+        Icon::BurnNormal => "~",
+        Icon::Block => "block:",
+        Icon::UpdateReady => "update:",
+        Icon::Updating => "updating:",
+        Icon::Updated => "updated:",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_auto_resolution() {
+        let env = |vars: &'static [(&'static str, &'static str)]| {
+            move |name: &str| {
+                vars.iter()
+                    .find(|(key, _)| *key == name)
+                    .map(|(_, value)| value.to_string())
+            }
+        };
+
+        let auto = IconPack::Auto;
+        assert_eq!(
+            auto.resolve_with(env(&[("TERM", "xterm-256color"), ("LANG", "en_US.UTF-8")])),
+            IconPack::UnicodeEmoji
+        );
+        assert_eq!(auto.resolve_with(env(&[])), IconPack::UnicodeEmoji);
+        assert_eq!(
+            auto.resolve_with(env(&[("TERM", "xterm-kitty"), ("LANG", "en_US.UTF-8")])),
+            IconPack::NerdFont
+        );
+        assert_eq!(
+            auto.resolve_with(env(&[("TERM_PROGRAM", "WezTerm")])),
+            IconPack::NerdFont
+        );
+        assert_eq!(
+            auto.resolve_with(env(&[("TERM_PROGRAM", "WezTerm"), ("LANG", "C")])),
+            IconPack::PlainAscii
+        );
+        assert_eq!(
+            auto.resolve_with(env(&[("TERM", "linux")])),
+            IconPack::PlainAscii
+        );
+        assert_eq!(
+            auto.resolve_with(env(&[("LC_ALL", "C"), ("LANG", "en_US.utf8")])),
+            IconPack::PlainAscii
+        );
+        assert_eq!(
+            IconPack::UnicodeEmoji.resolve_with(env(&[("TERM", "dumb")])),
+            IconPack::UnicodeEmoji
+        );
+    }
+
+    #[test]
+    fn test_packs_cover_every_icon() {
+        for icon in Icon::ALL {
+            assert!(!IconPack::NerdFont.glyph(icon).is_empty(), "{:?}", icon);
+            assert!(!IconPack::UnicodeEmoji.glyph(icon).is_empty(), "{:?}", icon);
+            let ascii = IconPack::PlainAscii.glyph(icon);
+            assert!(!ascii.is_empty() && ascii.is_ascii(), "{:?}", icon);
+            assert_eq!(IconPack::None.glyph(icon), "");
+        }
+    }
+}
@@ -1,4 +1,17 @@
+pub mod bar;
+pub mod cache;
+pub mod context;
+pub mod icons;
+pub mod render;
 pub mod segments;
 pub mod statusline;
+pub mod template;
+pub mod theme;
+pub mod width;
 
+pub use bar::{Bar, BarConfig};
+pub use context::RenderContext;
+pub use icons::{Icon, IconPack};
+pub use render::{Align, RenderConfig, RenderMode, StyledSegment};
 pub use statusline::StatusLineGenerator;
+pub use theme::{Color, ColorMode, Style, Theme, ThemeConfig};
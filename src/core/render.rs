@@ -0,0 +1,244 @@
+use super::theme::{Style, Theme};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+const DEFAULT_SEPARATOR: &str = " | ";
+const DEFAULT_POWERLINE_ARROW: &str = "\u{e0b0}";
+const DEFAULT_CAPSULE_LEFT: &str = "\u{e0b6}";
+const DEFAULT_CAPSULE_RIGHT: &str = "\u{e0b4}";
+const DEFAULT_TIMEOUT_MS: u64 = 1000;
+const DEFAULT_PLACEHOLDER: &str = "…";
+
+/// How segments are laid out and separated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RenderMode {
+    /// Colored text joined with a separator (` | ` by default)
+    #[default]
+    Plain,
+    /// Filled background blocks joined by arrow transitions
+    Powerline,
+    /// Each segment drawn as a rounded, filled pill
+    Capsule,
+    /// Colored text joined by a single space
+    Minimal,
+}
+
+/// Side of a row a segment is placed on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Align {
+    #[default]
+    Left,
+    Right,
+}
+
+impl Align {
+    pub fn is_left(&self) -> bool {
+        *self == Align::Left
+    }
+}
+
+/// `[render]` section: layout mode and separator glyphs
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct RenderConfig {
+    pub mode: RenderMode,
+    /// Separator used in plain mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub separator: Option<String>,
+    /// Transition glyph used in powerline mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub powerline_arrow: Option<String>,
+    /// Left cap used in capsule mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capsule_left: Option<String>,
+    /// Right cap used in capsule mode
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub capsule_right: Option<String>,
+    /// Maximum line width in columns; `COLUMNS` is used when unset, `0` disables truncation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub width: Option<usize>,
+    /// Deadline for each segment in milliseconds before its cached output is shown
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout_ms: Option<u64>,
+    /// Shown for a segment that missed its deadline and has no cached output
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub placeholder: Option<String>,
+}
+
+impl RenderConfig {
+    pub fn separator(&self) -> &str {
+        self.separator.as_deref().unwrap_or(DEFAULT_SEPARATOR)
+    }
+
+    pub fn powerline_arrow(&self) -> &str {
+        self.powerline_arrow
+            .as_deref()
+            .unwrap_or(DEFAULT_POWERLINE_ARROW)
+    }
+
+    pub fn capsule_left(&self) -> &str {
+        self.capsule_left.as_deref().unwrap_or(DEFAULT_CAPSULE_LEFT)
+    }
+
+    pub fn capsule_right(&self) -> &str {
+        self.capsule_right
+            .as_deref()
+            .unwrap_or(DEFAULT_CAPSULE_RIGHT)
+    }
+
+    pub fn timeout(&self) -> Duration {
+        Duration::from_millis(self.timeout_ms.unwrap_or(DEFAULT_TIMEOUT_MS))
+    }
+
+    pub fn placeholder(&self) -> &str {
+        self.placeholder.as_deref().unwrap_or(DEFAULT_PLACEHOLDER)
+    }
+}
+
+/// A rendered segment together with the style it is painted in
+#[derive(Debug, Clone, PartialEq)]
+pub struct StyledSegment {
+    pub style: Style,
+    pub content: String,
+}
+
+impl StyledSegment {
+    pub fn new(style: Style, content: String) -> Self {
+        Self { style, content }
+    }
+}
+
+/// Join rendered segments into one line according to the render mode
+pub fn render_line(theme: &Theme, config: &RenderConfig, segments: &[StyledSegment]) -> String {
+    match config.mode {
+        RenderMode::Plain => join_painted(
+            theme,
+            segments,
+            &theme.paint("separator", config.separator()),
+        ),
+        RenderMode::Minimal => join_painted(theme, segments, " "),
+        RenderMode::Powerline => render_powerline(theme, config, segments),
+        RenderMode::Capsule => render_capsule(theme, config, segments),
+    }
+}
+
+fn join_painted(theme: &Theme, segments: &[StyledSegment], separator: &str) -> String {
+    segments
+        .iter()
+        .map(|segment| theme.paint_with(&segment.style, &segment.content))
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn render_powerline(theme: &Theme, config: &RenderConfig, segments: &[StyledSegment]) -> String {
+    let mode = theme.color_mode();
+    let styles: Vec<Style> = segments.iter().map(|s| s.style.filled()).collect();
+
+    let mut line = String::new();
+    for (i, segment) in segments.iter().enumerate() {
+        let style = &styles[i];
+        let padding = style.paint(" ", mode);
+        line.push_str(&padding);
+        line.push_str(&theme.paint_with(style, &segment.content));
+        line.push_str(&padding);
+
+        // The arrow takes this segment's background as its color and sits on the next one's
+        let arrow = Style {
+            fg: style.bg,
+            bg: styles.get(i + 1).and_then(|next| next.bg),
+            ..Style::default()
+        };
+        line.push_str(&arrow.paint(config.powerline_arrow(), mode));
+    }
+    line
+}
+
+fn render_capsule(theme: &Theme, config: &RenderConfig, segments: &[StyledSegment]) -> String {
+    let mode = theme.color_mode();
+    segments
+        .iter()
+        .map(|segment| {
+            let style = segment.style.filled();
+            let cap = Style {
+                fg: style.bg,
+                ..Style::default()
+            };
+            format!(
+                "{}{}{}",
+                cap.paint(config.capsule_left(), mode),
+                theme.paint_with(&style, &segment.content),
+                cap.paint(config.capsule_right(), mode)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::theme::ColorMode;
+    use std::collections::BTreeMap;
+
+    fn theme(name: &str) -> Theme {
+        Theme::resolve(name, &BTreeMap::new(), ColorMode::Ansi16)
+    }
+
+    fn segments(theme: &Theme) -> Vec<StyledSegment> {
+        vec![
+            StyledSegment::new(theme.style("model"), "Sonnet".to_string()),
+            StyledSegment::new(theme.style("git"), "main".to_string()),
+        ]
+    }
+
+    fn config(mode: RenderMode) -> RenderConfig {
+        RenderConfig {
+            mode,
+            ..RenderConfig::default()
+        }
+    }
+
+    #[test]
+    fn test_plain_and_minimal_separators() {
+        let dark = theme("dark");
+        let plain = render_line(&dark, &config(RenderMode::Plain), &segments(&dark));
+        assert_eq!(
+            plain,
+            "\x1b[1;36mSonnet\x1b[0m\x1b[37m | \x1b[0m\x1b[1;34mmain\x1b[0m"
+        );
+
+        let custom = RenderConfig {
+            separator: Some(" · ".to_string()),
+            ..config(RenderMode::Plain)
+        };
+        assert!(render_line(&dark, &custom, &segments(&dark)).contains("\x1b[37m · \x1b[0m"));
+
+        let minimal = render_line(&dark, &config(RenderMode::Minimal), &segments(&dark));
+        assert_eq!(minimal, "\x1b[1;36mSonnet\x1b[0m \x1b[1;34mmain\x1b[0m");
+    }
+
+    #[test]
+    fn test_powerline_transitions_use_backgrounds() {
+        let high_contrast = theme("high-contrast");
+        let line = render_line(
+            &high_contrast,
+            &config(RenderMode::Powerline),
+            &segments(&high_contrast),
+        );
+        // Arrow between model (bright cyan bg) and git (blue bg), then a trailing arrow
+        assert!(line.contains("\x1b[96;44m\u{e0b0}\x1b[0m"));
+        assert!(line.ends_with("\x1b[34m\u{e0b0}\x1b[0m"));
+        assert!(line.contains("\x1b[1;97;44mmain\x1b[0m"));
+    }
+
+    #[test]
+    fn test_block_background_derived_from_foreground() {
+        let dark = theme("dark");
+        let line = render_line(&dark, &config(RenderMode::Capsule), &segments(&dark));
+        // Cyan foreground becomes the capsule background with black text
+        assert!(line.starts_with("\x1b[36m\u{e0b6}\x1b[0m\x1b[1;30;46mSonnet\x1b[0m"));
+        assert!(line.contains("\x1b[36m\u{e0b4}\x1b[0m \x1b[34m\u{e0b6}"));
+    }
+}
@@ -0,0 +1,181 @@
+use super::{Level, Segment};
+use crate::billing::calculator::{calculate_burn_rate, project_block_cost};
+use crate::billing::{BillingBlock, BlockConfig};
+use crate::core::bar::Bar;
+use crate::core::context::RenderContext;
+use crate::core::icons::{Icon, IconPack};
+use crate::core::template::{Value, Variables};
+use chrono::{DateTime, Utc};
+use std::panic::AssertUnwindSafe;
+
+/// Progress of the active 5-hour billing block
+pub struct BlockSegment {
+    enabled: bool,
+    config: BlockConfig,
+    bar: Bar,
+    icons: IconPack,
+}
+
+impl BlockSegment {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            config: BlockConfig::default(),
+            bar: Bar::default(),
+            icons: IconPack::NerdFont,
+        }
+    }
+
+    pub fn with_config(mut self, config: BlockConfig) -> Self {
+        self.config = config;
+        self
+    }
+
+    pub fn with_bar(mut self, bar: Bar) -> Self {
+        self.bar = bar;
+        self
+    }
+
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    fn collect_variables(&self, ctx: &RenderContext) -> Variables {
+        let variables = Variables::new().with("icon", self.icons.glyph(Icon::Block));
+        let Some(block) = ctx.active_block() else {
+            return variables.with("status", "No active block");
+        };
+
+        let mut variables = self.block_variables(block, Utc::now(), variables);
+        if let Some(rate) = calculate_burn_rate(block, ctx.entries()) {
+            variables.set(
+                "projected_cost",
+                Value::Money(project_block_cost(block, &rate)),
+            );
+        }
+        variables
+    }
+
+    /// Time, token and cost values of `block` as seen at `now`
+    fn block_variables(
+        &self,
+        block: &BillingBlock,
+        now: DateTime<Utc>,
+        mut variables: Variables,
+    ) -> Variables {
+        let length = (block.end_time - block.start_time).num_seconds().max(1) as f64;
+        let elapsed = (now - block.start_time).num_seconds().max(0) as f64;
+        let fraction = (elapsed / length).min(1.0);
+
+        variables.set("bar", self.bar.render(fraction));
+        variables.set("percent", Value::Number(fraction * 100.0));
+        variables.set("elapsed", Value::Minutes((elapsed / 60.0) as i64));
+        variables.set("remaining", Value::Minutes(block.remaining_minutes.max(0)));
+        variables.set("tokens", Value::Number(block.total_tokens as f64));
+        variables.set("cost", Value::Money(block.cost));
+        if let Some(budget) = self.config.budget {
+            variables.set("budget", Value::Money(budget));
+        }
+        variables
+    }
+}
+
+impl Segment for BlockSegment {
+    fn variables(&self, ctx: &RenderContext) -> Variables {
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.collect_variables(ctx))) {
+            Ok(variables) => variables,
+            Err(_) => Variables::new()
+                .with("icon", self.icons.glyph(Icon::Block))
+                .with("status", "Error loading data"),
+        }
+    }
+
+    fn default_format(&self) -> &'static str {
+        "{?icon}{icon} {/}{?status}{status}{/}{!status}{bar} {remaining} left · {tokens:k} tokens · {cost}{?projected_cost} → {projected_cost}{/}{/}"
+    }
+
+    /// Warning once the projection exceeds the budget, critical once the spend does
+    fn level(&self, variables: &Variables) -> Level {
+        let Some(budget) = self.config.budget else {
+            return Level::Normal;
+        };
+        let money = |name: &str| match variables.get(name) {
+            Some(Value::Money(amount)) => Some(*amount),
+            _ => None,
+        };
+
+        if money("cost").is_some_and(|cost| cost > budget) {
+            Level::Critical
+        } else if money("projected_cost").is_some_and(|projected| projected > budget) {
+            Level::Warning
+        } else {
+            Level::Normal
+        }
+    }
+
+    fn enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::billing::types::BlockStartSource;
+    use crate::core::bar::BarConfig;
+    use crate::core::template::Template;
+    use crate::core::theme::ColorMode;
+    use chrono::Duration;
+
+    #[test]
+    fn test_block_variables_and_budget() {
+        let now = Utc::now();
+        let block = BillingBlock {
+            start_time: now - Duration::minutes(90),
+            end_time: now + Duration::minutes(210),
+            cost: 6.0,
+            remaining_minutes: 210,
+            is_active: true,
+            session_count: 2,
+            total_tokens: 1_250_000,
+            start_time_source: BlockStartSource::Auto,
+            is_gap: false,
+        };
+        let bar = Bar::new(
+            &BarConfig {
+                colors: Some(Vec::new()),
+                ..BarConfig::default()
+            },
+            ColorMode::Ansi16,
+        );
+        let segment = BlockSegment::new(true)
+            .with_config(BlockConfig { budget: Some(10.0) })
+            .with_bar(bar)
+            .with_icons(IconPack::None);
+
+        let mut variables = segment.block_variables(&block, now, Variables::new());
+        assert_eq!(variables.get("percent"), Some(&Value::Number(30.0)));
+        assert_eq!(segment.level(&variables), Level::Normal);
+
+        variables.set("projected_cost", Value::Money(12.5));
+        assert_eq!(
+            Template::parse(segment.default_format())
+                .unwrap()
+                .render(&variables),
+            "▰▰▰▱▱▱▱▱▱▱ 3h 30m left · 1250k tokens · $6.00 → $12.50"
+        );
+        assert_eq!(segment.level(&variables), Level::Warning);
+
+        variables.set("cost", Value::Money(10.5));
+        assert_eq!(segment.level(&variables), Level::Critical);
+
+        let idle = Variables::new().with("status", "No active block");
+        assert_eq!(
+            Template::parse(segment.default_format())
+                .unwrap()
+                .render(&idle),
+            "No active block"
+        );
+    }
+}
@@ -1,15 +1,14 @@
 use super::Segment;
-use crate::billing::{
-    block::{find_active_block, identify_session_blocks_with_overrides},
-    calculator::calculate_burn_rate,
-    BurnRateThresholds, ModelPricing,
-};
-use crate::config::InputData;
-use crate::utils::data_loader::DataLoader;
+use crate::billing::{calculator::calculate_burn_rate, BurnRateThresholds};
+use crate::core::context::RenderContext;
+use crate::core::icons::{Icon, IconPack};
+use crate::core::template::{Value, Variables};
+use std::panic::AssertUnwindSafe;
 
 pub struct BurnRateSegment {
     enabled: bool,
     thresholds: BurnRateThresholds,
+    icons: IconPack,
 }
 
 impl BurnRateSegment {
@@ -17,65 +16,63 @@ impl BurnRateSegment {
         Self {
             enabled,
             thresholds: BurnRateThresholds::from_env(),
+            icons: IconPack::NerdFont,
         }
     }
 
-    fn get_indicator(&self, tokens_per_minute: f64) -> &'static str {
-        if tokens_per_minute > self.thresholds.high {
-            "\u{ef76}" // 🔥 Fire (Nerd Font)
-        } else if tokens_per_minute > self.thresholds.medium {
-            "\u{f0e7}" // ⚡ Lightning bolt (Nerd Font)
-        } else {
-            "\u{f0e4}" // 📊 Dashboard/gauge (Nerd Font)
-        }
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
     }
 
-    fn render_with_data(&self, _input: &InputData) -> String {
-        // Load all project data globally (like ccusage does)
-        let data_loader = DataLoader::new();
-        let mut all_entries = data_loader.load_all_projects();
+    pub fn with_thresholds(mut self, thresholds: BurnRateThresholds) -> Self {
+        self.thresholds = thresholds;
+        self
+    }
 
-        // Get pricing data (create a runtime to handle async)
-        let pricing_map = {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async { ModelPricing::get_pricing_with_fallback().await })
+    fn get_indicator(&self, tokens_per_minute: f64) -> &'static str {
+        let icon = if tokens_per_minute > self.thresholds.high {
+            Icon::BurnHigh
+        } else if tokens_per_minute > self.thresholds.medium {
+            Icon::BurnMedium
+        } else {
+            Icon::BurnNormal
         };
+        self.icons.glyph(icon)
+    }
 
-        // Calculate costs for entries
-        for entry in &mut all_entries {
-            if let Some(pricing) = ModelPricing::get_model_pricing(&pricing_map, &entry.model) {
-                entry.cost = Some(pricing.calculate_cost(entry));
-            }
-        }
-
-        // Find active billing block using dynamic calculation
-        let blocks = identify_session_blocks_with_overrides(&all_entries);
-        let active_block = find_active_block(&blocks);
+    fn collect_variables(&self, ctx: &RenderContext) -> Variables {
+        // Entries and the active block are shared with the cost segment
+        let all_entries = ctx.entries();
+        let active_block = ctx.active_block();
 
         // Calculate burn rate
-        match active_block.and_then(|block| calculate_burn_rate(block, &all_entries)) {
-            Some(rate) => {
-                let indicator = self.get_indicator(rate.tokens_per_minute_for_indicator);
-                format!("{} ${:.2}/hr", indicator, rate.cost_per_hour)
-            }
-            None => "\u{f0e4} —/hr".to_string(), // No data available
+        match active_block.and_then(|block| calculate_burn_rate(block, all_entries)) {
+            Some(rate) => Variables::new()
+                .with(
+                    "icon",
+                    self.get_indicator(rate.tokens_per_minute_for_indicator),
+                )
+                .with("cost_per_hour", Value::Money(rate.cost_per_hour))
+                .with("tokens_per_minute", Value::Number(rate.tokens_per_minute)),
+            None => Variables::new().with("icon", self.icons.glyph(Icon::BurnNormal)), // No data available
         }
     }
 }
 
 impl Segment for BurnRateSegment {
-    fn render(&self, input: &InputData) -> String {
-        if !self.enabled {
-            return String::new();
-        }
-
+    fn variables(&self, ctx: &RenderContext) -> Variables {
         // Handle potential errors gracefully
-        match std::panic::catch_unwind(|| self.render_with_data(input)) {
-            Ok(result) => result,
-            Err(_) => "\u{f0e4} —/hr".to_string(), // Error fallback
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.collect_variables(ctx))) {
+            Ok(variables) => variables,
+            Err(_) => Variables::new().with("icon", self.icons.glyph(Icon::BurnNormal)), // Error fallback
         }
     }
 
+    fn default_format(&self) -> &'static str {
+        "{?icon}{icon} {/}{?cost_per_hour}{cost_per_hour}{/}{!cost_per_hour}—{/}/hr"
+    }
+
     fn enabled(&self) -> bool {
         self.enabled
     }
@@ -84,7 +81,7 @@ impl Segment for BurnRateSegment {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Model, ProviderKind, Workspace};
+    use crate::config::{InputData, Model, ProviderKind, Workspace};
 
     #[test]
     fn test_burn_rate_segment_disabled() {
@@ -101,7 +98,7 @@ mod tests {
             transcript_path: "/test/transcript.jsonl".to_string(),
         };
 
-        assert_eq!(segment.render(&input), "");
+        assert_eq!(segment.render(&RenderContext::new(input)), "");
         assert!(!segment.enabled());
     }
 
@@ -123,5 +120,9 @@ mod tests {
 
         // Test normal burn rate
         assert_eq!(segment.get_indicator(1000.0), "\u{f0e4}"); // Dashboard
+
+        // Other packs go through the same registry
+        let ascii = BurnRateSegment::new(true).with_icons(IconPack::PlainAscii);
+        assert_eq!(ascii.get_indicator(6000.0), "!!");
     }
 }
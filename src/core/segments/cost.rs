@@ -1,16 +1,30 @@
 use super::Segment;
-use crate::billing::{
-    block::{find_active_block, identify_session_blocks_with_overrides},
-    calculator::{calculate_daily_total, calculate_session_cost, format_remaining_time},
-    ModelPricing,
-};
-use crate::config::InputData;
-use crate::utils::{data_loader::DataLoader, transcript::extract_session_id};
+use crate::billing::calculator::{calculate_daily_total, calculate_session_cost};
+use crate::core::context::RenderContext;
+use crate::core::icons::{Icon, IconPack};
+use crate::core::template::{Value, Variables};
+use crate::utils::transcript::extract_session_id;
+use serde::{Deserialize, Serialize};
+use std::panic::AssertUnwindSafe;
 use std::time::Instant;
 
+/// Spending window shown by a cost segment
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum CostWindow {
+    /// Current session only
+    Session,
+    /// Everything spent today
+    Today,
+    /// Active 5-hour billing block
+    Block,
+}
+
 pub struct CostSegment {
     enabled: bool,
     show_timing: bool,
+    window: Option<CostWindow>,
+    icons: IconPack,
 }
 
 impl CostSegment {
@@ -18,101 +32,105 @@ impl CostSegment {
         Self {
             enabled,
             show_timing: std::env::var("CCLINE_SHOW_TIMING").is_ok(),
+            window: None,
+            icons: IconPack::NerdFont,
         }
     }
 
-    fn render_with_pricing(&self, input: &InputData) -> String {
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    /// Show a single spending window instead of the combined summary
+    pub fn with_window(mut self, window: CostWindow) -> Self {
+        self.window = Some(window);
+        self
+    }
+
+    fn collect_variables(&self, ctx: &RenderContext) -> Variables {
         // Performance timing
         let start = Instant::now();
         let mut timings = Vec::new();
 
-        // 1. Load all project data
-        let load_start = Instant::now();
-        let data_loader = DataLoader::new();
-        let mut all_entries = data_loader.load_all_projects();
-        timings.push(("L", load_start.elapsed().as_millis()));
-
-        // 2. Get pricing data (create a runtime to handle async)
+        // 1. Get pricing data (shared with other segments)
         let pricing_start = Instant::now();
-        let pricing_map = {
-            let rt = tokio::runtime::Runtime::new().unwrap();
-            rt.block_on(async { ModelPricing::get_pricing_with_fallback().await })
-        };
+        let pricing_map = ctx.pricing();
         timings.push(("P", pricing_start.elapsed().as_millis()));
 
-        // 3. Calculate costs for all entries
-        let calc_start = Instant::now();
-        for entry in &mut all_entries {
-            if let Some(pricing) = ModelPricing::get_model_pricing(&pricing_map, &entry.model) {
-                entry.cost = Some(pricing.calculate_cost(entry));
-            }
-        }
-        timings.push(("C", calc_start.elapsed().as_millis()));
+        // 2. Load all project data with costs filled in
+        let load_start = Instant::now();
+        let all_entries = ctx.entries();
+        timings.push(("L", load_start.elapsed().as_millis()));
 
-        // 4. Calculate session and daily costs
+        // 3. Calculate session and daily costs
         let analyze_start = Instant::now();
-        let transcript_path = std::path::Path::new(&input.transcript_path);
+        let transcript_path = std::path::Path::new(&ctx.input().transcript_path);
         let session_id = extract_session_id(transcript_path);
-        let session_cost = calculate_session_cost(&all_entries, &session_id, &pricing_map);
-        let daily_total = calculate_daily_total(&all_entries, &pricing_map);
+        let session_cost = calculate_session_cost(all_entries, &session_id, pricing_map);
+        let daily_total = calculate_daily_total(all_entries, pricing_map);
         timings.push(("A", analyze_start.elapsed().as_millis()));
 
-        // 5. Calculate dynamic blocks with override support
+        // 4. Find the active block (dynamic blocks with override support)
         let block_start = Instant::now();
-        let blocks = identify_session_blocks_with_overrides(&all_entries);
-        let active_block = find_active_block(&blocks);
+        let active_block = ctx.active_block();
         timings.push(("B", block_start.elapsed().as_millis()));
 
-        // Format basic output
-        let cost_display = match active_block {
-            Some(block) => format!(
-                "\u{f155} ${:.2} session · ${:.2} today · ${:.2} block ({})",
-                session_cost,
-                daily_total,
-                block.cost,
-                format_remaining_time(block.remaining_minutes)
-            ),
-            None => format!(
-                "\u{f155} ${:.2} session · ${:.2} today · No active block",
-                session_cost, daily_total
-            ),
-        };
+        let mut variables = Variables::new()
+            .with("icon", self.icons.glyph(Icon::Cost))
+            .with("session_cost", Value::Money(session_cost))
+            .with("daily_cost", Value::Money(daily_total));
+        match active_block {
+            Some(block) => {
+                variables.set("block_cost", Value::Money(block.cost));
+                variables.set("block_remaining", Value::Minutes(block.remaining_minutes));
+            }
+            None => variables.set("block_status", "No active block"),
+        }
 
         // Add performance timing if enabled
         if self.show_timing {
             let total_ms = start.elapsed().as_millis();
             let timing_str = format!(
-                " [{}ms: L{}|P{}|C{}|A{}|B{}]",
+                "{}ms: P{}|L{}|A{}|B{}",
                 total_ms,
-                timings[0].1, // Load
-                timings[1].1, // Pricing
-                timings[2].1, // Calculate
-                timings[3].1, // Analyze
-                timings[4].1  // Block
+                timings[0].1, // Pricing
+                timings[1].1, // Load
+                timings[2].1, // Analyze
+                timings[3].1  // Block
             );
-            format!("{}{}", cost_display, timing_str)
-        } else {
-            cost_display
+            variables.set("timing", timing_str);
         }
+
+        variables
     }
 }
 
 impl Segment for CostSegment {
-    fn render(&self, input: &InputData) -> String {
-        if !self.enabled {
-            return String::new();
-        }
-
+    fn variables(&self, ctx: &RenderContext) -> Variables {
         // Handle potential errors gracefully
-        match std::panic::catch_unwind(|| self.render_with_pricing(input)) {
-            Ok(result) => result,
+        match std::panic::catch_unwind(AssertUnwindSafe(|| self.collect_variables(ctx))) {
+            Ok(variables) => variables,
             Err(_) => {
                 // Fallback display on error
-                "\u{f155} $0.00 session · $0.00 today · Error loading data".to_string()
+                Variables::new()
+                    .with("icon", self.icons.glyph(Icon::Cost))
+                    .with("session_cost", Value::Money(0.0))
+                    .with("daily_cost", Value::Money(0.0))
+                    .with("block_status", "Error loading data")
             }
         }
     }
 
+    fn default_format(&self) -> &'static str {
+        match self.window {
+            None => "{?icon}{icon} {/}{session_cost} session · {daily_cost} today · {?block_cost}{block_cost} block ({block_remaining}){/}{block_status}{?timing} [{timing}]{/}",
+            Some(CostWindow::Session) => "{?icon}{icon} {/}{session_cost} session{?timing} [{timing}]{/}",
+            Some(CostWindow::Today) => "{?icon}{icon} {/}{daily_cost} today{?timing} [{timing}]{/}",
+            Some(CostWindow::Block) => "{?icon}{icon} {/}{?block_cost}{block_cost} block ({block_remaining}){/}{block_status}{?timing} [{timing}]{/}",
+        }
+    }
+
     fn enabled(&self) -> bool {
         self.enabled
     }
@@ -121,7 +139,8 @@ impl Segment for CostSegment {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::config::{Model, ProviderKind, Workspace};
+    use crate::config::{InputData, Model, ProviderKind, Workspace};
+    use crate::core::template::Template;
 
     #[test]
     fn test_cost_segment_disabled() {
@@ -138,10 +157,53 @@ mod tests {
             transcript_path: "/test/transcript.jsonl".to_string(),
         };
 
-        assert_eq!(segment.render(&input), "");
+        assert_eq!(segment.render(&RenderContext::new(input)), "");
         assert!(!segment.enabled());
     }
 
+    #[test]
+    fn test_default_formats() {
+        let render = |segment: CostSegment, variables: &Variables| {
+            Template::parse(segment.default_format())
+                .unwrap()
+                .render(variables)
+        };
+        let with_block = Variables::new()
+            .with("icon", "\u{f155}")
+            .with("session_cost", Value::Money(1.5))
+            .with("daily_cost", Value::Money(12.25))
+            .with("block_cost", Value::Money(4.0))
+            .with("block_remaining", Value::Minutes(225));
+        let without_block = Variables::new()
+            .with("icon", "\u{f155}")
+            .with("session_cost", Value::Money(1.5))
+            .with("daily_cost", Value::Money(12.25))
+            .with("block_status", "No active block");
+
+        assert_eq!(
+            render(CostSegment::new(true), &with_block),
+            "\u{f155} $1.50 session · $12.25 today · $4.00 block (3h 45m)"
+        );
+        assert_eq!(
+            render(CostSegment::new(true), &without_block),
+            "\u{f155} $1.50 session · $12.25 today · No active block"
+        );
+        assert_eq!(
+            render(
+                CostSegment::new(true).with_window(CostWindow::Today),
+                &with_block
+            ),
+            "\u{f155} $12.25 today"
+        );
+        assert_eq!(
+            render(
+                CostSegment::new(true).with_window(CostWindow::Block),
+                &without_block
+            ),
+            "\u{f155} No active block"
+        );
+    }
+
     #[test]
     fn test_cost_segment_enabled() {
         let segment = CostSegment::new(true);
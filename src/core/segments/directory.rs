@@ -1,25 +1,59 @@
 use super::Segment;
-use crate::config::InputData;
+use crate::core::context::RenderContext;
+use crate::core::icons::{Icon, IconPack};
+use crate::core::template::{Value, Variables};
+use crate::core::width::truncate;
 use std::path::Path;
 
+/// Longest directory name kept when the line has to be shortened
+const SHORT_DIR_WIDTH: usize = 12;
+
 pub struct DirectorySegment {
     enabled: bool,
+    icons: IconPack,
 }
 
 impl DirectorySegment {
     pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+        Self {
+            enabled,
+            icons: IconPack::NerdFont,
+        }
+    }
+
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
     }
 }
 
 impl Segment for DirectorySegment {
-    fn render(&self, input: &InputData) -> String {
-        if !self.enabled {
-            return String::new();
-        }
+    fn variables(&self, ctx: &RenderContext) -> Variables {
+        let input = ctx.input();
+        Variables::new()
+            .with("icon", self.icons.glyph(Icon::Directory))
+            .with("dir", get_current_dir_name(&input.workspace.current_dir))
+            .with("path", input.workspace.current_dir.as_str())
+    }
 
-        let dir_name = get_current_dir_name(&input.workspace.current_dir);
-        format!("\u{f024b} {}", dir_name)
+    fn default_format(&self) -> &'static str {
+        "{?icon}{icon} {/}{dir}"
+    }
+
+    /// Truncate the directory name and abbreviate parent components of the path
+    fn shorten(&self, variables: &mut Variables) -> bool {
+        let mut changed = false;
+        if let Some(Value::Text(dir)) = variables.get("dir").cloned() {
+            let short = truncate(&dir, SHORT_DIR_WIDTH);
+            changed |= short != dir;
+            variables.set("dir", short);
+        }
+        if let Some(Value::Text(path)) = variables.get("path").cloned() {
+            let short = abbreviate_path(&path);
+            changed |= short != path;
+            variables.set("path", short);
+        }
+        changed
     }
 
     fn enabled(&self) -> bool {
@@ -34,3 +68,52 @@ fn get_current_dir_name<P: AsRef<Path>>(path: P) -> String {
         .unwrap_or("unknown")
         .to_string()
 }
+
+/// Reduce every component but the last to its first character (`/home/me/crate` → `/h/m/crate`)
+fn abbreviate_path(path: &str) -> String {
+    let separator = if path.contains('\\') && !path.contains('/') {
+        '\\'
+    } else {
+        '/'
+    };
+    let components: Vec<&str> = path.split(separator).collect();
+    let last = components.len().saturating_sub(1);
+    components
+        .iter()
+        .enumerate()
+        .map(|(i, component)| match component.chars().next() {
+            Some(first) if i < last => {
+                // Keep hidden directories recognisable (`.config` → `.c`)
+                let take = if first == '.' { 2 } else { 1 };
+                component.chars().take(take).collect()
+            }
+            _ => component.to_string(),
+        })
+        .collect::<Vec<String>>()
+        .join(&separator.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shorten_directory() {
+        let segment = DirectorySegment::new(true);
+        let mut variables = Variables::new()
+            .with("dir", "ccometixline-workspace")
+            .with("path", "/home/me/.config/ccometixline-workspace");
+        assert!(segment.shorten(&mut variables));
+        assert_eq!(
+            variables.get("dir"),
+            Some(&Value::Text("ccometixlin…".to_string()))
+        );
+        assert_eq!(
+            variables.get("path"),
+            Some(&Value::Text("/h/m/.c/ccometixline-workspace".to_string()))
+        );
+
+        let mut short = Variables::new().with("dir", "crate").with("path", "crate");
+        assert!(!segment.shorten(&mut short));
+    }
+}
@@ -1,7 +1,13 @@
 use super::Segment;
-use crate::config::InputData;
+use crate::core::context::RenderContext;
+use crate::core::icons::{Icon, IconPack};
+use crate::core::template::{Value, Variables};
+use crate::core::width::truncate;
 use std::process::Command;
 
+/// Longest branch name kept when the line has to be shortened
+const SHORT_BRANCH_WIDTH: usize = 16;
+
 #[derive(Debug)]
 pub struct GitInfo {
     pub branch: String,
@@ -21,6 +27,7 @@ pub enum GitStatus {
 pub struct GitSegment {
     enabled: bool,
     show_sha: bool,
+    icons: IconPack,
 }
 
 impl GitSegment {
@@ -28,9 +35,15 @@ impl GitSegment {
         Self {
             enabled,
             show_sha: false,
+            icons: IconPack::NerdFont,
         }
     }
 
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
+    }
+
     pub fn with_sha(mut self, show_sha: bool) -> Self {
         self.show_sha = show_sha;
         self
@@ -171,49 +184,92 @@ impl GitSegment {
         }
     }
 
-    fn format_git_status(&self, info: &GitInfo) -> String {
-        let mut parts = Vec::new();
+    fn git_variables(&self, info: &GitInfo) -> Variables {
+        let status = match info.status {
+            GitStatus::Clean => Icon::GitClean,
+            GitStatus::Dirty => Icon::GitDirty,
+            GitStatus::Conflicts => Icon::GitConflicts,
+        };
 
-        // Branch name with Nerd Font branch icon
-        parts.push(format!("\u{f02a2} {}", info.branch));
+        let mut variables = Variables::new()
+            .with("icon", self.icons.glyph(Icon::Git))
+            .with("branch", info.branch.as_str())
+            .with("status", self.icons.glyph(status))
+            .with("ahead_icon", self.icons.glyph(Icon::Ahead))
+            .with("behind_icon", self.icons.glyph(Icon::Behind));
 
-        // Status indicators using simple Unicode symbols
-        match info.status {
-            GitStatus::Clean => parts.push("✓".to_string()),
-            GitStatus::Dirty => parts.push("●".to_string()),
-            GitStatus::Conflicts => parts.push("⚠".to_string()),
-        }
-
-        // Remote tracking status with arrows
+        // Remote tracking counters are only set when non-zero
         if info.ahead > 0 {
-            parts.push(format!("↑{}", info.ahead));
+            variables.set("ahead", Value::Number(info.ahead as f64));
         }
         if info.behind > 0 {
-            parts.push(format!("↓{}", info.behind));
+            variables.set("behind", Value::Number(info.behind as f64));
         }
 
         // Short SHA hash
         if let Some(ref sha) = info.sha {
-            parts.push(sha.clone());
+            variables.set("sha", sha.as_str());
         }
 
-        parts.join(" ")
+        variables
     }
 }
 
 impl Segment for GitSegment {
-    fn render(&self, input: &InputData) -> String {
-        if !self.enabled {
-            return String::new();
-        }
-
+    fn variables(&self, ctx: &RenderContext) -> Variables {
+        let input = ctx.input();
         match self.get_git_info(&input.workspace.current_dir) {
-            Some(git_info) => self.format_git_status(&git_info),
-            None => String::new(), // Not in a Git repository
+            Some(git_info) => self.git_variables(&git_info),
+            None => Variables::new(), // Not in a Git repository
         }
     }
 
+    fn default_format(&self) -> &'static str {
+        "{?icon}{icon} {/}{branch}{?status} {status}{/}{?ahead} {ahead_icon}{ahead}{/}{?behind} {behind_icon}{behind}{/}{?sha} {sha}{/}"
+    }
+
+    /// Collapse the branch's prefix components and cap its length
+    fn shorten(&self, variables: &mut Variables) -> bool {
+        let Some(Value::Text(branch)) = variables.get("branch").cloned() else {
+            return false;
+        };
+        let short = collapse_branch(&branch);
+        let changed = short != branch;
+        variables.set("branch", short);
+        changed
+    }
+
     fn enabled(&self) -> bool {
         self.enabled
     }
 }
+
+/// `feature/terminal-width-truncation` → `f/terminal-width…`
+fn collapse_branch(branch: &str) -> String {
+    let collapsed = match branch.rsplit_once('/') {
+        Some((prefix, name)) => {
+            let initials: Vec<String> = prefix
+                .split('/')
+                .map(|part| part.chars().take(1).collect())
+                .collect();
+            format!("{}/{}", initials.join("/"), name)
+        }
+        None => branch.to_string(),
+    };
+    truncate(&collapsed, SHORT_BRANCH_WIDTH)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collapse_branch() {
+        assert_eq!(collapse_branch("main"), "main");
+        assert_eq!(
+            collapse_branch("feature/terminal-width-truncation"),
+            "f/terminal-widt…"
+        );
+        assert_eq!(collapse_branch("user/me/fix-ci"), "u/m/fix-ci");
+    }
+}
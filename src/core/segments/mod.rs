@@ -1,3 +1,4 @@
+pub mod block;
 pub mod burn_rate;
 pub mod cost;
 pub mod directory;
@@ -6,16 +7,70 @@ pub mod model;
 pub mod update;
 pub mod usage;
 
-use crate::config::InputData;
+use crate::core::context::RenderContext;
+use crate::core::template::{Template, Variables};
 
-pub trait Segment {
-    fn render(&self, input: &InputData) -> String;
+/// How close a segment's value is to a limit, used to pick an overlay style
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl Level {
+    /// Theme style laid over the segment's own style, if any
+    pub fn style_key(self) -> Option<&'static str> {
+        match self {
+            Level::Normal => None,
+            Level::Warning => Some("warning"),
+            Level::Critical => Some("critical"),
+        }
+    }
+}
+
+pub trait Segment: Send {
     fn enabled(&self) -> bool;
+
+    /// Named values exposed to `format` templates; empty when there is nothing to show
+    ///
+    /// Shared data such as transcript entries and pricing comes from `ctx`,
+    /// which memoizes it for every segment in the invocation.
+    fn variables(&self, ctx: &RenderContext) -> Variables;
+
+    /// Template used when the segment has no `format` configured
+    fn default_format(&self) -> &'static str;
+
+    /// Shorten `variables` in place when the line is too wide; false when nothing changed
+    fn shorten(&self, _variables: &mut Variables) -> bool {
+        false
+    }
+
+    /// Level of the values in `variables`; segments without thresholds stay `Normal`
+    fn level(&self, _variables: &Variables) -> Level {
+        Level::Normal
+    }
+
+    fn render(&self, ctx: &RenderContext) -> String {
+        if !self.enabled() {
+            return String::new();
+        }
+
+        let variables = self.variables(ctx);
+        if variables.is_empty() {
+            return String::new();
+        }
+        Template::parse(self.default_format())
+            .map(|template| template.render(&variables))
+            .unwrap_or_default()
+    }
 }
 
 // Re-export all segment types
+pub use block::BlockSegment;
 pub use burn_rate::BurnRateSegment;
-pub use cost::CostSegment;
+pub use cost::{CostSegment, CostWindow};
 pub use directory::DirectorySegment;
 pub use git::GitSegment;
 pub use model::ModelSegment;
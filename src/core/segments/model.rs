@@ -1,26 +1,43 @@
 use super::Segment;
-use crate::config::InputData;
+use crate::core::context::RenderContext;
+use crate::core::icons::{Icon, IconPack};
+use crate::core::template::Variables;
 
 pub struct ModelSegment {
     enabled: bool,
+    icons: IconPack,
 }
 
 impl ModelSegment {
     pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+        Self {
+            enabled,
+            icons: IconPack::NerdFont,
+        }
+    }
+
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
     }
 }
 
 impl Segment for ModelSegment {
-    fn render(&self, input: &InputData) -> String {
-        if !self.enabled {
-            return String::new();
-        }
+    fn variables(&self, ctx: &RenderContext) -> Variables {
+        let input = ctx.input();
+        let identifier = input.model.identifier.as_deref();
+        Variables::new()
+            .with("icon", self.icons.glyph(Icon::Model))
+            .with(
+                "model",
+                self.format_model_name(identifier, &input.model.display_name),
+            )
+            .with("id", identifier.unwrap_or(&input.model.display_name))
+            .with("display_name", input.model.display_name.as_str())
+    }
 
-        format!(
-            "\u{e26d} {}",
-            self.format_model_name(input.model.identifier.as_deref(), &input.model.display_name)
-        )
+    fn default_format(&self) -> &'static str {
+        "{?icon}{icon} {/}{model}"
     }
 
     fn enabled(&self) -> bool {
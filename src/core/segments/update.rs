@@ -1,18 +1,27 @@
-use crate::config::InputData;
+use crate::core::context::RenderContext;
+use crate::core::icons::IconPack;
 use crate::core::segments::Segment;
+use crate::core::template::Variables;
 use crate::updater::UpdateState;
 
 /// Update notification segment
 pub struct UpdateSegment {
     state: UpdateState,
+    icons: IconPack,
 }
 
 impl UpdateSegment {
     pub fn new() -> Self {
         Self {
             state: UpdateState::load(),
+            icons: IconPack::NerdFont,
         }
     }
+
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
+    }
 }
 
 impl Default for UpdateSegment {
@@ -22,11 +31,20 @@ impl Default for UpdateSegment {
 }
 
 impl Segment for UpdateSegment {
-    fn render(&self, _input: &InputData) -> String {
-        self.state.status_text().unwrap_or_default()
+    fn variables(&self, _ctx: &RenderContext) -> Variables {
+        let Some((icon, status)) = self.state.status_display() else {
+            return Variables::new();
+        };
+        Variables::new()
+            .with("icon", self.icons.glyph(icon))
+            .with("status", status)
+    }
+
+    fn default_format(&self) -> &'static str {
+        "{?icon}{icon} {/}{status}"
     }
 
     fn enabled(&self) -> bool {
-        self.state.status_text().is_some()
+        self.state.status_display().is_some()
     }
 }
@@ -1,51 +1,113 @@
-use super::Segment;
-use crate::config::{InputData, ProviderKind};
+use super::{Level, Segment};
+use crate::billing::{limits::context_limit, ContextConfig};
+use crate::config::ProviderKind;
+use crate::core::bar::Bar;
+use crate::core::context::RenderContext;
+use crate::core::icons::{Icon, IconPack};
+use crate::core::template::{Value, Variables};
 use crate::utils::transcript::parse_latest_usage;
 
-const CONTEXT_LIMIT: u32 = 200000;
-
 pub struct UsageSegment {
     enabled: bool,
+    icons: IconPack,
+    context: ContextConfig,
+    bar: Bar,
 }
 
 impl UsageSegment {
     pub fn new(enabled: bool) -> Self {
-        Self { enabled }
+        Self {
+            enabled,
+            icons: IconPack::NerdFont,
+            context: ContextConfig::default(),
+            bar: Bar::default(),
+        }
     }
-}
 
-impl Segment for UsageSegment {
-    fn render(&self, input: &InputData) -> String {
-        if !self.enabled {
-            return String::new();
+    pub fn with_bar(mut self, bar: Bar) -> Self {
+        self.bar = bar;
+        self
+    }
+
+    pub fn with_context_config(mut self, context: ContextConfig) -> Self {
+        self.context = context;
+        self
+    }
+
+    pub fn with_icons(mut self, icons: IconPack) -> Self {
+        self.icons = icons;
+        self
+    }
+
+    fn level_of(&self, percent: f64) -> Level {
+        if percent >= self.context.critical() {
+            Level::Critical
+        } else if percent >= self.context.warning() {
+            Level::Warning
+        } else {
+            Level::Normal
         }
+    }
 
-        let context_used_token = parse_transcript_usage(&input.transcript_path, input.provider);
-        let context_used_rate = (context_used_token as f64 / CONTEXT_LIMIT as f64) * 100.0;
+    fn icon_of(&self, percent: f64) -> Icon {
+        match self.level_of(percent) {
+            Level::Normal => Icon::Usage,
+            Level::Warning => Icon::UsageWarning,
+            Level::Critical => Icon::UsageCritical,
+        }
+    }
+}
 
-        // Format percentage: show integer when whole number, decimal when fractional
-        let percentage_display = if context_used_rate.fract() == 0.0 {
-            format!("{:.0}%", context_used_rate)
+impl Segment for UsageSegment {
+    fn variables(&self, ctx: &RenderContext) -> Variables {
+        let input = ctx.input();
+        let context_used_token = parse_transcript_usage(&input.transcript_path, input.provider);
+        let model_id = input
+            .model
+            .identifier
+            .as_deref()
+            .unwrap_or(&input.model.display_name);
+        // Limits only need the local table, so a first run never waits on a fetch
+        let (limit, _) = context_limit(
+            model_id,
+            &input.model.display_name,
+            ctx.local_pricing(),
+            &self.context,
+        );
+        let context_used_rate = if limit > 0 {
+            (context_used_token as f64 / limit as f64) * 100.0
         } else {
-            format!("{:.1}%", context_used_rate)
+            0.0
         };
+        let compact_at = self.context.compact_at(limit);
+
+        Variables::new()
+            .with("icon", self.icons.glyph(self.icon_of(context_used_rate)))
+            .with("percent", Value::Number(context_used_rate))
+            .with("tokens", Value::Number(context_used_token as f64))
+            .with("limit", Value::Number(limit as f64))
+            .with("bar", self.bar.render(context_used_rate / 100.0))
+            .with("compact_at", Value::Number(compact_at as f64))
+            .with(
+                "compact_in",
+                Value::Number(compact_at.saturating_sub(context_used_token as u64) as f64),
+            )
+    }
 
-        // Format tokens: show integer k when whole number, decimal k when fractional
-        let tokens_display = if context_used_token >= 1000 {
-            let k_value = context_used_token as f64 / 1000.0;
-            if k_value.fract() == 0.0 {
-                format!("{}k", k_value as u32)
-            } else {
-                format!("{:.1}k", k_value)
-            }
+    // Whole percentages and thousands print without decimals, fractional ones with one
+    fn default_format(&self) -> &'static str {
+        if self.context.show_until_compact {
+            "{?icon}{icon} {/}{percent:%} · {tokens:k} tokens · {compact_in:k} to compact"
         } else {
-            context_used_token.to_string()
-        };
+            "{?icon}{icon} {/}{percent:%} · {tokens:k} tokens"
+        }
+    }
 
-        format!(
-            "\u{f49b} {} · {} tokens",
-            percentage_display, tokens_display
-        )
+    fn level(&self, variables: &Variables) -> Level {
+        match variables.get("percent") {
+            Some(Value::Number(percent)) => self.level_of(*percent),
+            _ => Level::Normal,
+        }
     }
 
     fn enabled(&self) -> bool {
@@ -61,3 +123,45 @@ fn parse_transcript_usage<P: AsRef<std::path::Path>>(
         .map(|usage| usage.display_tokens())
         .unwrap_or(0)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::template::Template;
+
+    #[test]
+    fn test_levels_and_compact_display() {
+        let segment = UsageSegment::new(true).with_context_config(ContextConfig {
+            warning: Some(50.0),
+            show_until_compact: true,
+            ..ContextConfig::default()
+        });
+        let percent = |p: f64| Variables::new().with("percent", Value::Number(p));
+
+        assert_eq!(segment.level(&percent(49.9)), Level::Normal);
+        assert_eq!(segment.level(&percent(50.0)), Level::Warning);
+        assert_eq!(segment.level(&percent(80.0)), Level::Critical);
+        assert_eq!(segment.level(&Variables::new()), Level::Normal);
+
+        // Each level has its own icon
+        assert_eq!(segment.icon_of(49.9), Icon::Usage);
+        assert_eq!(segment.icon_of(50.0), Icon::UsageWarning);
+        assert_eq!(segment.icon_of(74.9), Icon::UsageWarning);
+        assert_eq!(segment.icon_of(75.0), Icon::UsageCritical);
+        assert_ne!(
+            IconPack::NerdFont.glyph(Icon::UsageWarning),
+            IconPack::NerdFont.glyph(Icon::UsageCritical)
+        );
+
+        let variables = Variables::new()
+            .with("percent", Value::Number(45.0))
+            .with("tokens", Value::Number(90_000.0))
+            .with("compact_in", Value::Number(70_000.0));
+        assert_eq!(
+            Template::parse(segment.default_format())
+                .unwrap()
+                .render(&variables),
+            "45% · 90k tokens · 70k to compact"
+        );
+    }
+}
@@ -1,75 +1,693 @@
-use crate::config::{Config, InputData};
+use crate::config::{Config, InputData, SegmentConfig, SegmentKind};
+use crate::core::bar::Bar;
+use crate::core::cache::SegmentCache;
+use crate::core::context::RenderContext;
+use crate::core::icons::IconPack;
+use crate::core::render::{render_line, Align, StyledSegment};
 use crate::core::segments::{
-    BurnRateSegment, CostSegment, DirectorySegment, GitSegment, ModelSegment, Segment,
-    UpdateSegment, UsageSegment,
+    BlockSegment, BurnRateSegment, CostSegment, DirectorySegment, GitSegment, Level, ModelSegment,
+    Segment, UpdateSegment, UsageSegment,
 };
+use crate::core::template::{Template, Variables};
+use crate::core::theme::{Style, Theme};
+use crate::core::width::{display_width, terminal_columns};
+use std::collections::BTreeMap;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
 pub struct StatusLineGenerator {
     config: Config,
+    theme: Theme,
+    icons: IconPack,
+    width: Option<usize>,
 }
 
+/// A segment that made it onto the line, kept so it can be shortened or dropped
+struct PlacedSegment<'a> {
+    entry: &'a SegmentConfig,
+    segment: Box<dyn Segment>,
+    format: SegmentFormat,
+    variables: Variables,
+    styled: StyledSegment,
+}
+
+impl PlacedSegment<'_> {
+    /// Let the segment shorten its variables and re-render; false when nothing changed
+    fn shorten(&mut self) -> bool {
+        if !self.segment.shorten(&mut self.variables) {
+            return false;
+        }
+        self.styled.content = self.format.render(self.variables.clone());
+        true
+    }
+}
+
+/// Segments shortened, in this order, before any segment is dropped
+const SHORTEN_ORDER: [SegmentKind; 2] = [SegmentKind::Directory, SegmentKind::Git];
+
 impl StatusLineGenerator {
     pub fn new(config: Config) -> Self {
-        Self { config }
+        let theme = Theme::resolve(&config.theme, &config.themes, config.color_mode);
+        let icons = config.icons.resolve();
+        let width = config.render.width.or_else(terminal_columns);
+        Self {
+            config,
+            theme,
+            icons,
+            width: width.filter(|&w| w > 0),
+        }
+    }
+
+    /// Target line width in columns; `None` or `Some(0)` renders at full length
+    pub fn with_width(mut self, width: Option<usize>) -> Self {
+        self.width = width.filter(|&w| w > 0);
+        self
     }
 
     pub fn generate(&self, input: &InputData) -> String {
-        let mut segments = Vec::new();
-
-        // Assemble segments with proper colors
-        if self.config.segments.model {
-            let model_segment = ModelSegment::new(true);
-            let content = model_segment.render(input);
-            segments.push(format!("\x1b[1;36m{}\x1b[0m", content));
-        }
-
-        if self.config.segments.directory {
-            let dir_segment = DirectorySegment::new(true);
-            let content = dir_segment.render(input);
-            // Extract directory name without icon
-            let dir_name = content.trim_start_matches('\u{f024b}').trim_start();
-            segments.push(format!(
-                "\x1b[1;33m\u{f024b}\x1b[0m \x1b[1;32m{}\x1b[0m",
-                dir_name
-            ));
-        }
-
-        if self.config.segments.git {
-            let git_segment = GitSegment::new(true);
-            let git_output = git_segment.render(input);
-            if !git_output.is_empty() {
-                segments.push(format!("\x1b[1;34m{}\x1b[0m", git_output));
+        let entries: Vec<(usize, &SegmentConfig)> = self
+            .config
+            .segments
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| entry.enabled)
+            .collect();
+
+        // Gather every segment's variables concurrently, each within its own deadline
+        let jobs = entries
+            .iter()
+            .map(|(_, entry)| (self.build_segment(entry), self.timeout(entry)))
+            .collect();
+        let ctx = Arc::new(
+            RenderContext::new(input.clone()).with_pricing_config(self.config.pricing.clone()),
+        );
+        let results = collect_variables(jobs, &ctx);
+
+        // Render in configured order, grouped by row; late segments show their last output
+        let mut cache = SegmentCache::load(&session_key(input));
+        let mut rows: BTreeMap<usize, Vec<PlacedSegment>> = BTreeMap::new();
+        for ((index, entry), result) in entries.into_iter().zip(results) {
+            let key = format!("{}:{}", index, entry.kind.name());
+            let placed = match result {
+                Some((segment, variables)) => {
+                    let format = SegmentFormat::new(entry, segment.as_ref());
+                    let content = format.render(variables.clone());
+                    cache.set(key, content.clone());
+                    self.place(entry, segment, format, variables, content)
+                }
+                None => {
+                    let segment = self.build_segment(entry);
+                    let format = SegmentFormat::new(entry, segment.as_ref());
+                    let content = cache
+                        .get(&key)
+                        .unwrap_or(self.config.render.placeholder())
+                        .to_string();
+                    self.place(entry, segment, format, Variables::new(), content)
+                }
+            };
+            if let Some(placed) = placed {
+                rows.entry(entry.line).or_default().push(placed);
             }
         }
+        if let Err(e) = cache.save() {
+            eprintln!("Warning: failed to save segment cache: {}", e);
+        }
 
-        if self.config.segments.usage {
-            let usage_segment = UsageSegment::new(true);
-            let content = usage_segment.render(input);
-            segments.push(format!("\x1b[1;35m{}\x1b[0m", content));
+        rows.into_values()
+            .map(|row| self.fit_to_width(row))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    /// Render a row, shortening then dropping segments until it fits the target width
+    fn fit_to_width(&self, mut segments: Vec<PlacedSegment>) -> String {
+        let mut line = self.render_row(&segments);
+        let Some(width) = self.width else {
+            return line;
+        };
+
+        for kind in SHORTEN_ORDER {
+            if display_width(&line) <= width {
+                return line;
+            }
+            let mut changed = false;
+            for placed in segments.iter_mut().filter(|p| p.entry.kind == kind) {
+                changed |= placed.shorten();
+            }
+            if changed {
+                line = self.render_row(&segments);
+            }
         }
 
-        // Add cost segment
-        if self.config.segments.cost {
-            let cost_segment = CostSegment::new(true);
-            let content = cost_segment.render(input);
-            segments.push(format!("\x1b[1;33m{}\x1b[0m", content)); // Yellow
+        // Drop the lowest-priority segment (the rightmost on ties), keeping at least one
+        while display_width(&line) > width && segments.len() > 1 {
+            let lowest = segments
+                .iter()
+                .enumerate()
+                .min_by_key(|(i, placed)| (placed.entry.priority(), std::cmp::Reverse(*i)))
+                .map(|(i, _)| i)
+                .unwrap_or_default();
+            segments.remove(lowest);
+            line = self.render_row(&segments);
         }
+        line
+    }
+
+    /// Join a row's left-aligned segments, then its right-aligned ones
+    ///
+    /// With a known width the right group is pushed to the edge; otherwise it
+    /// simply follows the left group.
+    fn render_row(&self, segments: &[PlacedSegment]) -> String {
+        let (left, right): (Vec<&PlacedSegment>, Vec<&PlacedSegment>) = segments
+            .iter()
+            .partition(|placed| placed.entry.align == Align::Left);
+
+        let Some(width) = self.width.filter(|_| !right.is_empty()) else {
+            return self.join(left.iter().chain(right.iter()).copied());
+        };
+
+        let left = self.join(left.into_iter());
+        let right = self.join(right.into_iter());
+        let used = display_width(&left) + display_width(&right);
+        let min_gap = usize::from(!left.is_empty());
+        let gap = width.saturating_sub(used).max(min_gap);
+        format!("{}{}{}", left, " ".repeat(gap), right)
+    }
+
+    fn join<'a, 'b: 'a>(&self, segments: impl Iterator<Item = &'a PlacedSegment<'b>>) -> String {
+        let styled: Vec<StyledSegment> = segments.map(|p| p.styled.clone()).collect();
+        render_line(&self.theme, &self.config.render, &styled)
+    }
 
-        // Add burn rate segment
-        if self.config.segments.burn_rate {
-            let burn_rate_segment = BurnRateSegment::new(true);
-            let content = burn_rate_segment.render(input);
-            segments.push(format!("\x1b[1;31m{}\x1b[0m", content)); // Red
+    fn build_segment(&self, entry: &SegmentConfig) -> Box<dyn Segment> {
+        let icons = self.icons;
+        match entry.kind {
+            SegmentKind::Model => Box::new(ModelSegment::new(true).with_icons(icons)),
+            SegmentKind::Directory => Box::new(DirectorySegment::new(true).with_icons(icons)),
+            SegmentKind::Git => Box::new(GitSegment::new(true).with_icons(icons)),
+            SegmentKind::Usage => Box::new(
+                UsageSegment::new(true)
+                    .with_context_config(self.config.context.clone())
+                    .with_bar(self.bar())
+                    .with_icons(icons),
+            ),
+            SegmentKind::Cost => {
+                let mut cost_segment = CostSegment::new(true).with_icons(icons);
+                if let Some(window) = entry.window {
+                    cost_segment = cost_segment.with_window(window);
+                }
+                Box::new(cost_segment)
+            }
+            SegmentKind::BurnRate => Box::new(
+                BurnRateSegment::new(true)
+                    .with_thresholds(self.config.burn_rate.clone())
+                    .with_icons(icons),
+            ),
+            SegmentKind::Block => Box::new(
+                BlockSegment::new(true)
+                    .with_config(self.config.block.clone())
+                    .with_bar(self.bar())
+                    .with_icons(icons),
+            ),
+            SegmentKind::Update => Box::new(UpdateSegment::new().with_icons(icons)),
         }
+    }
+
+    /// Progress bar renderer for the configured look and the terminal's color depth
+    fn bar(&self) -> Bar {
+        Bar::new(&self.config.bar, self.theme.color_mode())
+    }
 
-        // Add update segment (always enabled when there's an update)
-        let update_segment = UpdateSegment::new();
-        if update_segment.enabled() {
-            let content = update_segment.render(input);
-            segments.push(format!("\x1b[1;37m{}\x1b[0m", content));
+    fn timeout(&self, entry: &SegmentConfig) -> Duration {
+        entry
+            .timeout_ms
+            .map(Duration::from_millis)
+            .unwrap_or_else(|| self.config.render.timeout())
+    }
+
+    fn place<'a>(
+        &self,
+        entry: &'a SegmentConfig,
+        segment: Box<dyn Segment>,
+        format: SegmentFormat,
+        variables: Variables,
+        content: String,
+    ) -> Option<PlacedSegment<'a>> {
+        if content.is_empty() && entry.hide_when_empty {
+            return None;
         }
 
-        // Join segments with white separator
-        segments.join("\x1b[37m | \x1b[0m")
+        let style = self.segment_style(entry, segment.level(&variables), format.shows_icon());
+        Some(PlacedSegment {
+            entry,
+            segment,
+            format,
+            variables,
+            styled: StyledSegment::new(style, content),
+        })
+    }
+
+    fn segment_style(&self, entry: &SegmentConfig, level: Level, shows_icon: bool) -> Style {
+        let mut style = self.theme.style(entry.kind.name());
+        if let Some(color) = entry.color {
+            style.fg = Some(color);
+        }
+        // A warning or critical overlay recolors the whole segment, icon included
+        if let Some(key) = level.style_key() {
+            let overlay = self.theme.style(key);
+            style = style.merge(&overlay);
+            style.icon = overlay.icon;
+        }
+        // Without a leading icon there is nothing for the icon color to apply to
+        if !shows_icon
+            || entry.icon.as_deref() == Some("")
+            || (entry.icon.is_none() && self.icons == IconPack::None)
+        {
+            style.icon = None;
+        }
+        style
+    }
+}
+
+/// Session the segment cache is kept for: the transcript file name, which is
+/// the session id for Claude and Codex alike
+fn session_key(input: &InputData) -> String {
+    std::path::Path::new(&input.transcript_path)
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| input.workspace.current_dir.clone())
+}
+
+/// Compute each segment's variables on its own thread
+///
+/// Returns the segment with its variables, or `None` for a segment that missed
+/// its deadline. Late threads are detached rather than joined, since joining
+/// would wait out the very segment that missed its deadline; `ccline` exits
+/// right after printing the line, which ends them. A long-lived caller of
+/// [`StatusLineGenerator::generate`] keeps them running until they finish.
+fn collect_variables(
+    jobs: Vec<(Box<dyn Segment>, Duration)>,
+    ctx: &Arc<RenderContext>,
+) -> Vec<Option<(Box<dyn Segment>, Variables)>> {
+    let start = Instant::now();
+    let deadlines: Vec<Instant> = jobs.iter().map(|(_, timeout)| start + *timeout).collect();
+    let (sender, receiver) = mpsc::channel();
+
+    for (index, (segment, _)) in jobs.into_iter().enumerate() {
+        let sender = sender.clone();
+        let ctx = Arc::clone(ctx);
+        thread::spawn(move || {
+            let variables = segment.variables(&ctx);
+            let _ = sender.send((index, segment, variables));
+        });
+    }
+    drop(sender);
+
+    let mut results: Vec<Option<(Box<dyn Segment>, Variables)>> =
+        deadlines.iter().map(|_| None).collect();
+    let mut waiting = vec![true; deadlines.len()];
+    while let Some(next) = deadlines
+        .iter()
+        .zip(&waiting)
+        .filter(|(_, waiting)| **waiting)
+        .map(|(deadline, _)| *deadline)
+        .min()
+    {
+        match receiver.recv_timeout(next.saturating_duration_since(Instant::now())) {
+            Ok((index, segment, variables)) => {
+                results[index] = Some((segment, variables));
+                waiting[index] = false;
+            }
+            Err(RecvTimeoutError::Timeout) => {
+                let now = Instant::now();
+                for (waiting, deadline) in waiting.iter_mut().zip(&deadlines) {
+                    if *deadline <= now {
+                        *waiting = false;
+                    }
+                }
+            }
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    results
+}
+
+/// A segment entry's parsed templates: its own `format`, or the segment default
+struct SegmentFormat {
+    default: Template,
+    custom: Option<Template>,
+    icon: Option<String>,
+}
+
+impl SegmentFormat {
+    fn new(entry: &SegmentConfig, segment: &dyn Segment) -> Self {
+        let default = Template::parse(segment.default_format())
+            .unwrap_or_else(|e| panic!("invalid built-in format for {}: {}", entry.kind.name(), e));
+
+        let custom = entry.format.as_deref().and_then(|format| {
+            Template::parse(format)
+                .map_err(|e| {
+                    eprintln!(
+                        "Warning: invalid format for {} segment: {}",
+                        entry.kind.name(),
+                        e
+                    )
+                })
+                .ok()
+        });
+
+        Self {
+            default,
+            custom,
+            icon: entry.icon.clone(),
+        }
+    }
+
+    fn template(&self) -> &Template {
+        self.custom.as_ref().unwrap_or(&self.default)
+    }
+
+    /// Whether the template shows `{icon}`, the part the icon color applies to
+    fn shows_icon(&self) -> bool {
+        self.template().uses("icon")
+    }
+
+    /// Render a segment's variables
+    ///
+    /// Besides the segment's own variables, `{text}` holds the default output
+    /// without its icon, and the entry's `icon` option replaces `{icon}`.
+    fn render(&self, mut variables: Variables) -> String {
+        if variables.is_empty() {
+            return String::new();
+        }
+
+        if let Some(template) = &self.custom {
+            if template.uses("text") {
+                let mut without_icon = variables.clone();
+                without_icon.remove("icon");
+                variables.set("text", self.default.render(&without_icon));
+            }
+        }
+        if let Some(icon) = &self.icon {
+            variables.set("icon", icon.as_str());
+        }
+
+        self.template().render(&variables)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::template::Value;
+    use crate::core::theme::ColorMode;
+
+    fn model_variables() -> Variables {
+        Variables::new()
+            .with("icon", "\u{e26d}")
+            .with("model", "Sonnet 4")
+            .with("tokens", Value::Number(1500.0))
+    }
+
+    fn place<'a>(
+        entry: &'a SegmentConfig,
+        segment: Box<dyn Segment>,
+        variables: Variables,
+    ) -> PlacedSegment<'a> {
+        let format = SegmentFormat::new(entry, segment.as_ref());
+        let content = format.render(variables.clone());
+        PlacedSegment {
+            entry,
+            segment,
+            format,
+            variables,
+            styled: StyledSegment::new(Style::default(), content),
+        }
+    }
+
+    #[test]
+    fn test_format_segment_options() {
+        let segment = ModelSegment::new(true);
+        let format =
+            |entry: SegmentConfig| SegmentFormat::new(&entry, &segment).render(model_variables());
+
+        assert_eq!(
+            format(SegmentConfig::new(SegmentKind::Model)),
+            "\u{e26d} Sonnet 4"
+        );
+        assert_eq!(
+            format(SegmentConfig {
+                icon: Some("M".to_string()),
+                ..SegmentConfig::new(SegmentKind::Model)
+            }),
+            "M Sonnet 4"
+        );
+        assert_eq!(
+            format(SegmentConfig {
+                icon: Some(String::new()),
+                ..SegmentConfig::new(SegmentKind::Model)
+            }),
+            "Sonnet 4"
+        );
+        assert_eq!(
+            format(SegmentConfig {
+                format: Some("[{text}] {tokens:k}".to_string()),
+                ..SegmentConfig::new(SegmentKind::Model)
+            }),
+            "[Sonnet 4] 1.5k"
+        );
+        // An invalid format falls back to the default
+        assert_eq!(
+            format(SegmentConfig {
+                format: Some("{model".to_string()),
+                ..SegmentConfig::new(SegmentKind::Model)
+            }),
+            "\u{e26d} Sonnet 4"
+        );
+    }
+
+    #[test]
+    fn test_empty_variables_render_nothing() {
+        let segment = ModelSegment::new(true);
+        let entry = SegmentConfig {
+            format: Some("always".to_string()),
+            ..SegmentConfig::new(SegmentKind::Model)
+        };
+        assert_eq!(
+            SegmentFormat::new(&entry, &segment).render(Variables::new()),
+            ""
+        );
+    }
+
+    #[test]
+    fn test_icon_color_needs_icon_in_format() {
+        let generator = StatusLineGenerator::new(Config {
+            color_mode: ColorMode::Ansi16,
+            ..Config::default()
+        });
+        let render = |entry: &SegmentConfig| {
+            let segment = Box::new(DirectorySegment::new(true));
+            let format = SegmentFormat::new(entry, segment.as_ref());
+            let variables = Variables::new().with("icon", "D").with("dir", "My Project");
+            let content = format.render(variables.clone());
+            let placed = generator
+                .place(entry, segment, format, variables, content)
+                .unwrap();
+            generator.render_row(&[placed])
+        };
+
+        // The default format leads with the icon, which gets its own color
+        assert_eq!(
+            render(&SegmentConfig::new(SegmentKind::Directory)),
+            "\x1b[1;33mD\x1b[0m \x1b[1;32mMy Project\x1b[0m"
+        );
+        // Without `{icon}` the first word is not an icon
+        assert_eq!(
+            render(&SegmentConfig {
+                format: Some("{dir}".to_string()),
+                ..SegmentConfig::new(SegmentKind::Directory)
+            }),
+            "\x1b[1;32mMy Project\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_fit_to_width_shortens_then_drops() {
+        let entries = [
+            SegmentConfig::new(SegmentKind::Model),
+            SegmentConfig::new(SegmentKind::Directory),
+            SegmentConfig::new(SegmentKind::Git),
+            SegmentConfig::new(SegmentKind::Update),
+        ];
+        let fit = |width: usize| {
+            let segments = vec![
+                place(
+                    &entries[0],
+                    Box::new(ModelSegment::new(true)),
+                    Variables::new().with("icon", "M").with("model", "Sonnet 4"),
+                ),
+                place(
+                    &entries[1],
+                    Box::new(DirectorySegment::new(true)),
+                    Variables::new()
+                        .with("icon", "D")
+                        .with("dir", "ccometixline-workspace"),
+                ),
+                place(
+                    &entries[2],
+                    Box::new(GitSegment::new(true)),
+                    Variables::new()
+                        .with("icon", "G")
+                        .with("branch", "feature/terminal-width-truncation"),
+                ),
+                place(
+                    &entries[3],
+                    Box::new(UpdateSegment::new()),
+                    Variables::new()
+                        .with("icon", "U")
+                        .with("status", "Update v2!"),
+                ),
+            ];
+            let generator = StatusLineGenerator::new(Config::default()).with_width(Some(width));
+            let line = generator.fit_to_width(segments);
+            // Separators are colored by the theme
+            line.replace("\x1b[37m", "").replace("\x1b[0m", "")
+        };
+
+        let full = "M Sonnet 4 | D ccometixline-workspace | G feature/terminal-width-truncation | U Update v2!";
+        assert_eq!(full.len(), 90);
+        assert_eq!(fit(90), full);
+        // Directory is shortened first, then git
+        assert_eq!(
+            fit(89),
+            "M Sonnet 4 | D ccometixlin… | G feature/terminal-width-truncation | U Update v2!"
+        );
+        assert_eq!(
+            fit(70),
+            "M Sonnet 4 | D ccometixlin… | G f/terminal-widt… | U Update v2!"
+        );
+        // Then segments are dropped by priority: update, then git, then directory
+        assert_eq!(fit(60), "M Sonnet 4 | D ccometixlin… | G f/terminal-widt…");
+        assert_eq!(fit(40), "M Sonnet 4 | D ccometixlin…");
+        assert_eq!(fit(27), "M Sonnet 4 | D ccometixlin…");
+        assert_eq!(fit(26), "M Sonnet 4");
+        // The highest-priority segment is kept even when it does not fit
+        assert_eq!(fit(5), "M Sonnet 4");
+    }
+
+    #[test]
+    fn test_right_aligned_segments() {
+        let left = SegmentConfig::new(SegmentKind::Model);
+        let right = SegmentConfig {
+            align: Align::Right,
+            ..SegmentConfig::new(SegmentKind::Update)
+        };
+        let generator = |width| {
+            let config = Config {
+                color_mode: ColorMode::Ansi16,
+                ..Config::default()
+            };
+            StatusLineGenerator::new(config).with_width(width)
+        };
+        let row = |generator: &StatusLineGenerator| {
+            [
+                (
+                    &right,
+                    Box::new(UpdateSegment::new()) as Box<dyn Segment>,
+                    "status",
+                    "Update v2!",
+                ),
+                (
+                    &left,
+                    Box::new(ModelSegment::new(true)),
+                    "model",
+                    "Sonnet 4",
+                ),
+            ]
+            .into_iter()
+            .filter_map(|(entry, segment, name, value)| {
+                let format = SegmentFormat::new(entry, segment.as_ref());
+                let variables = Variables::new().with(name, value);
+                let content = format.render(variables.clone());
+                generator.place(entry, segment, format, variables, content)
+            })
+            .collect::<Vec<_>>()
+        };
+
+        // Padding fills the row up to the width and pushes the right group to the edge
+        let bounded = generator(Some(30));
+        let line = bounded.fit_to_width(row(&bounded));
+        assert_eq!(display_width(&line), 30);
+        assert_eq!(
+            line,
+            format!(
+                "\x1b[1;36mSonnet 4\x1b[0m{}\x1b[1;37mUpdate v2!\x1b[0m",
+                " ".repeat(12)
+            )
+        );
+
+        // Without a width the right group just follows the left one
+        let unbounded = generator(None);
+        let line = unbounded.fit_to_width(row(&unbounded));
+        assert_eq!(display_width(&line), "Sonnet 4 | Update v2!".len());
+    }
+
+    /// Segment that takes a fixed time to produce its variables
+    struct Slow(Duration);
+
+    impl Segment for Slow {
+        fn variables(&self, _ctx: &RenderContext) -> Variables {
+            thread::sleep(self.0);
+            Variables::new().with("model", "done")
+        }
+
+        fn default_format(&self) -> &'static str {
+            "{model}"
+        }
+
+        fn enabled(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_collect_variables_honours_deadlines() {
+        let ctx = Arc::new(RenderContext::new(
+            InputData::from_value(serde_json::json!({
+            "model": {"id": "claude-sonnet-4", "display_name": "Sonnet 4"},
+            "workspace": {"current_dir": "/tmp"},
+                "transcript_path": "/tmp/missing.jsonl"
+            }))
+            .unwrap(),
+        ));
+        let jobs: Vec<(Box<dyn Segment>, Duration)> = vec![
+            (
+                Box::new(Slow(Duration::from_millis(10))),
+                Duration::from_secs(5),
+            ),
+            (
+                Box::new(Slow(Duration::from_secs(2))),
+                Duration::from_millis(50),
+            ),
+            (
+                Box::new(Slow(Duration::from_millis(10))),
+                Duration::from_secs(5),
+            ),
+        ];
+
+        let start = Instant::now();
+        let results = collect_variables(jobs, &ctx);
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(results[0].is_some());
+        assert!(results[1].is_none());
+        assert_eq!(
+            results[2]
+                .as_ref()
+                .and_then(|(_, v)| v.get("model"))
+                .cloned(),
+            Some(Value::Text("done".to_string()))
+        );
     }
 }
@@ -0,0 +1,440 @@
+//! Format-string templates for segment output
+//!
+//! Syntax:
+//! - `{name}` inserts a variable, `{name:spec}` formats it (see [`Spec`])
+//! - `{?name}...{/}` renders its body only when `name` is set (and not empty text)
+//! - `{!name}...{/}` renders its body only when `name` is unset
+//! - `{{` and `}}` produce literal braces
+
+use crate::billing::calculator::format_remaining_time;
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// A value exposed by a segment to its template
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Text(String),
+    /// Plain number; whole values print without decimals, others with one
+    Number(f64),
+    /// Amount in dollars; prints as `$x.xx` by default
+    Money(f64),
+    /// Length of time in minutes; prints as `3h 45m` by default
+    Minutes(i64),
+}
+
+impl From<String> for Value {
+    fn from(text: String) -> Self {
+        Value::Text(text)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(text: &str) -> Self {
+        Value::Text(text.to_string())
+    }
+}
+
+/// Named values available to a segment's template
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Variables(BTreeMap<String, Value>);
+
+impl Variables {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set<V: Into<Value>>(&mut self, name: &str, value: V) {
+        self.0.insert(name.to_string(), value.into());
+    }
+
+    /// Builder-style `set`
+    pub fn with<V: Into<Value>>(mut self, name: &str, value: V) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.0.get(name)
+    }
+
+    pub fn remove(&mut self, name: &str) -> Option<Value> {
+        self.0.remove(name)
+    }
+
+    /// No variables means the segment has nothing to show
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn is_set(&self, name: &str) -> bool {
+        match self.0.get(name) {
+            Some(Value::Text(text)) => !text.is_empty(),
+            Some(_) => true,
+            None => false,
+        }
+    }
+}
+
+/// How a number is scaled and suffixed
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Unit {
+    None,
+    /// Divide by 1000 and append `k` (values below 1000 are left as is)
+    Thousands,
+    /// Append `%`
+    Percent,
+    /// Minutes as a bare count, e.g. `225m`
+    Minutes,
+    /// Minutes as `h:mm`
+    Clock,
+}
+
+/// Format spec after the `:` in `{name:spec}`
+///
+/// `[$][.N][k|%|m|clock]`: `$` prints as currency, `.N` fixes the number of
+/// decimals, `k` shortens thousands, `%` appends a percent sign, and `m` /
+/// `clock` print durations as total minutes or `h:mm`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Spec {
+    currency: bool,
+    precision: Option<usize>,
+    unit: Unit,
+}
+
+impl Spec {
+    const DEFAULT: Spec = Spec {
+        currency: false,
+        precision: None,
+        unit: Unit::None,
+    };
+
+    fn parse(spec: &str) -> Result<Self, String> {
+        let mut rest = spec;
+        let mut parsed = Spec::DEFAULT;
+
+        if let Some(stripped) = rest.strip_prefix('$') {
+            parsed.currency = true;
+            rest = stripped;
+        }
+
+        if let Some(stripped) = rest.strip_prefix('.') {
+            let digits = stripped.chars().take_while(char::is_ascii_digit).count();
+            if digits == 0 {
+                return Err(format!(
+                    "expected digits after `.` in format spec `{}`",
+                    spec
+                ));
+            }
+            parsed.precision = stripped[..digits].parse().ok();
+            rest = &stripped[digits..];
+        }
+
+        parsed.unit = match rest {
+            "" => Unit::None,
+            "k" => Unit::Thousands,
+            "%" => Unit::Percent,
+            "m" => Unit::Minutes,
+            "clock" => Unit::Clock,
+            other => {
+                return Err(format!(
+                    "unknown format spec `{}` (expected [$][.N][k|%|m|clock])",
+                    other
+                ))
+            }
+        };
+        Ok(parsed)
+    }
+
+    fn format(&self, value: &Value) -> String {
+        match value {
+            Value::Text(text) => text.clone(),
+            Value::Money(amount) => self.format_number(*amount, true),
+            Value::Number(number) => self.format_number(*number, self.currency),
+            Value::Minutes(minutes) => match self.unit {
+                Unit::Minutes => format!("{}m", minutes),
+                Unit::Clock => format!("{}:{:02}", minutes / 60, (minutes % 60).abs()),
+                _ => format_remaining_time(*minutes),
+            },
+        }
+    }
+
+    fn format_number(&self, number: f64, currency: bool) -> String {
+        let (number, suffix) = match self.unit {
+            Unit::Thousands if number.abs() >= 1000.0 => (number / 1000.0, "k"),
+            Unit::Percent => (number, "%"),
+            _ => (number, ""),
+        };
+
+        let precision = self.precision.unwrap_or(if currency {
+            2
+        } else if number.fract() == 0.0 {
+            0
+        } else {
+            1
+        });
+
+        let prefix = if currency { "$" } else { "" };
+        format!("{}{:.*}{}", prefix, precision, number, suffix)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Node {
+    Literal(String),
+    Variable {
+        name: String,
+        spec: Spec,
+    },
+    Section {
+        name: String,
+        negated: bool,
+        body: Vec<Node>,
+    },
+}
+
+/// Error from parsing a template, with the byte offset it was found at
+#[derive(Debug, Clone, PartialEq)]
+pub struct TemplateError {
+    pub offset: usize,
+    pub message: String,
+}
+
+impl fmt::Display for TemplateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (at offset {})", self.message, self.offset)
+    }
+}
+
+impl std::error::Error for TemplateError {}
+
+/// A parsed format string
+#[derive(Debug, Clone, PartialEq)]
+pub struct Template {
+    nodes: Vec<Node>,
+}
+
+impl Template {
+    pub fn parse(source: &str) -> Result<Self, TemplateError> {
+        let mut parser = Parser { source, offset: 0 };
+        let (nodes, closed) = parser.parse_nodes()?;
+        if closed {
+            return Err(TemplateError {
+                offset: parser.offset - 3,
+                message: "`{/}` without an open section".to_string(),
+            });
+        }
+        Ok(Self { nodes })
+    }
+
+    pub fn render(&self, variables: &Variables) -> String {
+        let mut out = String::new();
+        render_nodes(&self.nodes, variables, &mut out);
+        out
+    }
+
+    /// Whether the template refers to `name` anywhere
+    pub fn uses(&self, name: &str) -> bool {
+        fn walk(nodes: &[Node], name: &str) -> bool {
+            nodes.iter().any(|node| match node {
+                Node::Literal(_) => false,
+                Node::Variable { name: n, .. } => n == name,
+                Node::Section { name: n, body, .. } => n == name || walk(body, name),
+            })
+        }
+        walk(&self.nodes, name)
+    }
+}
+
+fn render_nodes(nodes: &[Node], variables: &Variables, out: &mut String) {
+    for node in nodes {
+        match node {
+            Node::Literal(text) => out.push_str(text),
+            Node::Variable { name, spec } => {
+                if let Some(value) = variables.get(name) {
+                    out.push_str(&spec.format(value));
+                }
+            }
+            Node::Section {
+                name,
+                negated,
+                body,
+            } => {
+                if variables.is_set(name) != *negated {
+                    render_nodes(body, variables, out);
+                }
+            }
+        }
+    }
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    offset: usize,
+}
+
+impl Parser<'_> {
+    /// Parse until the end of input or a `{/}`; the flag reports which one ended it
+    fn parse_nodes(&mut self) -> Result<(Vec<Node>, bool), TemplateError> {
+        let mut nodes = Vec::new();
+        let mut literal = String::new();
+
+        while let Some(c) = self.source[self.offset..].chars().next() {
+            let rest = &self.source[self.offset..];
+            match c {
+                '{' if rest.starts_with("{{") => {
+                    literal.push('{');
+                    self.offset += 2;
+                }
+                '}' if rest.starts_with("}}") => {
+                    literal.push('}');
+                    self.offset += 2;
+                }
+                '}' => {
+                    return Err(
+                        self.error(self.offset, "unmatched `}` (use `}}` for a literal brace)")
+                    )
+                }
+                '{' => {
+                    let start = self.offset;
+                    let end = rest
+                        .find('}')
+                        .ok_or_else(|| self.error(start, "unclosed `{`"))?;
+                    let tag = &rest[1..end];
+                    self.offset += end + 1;
+
+                    if !literal.is_empty() {
+                        nodes.push(Node::Literal(std::mem::take(&mut literal)));
+                    }
+
+                    if tag == "/" {
+                        return Ok((nodes, true));
+                    }
+                    nodes.push(self.parse_tag(tag, start)?);
+                }
+                _ => {
+                    literal.push(c);
+                    self.offset += c.len_utf8();
+                }
+            }
+        }
+
+        if !literal.is_empty() {
+            nodes.push(Node::Literal(literal));
+        }
+        Ok((nodes, false))
+    }
+
+    fn parse_tag(&mut self, tag: &str, start: usize) -> Result<Node, TemplateError> {
+        if let Some((negated, name)) = tag
+            .strip_prefix('?')
+            .map(|name| (false, name))
+            .or_else(|| tag.strip_prefix('!').map(|name| (true, name)))
+        {
+            let name = self.check_name(name, start)?;
+            let (body, closed) = self.parse_nodes()?;
+            if !closed {
+                return Err(self.error(start, &format!("section `{}` is missing `{{/}}`", tag)));
+            }
+            return Ok(Node::Section {
+                name,
+                negated,
+                body,
+            });
+        }
+
+        let (name, spec) = match tag.split_once(':') {
+            Some((name, spec)) => (
+                name,
+                Spec::parse(spec).map_err(|message| self.error(start, &message))?,
+            ),
+            None => (tag, Spec::DEFAULT),
+        };
+        Ok(Node::Variable {
+            name: self.check_name(name, start)?,
+            spec,
+        })
+    }
+
+    fn check_name(&self, name: &str, start: usize) -> Result<String, TemplateError> {
+        let name = name.trim();
+        if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+            return Err(self.error(start, &format!("invalid variable name `{}`", name)));
+        }
+        Ok(name.to_string())
+    }
+
+    fn error(&self, offset: usize, message: &str) -> TemplateError {
+        TemplateError {
+            offset,
+            message: message.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn render(template: &str, variables: &Variables) -> String {
+        Template::parse(template).unwrap().render(variables)
+    }
+
+    #[test]
+    fn test_variables_and_specs() {
+        let vars = Variables::new()
+            .with("icon", "$")
+            .with("session_cost", Value::Money(1.2346))
+            .with("tokens", Value::Number(12500.0))
+            .with("small", Value::Number(999.0))
+            .with("percent", Value::Number(42.0))
+            .with("remaining", Value::Minutes(225));
+
+        assert_eq!(
+            render("{icon} {session_cost:$.2} / {session_cost:.3}", &vars),
+            "$ $1.23 / $1.235"
+        );
+        assert_eq!(
+            render("{tokens:k} {small:k} {percent:%}", &vars),
+            "12.5k 999 42%"
+        );
+        assert_eq!(render("{tokens:$.0}", &vars), "$12500");
+        assert_eq!(
+            render("{remaining} {remaining:m} {remaining:clock}", &vars),
+            "3h 45m 225m 3:45"
+        );
+        assert_eq!(render("{{literal}} {missing}", &vars), "{literal} ");
+    }
+
+    #[test]
+    fn test_conditional_sections() {
+        let template = "{?block}{block} block{/}{!block}No active block{/}";
+        let with_block = Variables::new().with("block", Value::Money(3.5));
+        assert_eq!(render(template, &with_block), "$3.50 block");
+        assert_eq!(render(template, &Variables::new()), "No active block");
+
+        // Empty text counts as unset; sections nest
+        let vars = Variables::new().with("branch", "main").with("sha", "");
+        assert_eq!(
+            render("{?branch}[{branch}{?sha} {sha}{/}]{/}", &vars),
+            "[main]"
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let error = |source: &str| Template::parse(source).unwrap_err();
+
+        assert_eq!(error("abc {name").message, "unclosed `{`");
+        assert_eq!(error("abc {name").offset, 4);
+        assert!(error("{x:.}").message.contains("expected digits"));
+        assert!(error("{x:bogus}").message.contains("unknown format spec"));
+        assert!(error("{?x}open").message.contains("missing `{/}`"));
+        assert!(error("close{/}")
+            .message
+            .contains("without an open section"));
+        assert!(error("a } b").message.contains("unmatched `}`"));
+        assert!(error("{bad name}")
+            .message
+            .contains("invalid variable name"));
+    }
+}
@@ -0,0 +1,686 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+
+/// Names of the themes shipped with ccline
+pub const BUILTIN_THEMES: &[&str] = &[
+    "dark",
+    "light",
+    "solarized",
+    "nord",
+    "gruvbox",
+    "high-contrast",
+];
+
+/// Style keys a theme can define: one per segment, the separator, and the
+/// `warning` / `critical` overlays for segments close to a limit
+pub const STYLE_KEYS: &[&str] = &[
+    "model",
+    "directory",
+    "git",
+    "usage",
+    "cost",
+    "burn_rate",
+    "block",
+    "update",
+    "separator",
+    "warning",
+    "critical",
+];
+
+const ANSI_NAMES: [&str; 16] = [
+    "black",
+    "red",
+    "green",
+    "yellow",
+    "blue",
+    "magenta",
+    "cyan",
+    "white",
+    "bright_black",
+    "bright_red",
+    "bright_green",
+    "bright_yellow",
+    "bright_blue",
+    "bright_magenta",
+    "bright_cyan",
+    "bright_white",
+];
+
+/// Approximate RGB values of the 16 standard ANSI colors (xterm defaults)
+const ANSI_RGB: [(u8, u8, u8); 16] = [
+    (0, 0, 0),
+    (205, 0, 0),
+    (0, 205, 0),
+    (205, 205, 0),
+    (0, 0, 238),
+    (205, 0, 205),
+    (0, 205, 205),
+    (229, 229, 229),
+    (127, 127, 127),
+    (255, 0, 0),
+    (0, 255, 0),
+    (255, 255, 0),
+    (92, 92, 255),
+    (255, 0, 255),
+    (0, 255, 255),
+    (255, 255, 255),
+];
+
+/// A terminal color
+///
+/// Specs accepted in configuration: ANSI names (`"cyan"`, `"bright_red"`),
+/// 256-color indices (`"208"`) and hex truecolor (`"#88c0d0"`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub enum Color {
+    /// One of the 16 standard colors (0-15)
+    Ansi(u8),
+    /// 256-color palette index
+    Indexed(u8),
+    Rgb(u8, u8, u8),
+}
+
+impl Color {
+    pub fn parse(spec: &str) -> Result<Self, String> {
+        let spec = spec.trim();
+
+        if let Some(hex) = spec.strip_prefix('#') {
+            if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).unwrap_or(0);
+                return Ok(Color::Rgb(channel(0), channel(2), channel(4)));
+            }
+            return Err(format!("invalid hex color `{}` (expected #rrggbb)", spec));
+        }
+
+        if let Ok(index) = spec.parse::<u16>() {
+            return u8::try_from(index)
+                .map(Color::Indexed)
+                .map_err(|_| format!("color index {} is out of range (0-255)", index));
+        }
+
+        let normalized = spec.to_lowercase().replace(['-', ' '], "_");
+        ANSI_NAMES
+            .iter()
+            .position(|name| *name == normalized)
+            .map(|i| Color::Ansi(i as u8))
+            .ok_or_else(|| {
+                format!(
+                    "unknown color `{}` (expected an ANSI name, 0-255 or #rrggbb)",
+                    spec
+                )
+            })
+    }
+
+    fn to_rgb(self) -> (u8, u8, u8) {
+        match self {
+            Color::Ansi(i) => ANSI_RGB[(i & 15) as usize],
+            Color::Indexed(i) if i < 16 => ANSI_RGB[i as usize],
+            Color::Indexed(i) if i >= 232 => {
+                let level = 8 + (i - 232) * 10;
+                (level, level, level)
+            }
+            Color::Indexed(i) => {
+                let i = i - 16;
+                let level = |v: u8| if v == 0 { 0 } else { 55 + v * 40 };
+                (level(i / 36), level((i / 6) % 6), level(i % 6))
+            }
+            Color::Rgb(r, g, b) => (r, g, b),
+        }
+    }
+
+    /// Whether dark text reads better than light text on this color
+    pub fn is_light(self) -> bool {
+        let (r, g, b) = self.to_rgb();
+        // Rec. 601 luma
+        (299 * r as u32 + 587 * g as u32 + 114 * b as u32) / 1000 > 140
+    }
+
+    /// Blend towards `other`; `t` = 0 is this color and 1 is `other`
+    pub fn mix(self, other: Color, t: f64) -> Color {
+        let (r1, g1, b1) = self.to_rgb();
+        let (r2, g2, b2) = other.to_rgb();
+        let t = t.clamp(0.0, 1.0);
+        let channel = |a: u8, b: u8| (a as f64 + (b as f64 - a as f64) * t).round() as u8;
+        Color::Rgb(channel(r1, r2), channel(g1, g2), channel(b1, b2))
+    }
+
+    /// Reduce the color to what the given mode can display
+    pub fn downsample(self, mode: ColorMode) -> Color {
+        match (mode, self) {
+            (ColorMode::TrueColor | ColorMode::Auto, color) => color,
+            (ColorMode::Ansi256, Color::Rgb(r, g, b)) => Color::Indexed(rgb_to_256(r, g, b)),
+            (ColorMode::Ansi256, color) => color,
+            (ColorMode::Ansi16, Color::Ansi(i)) => Color::Ansi(i),
+            (ColorMode::Ansi16, Color::Indexed(i)) if i < 16 => Color::Ansi(i),
+            (ColorMode::Ansi16, color) => Color::Ansi(nearest_ansi(color.to_rgb())),
+        }
+    }
+
+    fn sgr(self, background: bool) -> String {
+        match self {
+            Color::Ansi(i) => {
+                let base = match (background, i < 8) {
+                    (false, true) => 30,
+                    (false, false) => 82,
+                    (true, true) => 40,
+                    (true, false) => 92,
+                };
+                (base + i as u16).to_string()
+            }
+            Color::Indexed(i) => format!("{};5;{}", if background { 48 } else { 38 }, i),
+            Color::Rgb(r, g, b) => {
+                format!("{};2;{};{};{}", if background { 48 } else { 38 }, r, g, b)
+            }
+        }
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Color::parse(&value)
+    }
+}
+
+impl From<Color> for String {
+    fn from(color: Color) -> Self {
+        color.to_string()
+    }
+}
+
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Color::Ansi(i) => write!(f, "{}", ANSI_NAMES[(*i & 15) as usize]),
+            Color::Indexed(i) => write!(f, "{}", i),
+            Color::Rgb(r, g, b) => write!(f, "#{:02x}{:02x}{:02x}", r, g, b),
+        }
+    }
+}
+
+fn rgb_to_256(r: u8, g: u8, b: u8) -> u8 {
+    // Grayscale ramp gives better results for neutral colors
+    if r == g && g == b {
+        if r < 8 {
+            return 16;
+        }
+        if r > 248 {
+            return 231;
+        }
+        return 232 + ((r as u16 - 8) * 24 / 247) as u8;
+    }
+    let level = |v: u8| -> u8 {
+        if v < 48 {
+            0
+        } else if v < 115 {
+            1
+        } else {
+            ((v - 35) / 40).min(5)
+        }
+    };
+    16 + 36 * level(r) + 6 * level(g) + level(b)
+}
+
+fn nearest_ansi((r, g, b): (u8, u8, u8)) -> u8 {
+    let distance = |(cr, cg, cb): (u8, u8, u8)| {
+        let dr = r as i32 - cr as i32;
+        let dg = g as i32 - cg as i32;
+        let db = b as i32 - cb as i32;
+        dr * dr + dg * dg + db * db
+    };
+    (0..16u8)
+        .min_by_key(|i| distance(ANSI_RGB[*i as usize]))
+        .unwrap_or(7)
+}
+
+/// Color depth used when emitting escape sequences
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorMode {
+    /// Detect from `COLORTERM` / `TERM`
+    #[default]
+    Auto,
+    #[serde(rename = "16")]
+    Ansi16,
+    #[serde(rename = "256")]
+    Ansi256,
+    #[serde(rename = "truecolor")]
+    TrueColor,
+}
+
+impl ColorMode {
+    /// Resolve `Auto` against the environment
+    pub fn detect(self) -> ColorMode {
+        if self != ColorMode::Auto {
+            return self;
+        }
+
+        let colorterm = std::env::var("COLORTERM").unwrap_or_default();
+        if colorterm == "truecolor" || colorterm == "24bit" {
+            return ColorMode::TrueColor;
+        }
+        if std::env::var("TERM").is_ok_and(|term| term.contains("256")) {
+            return ColorMode::Ansi256;
+        }
+        ColorMode::Ansi16
+    }
+}
+
+/// Text style for a segment; unset fields inherit from the base theme
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Style {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fg: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bg: Option<Color>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub italic: Option<bool>,
+    /// Separate foreground for the leading icon
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub icon: Option<Color>,
+}
+
+impl Style {
+    fn new(fg: Color) -> Self {
+        Self {
+            fg: Some(fg),
+            bg: None,
+            bold: None,
+            italic: None,
+            icon: None,
+        }
+    }
+
+    fn bold(mut self) -> Self {
+        self.bold = Some(true);
+        self
+    }
+
+    fn on(mut self, bg: Color) -> Self {
+        self.bg = Some(bg);
+        self
+    }
+
+    fn icon(mut self, color: Color) -> Self {
+        self.icon = Some(color);
+        self
+    }
+
+    /// Overlay `other` on top of this style, field by field
+    pub fn merge(&self, other: &Style) -> Style {
+        Style {
+            fg: other.fg.or(self.fg),
+            bg: other.bg.or(self.bg),
+            bold: other.bold.or(self.bold),
+            italic: other.italic.or(self.italic),
+            icon: other.icon.or(self.icon),
+        }
+    }
+
+    /// SGR parameter string for this style (without the ESC[ ... m wrapper)
+    pub fn sgr(&self, mode: ColorMode) -> String {
+        let mut codes = Vec::new();
+        if self.bold == Some(true) {
+            codes.push("1".to_string());
+        }
+        if self.italic == Some(true) {
+            codes.push("3".to_string());
+        }
+        if let Some(fg) = self.fg {
+            codes.push(fg.downsample(mode).sgr(false));
+        }
+        if let Some(bg) = self.bg {
+            codes.push(bg.downsample(mode).sgr(true));
+        }
+        codes.join(";")
+    }
+
+    /// Variant of this style for a filled block (powerline and capsule modes)
+    ///
+    /// Styles without a background get one derived from the foreground,
+    /// with black or white text depending on its brightness.
+    pub fn filled(self) -> Style {
+        match (self.bg, self.fg) {
+            (Some(_), _) => self,
+            (None, Some(fg)) => Style {
+                fg: Some(if fg.is_light() {
+                    Color::Ansi(0)
+                } else {
+                    Color::Ansi(15)
+                }),
+                bg: Some(fg),
+                icon: None,
+                ..self
+            },
+            (None, None) => Style {
+                bg: Some(Color::Ansi(8)),
+                ..self
+            },
+        }
+    }
+
+    /// Wrap `text` in the escape sequences for this style
+    pub fn paint(&self, text: &str, mode: ColorMode) -> String {
+        let codes = self.sgr(mode);
+        if codes.is_empty() || text.is_empty() {
+            return text.to_string();
+        }
+        // Text may carry its own colored spans, such as a progress bar; restore
+        // this style after each of their resets
+        let text = text.replace("\x1b[0m", &format!("\x1b[0m\x1b[{}m", codes));
+        format!("\x1b[{}m{}\x1b[0m", codes, text)
+    }
+}
+
+/// User theme definition from `[themes.<name>]`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ThemeConfig {
+    /// Theme to inherit unset styles from (defaults to `dark`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub base: Option<String>,
+    /// Per-segment styles keyed by segment name (or `separator`)
+    #[serde(flatten)]
+    pub styles: BTreeMap<String, Style>,
+}
+
+/// A fully resolved theme
+#[derive(Debug, Clone)]
+pub struct Theme {
+    pub name: String,
+    styles: BTreeMap<String, Style>,
+    mode: ColorMode,
+}
+
+impl Theme {
+    /// Resolve a theme by name: user-defined themes first, then built-ins, then `dark`
+    pub fn resolve(
+        name: &str,
+        user_themes: &BTreeMap<String, ThemeConfig>,
+        mode: ColorMode,
+    ) -> Self {
+        let styles = resolve_styles(name, user_themes, 0).unwrap_or_else(|| {
+            eprintln!("Warning: unknown theme `{}`, using `dark`", name);
+            builtin_styles("dark").unwrap_or_default()
+        });
+
+        Self {
+            name: name.to_string(),
+            styles,
+            mode: mode.detect(),
+        }
+    }
+
+    /// Whether a theme name refers to a built-in or user-defined theme
+    pub fn exists(name: &str, user_themes: &BTreeMap<String, ThemeConfig>) -> bool {
+        user_themes.contains_key(name) || BUILTIN_THEMES.contains(&name)
+    }
+
+    pub fn color_mode(&self) -> ColorMode {
+        self.mode
+    }
+
+    /// Style for a segment or `separator`
+    pub fn style(&self, key: &str) -> Style {
+        self.styles.get(key).copied().unwrap_or_default()
+    }
+
+    /// Paint a rendered segment, giving its leading icon a separate color when configured
+    pub fn paint(&self, key: &str, content: &str) -> String {
+        self.paint_with(&self.style(key), content)
+    }
+
+    /// Paint `content` with an explicit style, honouring its icon color
+    pub fn paint_with(&self, style: &Style, content: &str) -> String {
+        if let (Some(icon_color), Some((icon, rest))) = (style.icon, content.split_once(' ')) {
+            let icon_style = Style {
+                fg: Some(icon_color),
+                ..*style
+            };
+            // Keep the gap inside the colored area when the segment has a background
+            let (gap, rest) = match style.bg {
+                Some(_) => ("", format!(" {}", rest)),
+                None => (" ", rest.to_string()),
+            };
+            return format!(
+                "{}{}{}",
+                icon_style.paint(icon, self.mode),
+                gap,
+                style.paint(&rest, self.mode)
+            );
+        }
+        style.paint(content, self.mode)
+    }
+}
+
+/// Maximum `base` chain length, guards against cycles in user themes
+const MAX_THEME_DEPTH: usize = 8;
+
+fn resolve_styles(
+    name: &str,
+    user_themes: &BTreeMap<String, ThemeConfig>,
+    depth: usize,
+) -> Option<BTreeMap<String, Style>> {
+    if depth > MAX_THEME_DEPTH {
+        return None;
+    }
+
+    let Some(user) = user_themes.get(name) else {
+        return builtin_styles(name);
+    };
+
+    let base_name = user.base.as_deref().unwrap_or("dark");
+    let mut styles = if base_name == name {
+        builtin_styles(name)
+    } else {
+        resolve_styles(base_name, user_themes, depth + 1)
+    }?;
+
+    for (key, style) in &user.styles {
+        let merged = styles.get(key).copied().unwrap_or_default().merge(style);
+        styles.insert(key.clone(), merged);
+    }
+    Some(styles)
+}
+
+fn builtin_styles(name: &str) -> Option<BTreeMap<String, Style>> {
+    use Color::{Ansi, Rgb};
+
+    let palette: [Style; 11] = match name {
+        "dark" => [
+            Style::new(Ansi(6)).bold(),
+            Style::new(Ansi(2)).bold().icon(Ansi(3)),
+            Style::new(Ansi(4)).bold(),
+            Style::new(Ansi(5)).bold(),
+            Style::new(Ansi(3)).bold(),
+            Style::new(Ansi(1)).bold(),
+            Style::new(Ansi(12)).bold(),
+            Style::new(Ansi(7)).bold(),
+            Style::new(Ansi(7)),
+            Style::new(Ansi(3)).bold(),
+            Style::new(Ansi(1)).bold(),
+        ],
+        "light" => [
+            Style::new(Color::Indexed(25)).bold(),
+            Style::new(Color::Indexed(28))
+                .bold()
+                .icon(Color::Indexed(130)),
+            Style::new(Color::Indexed(20)).bold(),
+            Style::new(Color::Indexed(90)).bold(),
+            Style::new(Color::Indexed(130)).bold(),
+            Style::new(Color::Indexed(124)).bold(),
+            Style::new(Color::Indexed(31)).bold(),
+            Style::new(Color::Indexed(236)).bold(),
+            Style::new(Color::Indexed(244)),
+            Style::new(Color::Indexed(166)).bold(),
+            Style::new(Color::Indexed(160)).bold(),
+        ],
+        "solarized" => [
+            Style::new(Rgb(0x2a, 0xa1, 0x98)).bold(),
+            Style::new(Rgb(0x85, 0x99, 0x00))
+                .bold()
+                .icon(Rgb(0xb5, 0x89, 0x00)),
+            Style::new(Rgb(0x26, 0x8b, 0xd2)).bold(),
+            Style::new(Rgb(0x6c, 0x71, 0xc4)).bold(),
+            Style::new(Rgb(0xb5, 0x89, 0x00)).bold(),
+            Style::new(Rgb(0xdc, 0x32, 0x2f)).bold(),
+            Style::new(Rgb(0x26, 0x8b, 0xd2)).bold(),
+            Style::new(Rgb(0xd3, 0x36, 0x82)).bold(),
+            Style::new(Rgb(0x58, 0x6e, 0x75)),
+            Style::new(Rgb(0xcb, 0x4b, 0x16)).bold(),
+            Style::new(Rgb(0xdc, 0x32, 0x2f)).bold(),
+        ],
+        "nord" => [
+            Style::new(Rgb(0x88, 0xc0, 0xd0)).bold(),
+            Style::new(Rgb(0xa3, 0xbe, 0x8c))
+                .bold()
+                .icon(Rgb(0xeb, 0xcb, 0x8b)),
+            Style::new(Rgb(0x81, 0xa1, 0xc1)).bold(),
+            Style::new(Rgb(0xb4, 0x8e, 0xad)).bold(),
+            Style::new(Rgb(0xeb, 0xcb, 0x8b)).bold(),
+            Style::new(Rgb(0xbf, 0x61, 0x6a)).bold(),
+            Style::new(Rgb(0x8f, 0xbc, 0xbb)).bold(),
+            Style::new(Rgb(0xd8, 0xde, 0xe9)).bold(),
+            Style::new(Rgb(0x4c, 0x56, 0x6a)),
+            Style::new(Rgb(0xd0, 0x87, 0x70)).bold(),
+            Style::new(Rgb(0xbf, 0x61, 0x6a)).bold(),
+        ],
+        "gruvbox" => [
+            Style::new(Rgb(0x8e, 0xc0, 0x7c)).bold(),
+            Style::new(Rgb(0xb8, 0xbb, 0x26))
+                .bold()
+                .icon(Rgb(0xfa, 0xbd, 0x2f)),
+            Style::new(Rgb(0x83, 0xa5, 0x98)).bold(),
+            Style::new(Rgb(0xd3, 0x86, 0x9b)).bold(),
+            Style::new(Rgb(0xfa, 0xbd, 0x2f)).bold(),
+            Style::new(Rgb(0xfb, 0x49, 0x34)).bold(),
+            Style::new(Rgb(0x68, 0x9d, 0x6a)).bold(),
+            Style::new(Rgb(0xfe, 0x80, 0x19)).bold(),
+            Style::new(Rgb(0xa8, 0x99, 0x84)),
+            Style::new(Rgb(0xfe, 0x80, 0x19)).bold(),
+            Style::new(Rgb(0xfb, 0x49, 0x34)).bold(),
+        ],
+        "high-contrast" => [
+            Style::new(Ansi(0)).bold().on(Ansi(14)),
+            Style::new(Ansi(0)).bold().on(Ansi(10)),
+            Style::new(Ansi(15)).bold().on(Ansi(4)),
+            Style::new(Ansi(0)).bold().on(Ansi(13)),
+            Style::new(Ansi(0)).bold().on(Ansi(11)),
+            Style::new(Ansi(15)).bold().on(Ansi(1)),
+            Style::new(Ansi(0)).bold().on(Ansi(12)),
+            Style::new(Ansi(0)).bold().on(Ansi(15)),
+            Style::new(Ansi(15)).bold(),
+            Style::new(Ansi(0)).bold().on(Ansi(11)),
+            Style::new(Ansi(15)).bold().on(Ansi(9)),
+        ],
+        _ => return None,
+    };
+
+    Some(
+        STYLE_KEYS
+            .iter()
+            .zip(palette)
+            .map(|(key, style)| (key.to_string(), style))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_color_specs() {
+        assert_eq!(Color::parse("cyan"), Ok(Color::Ansi(6)));
+        assert_eq!(Color::parse("Bright-Red"), Ok(Color::Ansi(9)));
+        assert_eq!(Color::parse("208"), Ok(Color::Indexed(208)));
+        assert_eq!(Color::parse("#88C0d0"), Ok(Color::Rgb(0x88, 0xc0, 0xd0)));
+        assert!(Color::parse("256").is_err());
+        assert!(Color::parse("#12345").is_err());
+        assert!(Color::parse("purple-ish").is_err());
+    }
+
+    #[test]
+    fn test_dark_theme_matches_classic_escapes() {
+        let theme = Theme::resolve("dark", &BTreeMap::new(), ColorMode::Ansi16);
+        assert_eq!(
+            theme.paint("model", "Sonnet 4"),
+            "\x1b[1;36mSonnet 4\x1b[0m"
+        );
+        assert_eq!(
+            theme.paint("directory", "\u{f024b} demo"),
+            "\x1b[1;33m\u{f024b}\x1b[0m \x1b[1;32mdemo\x1b[0m"
+        );
+        assert_eq!(theme.paint("separator", " | "), "\x1b[37m | \x1b[0m");
+    }
+
+    #[test]
+    fn test_color_depths() {
+        let style = Style::new(Color::Rgb(0x88, 0xc0, 0xd0)).on(Color::Indexed(236));
+        assert_eq!(style.sgr(ColorMode::TrueColor), "38;2;136;192;208;48;5;236");
+        assert_eq!(style.sgr(ColorMode::Ansi256), "38;5;110;48;5;236");
+        assert_eq!(style.sgr(ColorMode::Ansi16), "37;40");
+    }
+
+    #[test]
+    fn test_nested_spans_restore_style() {
+        let inner = Style::new(Color::Ansi(2)).paint("▰", ColorMode::Ansi16);
+        let outer = Style::new(Color::Ansi(5)).bold();
+        assert_eq!(
+            outer.paint(&format!("{}▱ 40%", inner), ColorMode::Ansi16),
+            "\x1b[1;35m\x1b[32m▰\x1b[0m\x1b[1;35m▱ 40%\x1b[0m"
+        );
+    }
+
+    #[test]
+    fn test_user_theme_inherits_from_base() {
+        let git = Style {
+            fg: Some(Color::Rgb(0xff, 0x88, 0x00)),
+            italic: Some(true),
+            ..Default::default()
+        };
+
+        let mut user_themes = BTreeMap::new();
+        user_themes.insert(
+            "mine".to_string(),
+            ThemeConfig {
+                base: Some("nord".to_string()),
+                styles: BTreeMap::from([("git".to_string(), git)]),
+            },
+        );
+
+        let theme = Theme::resolve("mine", &user_themes, ColorMode::TrueColor);
+        let git_style = theme.style("git");
+        assert_eq!(git_style.fg, Some(Color::Rgb(0xff, 0x88, 0x00)));
+        assert_eq!(git_style.bold, Some(true));
+        assert_eq!(git_style.italic, Some(true));
+        assert_eq!(theme.style("model").fg, Some(Color::Rgb(0x88, 0xc0, 0xd0)));
+        assert!(Theme::exists("mine", &user_themes));
+        assert!(!Theme::exists("missing", &user_themes));
+    }
+
+    #[test]
+    fn test_user_theme_cycle_falls_back() {
+        let user_themes = BTreeMap::from([
+            (
+                "a".to_string(),
+                ThemeConfig {
+                    base: Some("b".to_string()),
+                    styles: BTreeMap::new(),
+                },
+            ),
+            (
+                "b".to_string(),
+                ThemeConfig {
+                    base: Some("a".to_string()),
+                    styles: BTreeMap::new(),
+                },
+            ),
+        ]);
+        assert!(resolve_styles("a", &user_themes, 0).is_none());
+    }
+}
@@ -0,0 +1,258 @@
+//! Terminal display width of rendered text
+
+/// Number of terminal columns `text` occupies, ignoring ANSI escape sequences
+pub fn display_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            width += char_width(c);
+            continue;
+        }
+
+        match chars.next() {
+            // CSI: parameters up to a final byte in @..~
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('\x40'..='\x7e').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: terminated by BEL or ESC \
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' {
+                        break;
+                    }
+                    if c == '\x1b' && chars.peek() == Some(&'\\') {
+                        chars.next();
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    width
+}
+
+/// Shorten plain `text` to at most `max` columns, ending it with `…` when cut
+pub fn truncate(text: &str, max: usize) -> String {
+    if display_width(text) <= max {
+        return text.to_string();
+    }
+
+    let mut result = String::new();
+    let mut width = 0;
+    for c in text.chars() {
+        let w = char_width(c);
+        // Leave one column for the ellipsis
+        if width + w + 1 > max {
+            break;
+        }
+        result.push(c);
+        width += w;
+    }
+    result.push('…');
+    result
+}
+
+/// Terminal rows `text` takes on a terminal `columns` wide, counting lines the
+/// terminal wraps
+pub fn rows(text: &str, columns: usize) -> usize {
+    text.lines()
+        .map(|line| display_width(line).div_ceil(columns.max(1)).max(1))
+        .sum()
+}
+
+/// Terminal width from `COLUMNS`, or else from the controlling terminal
+///
+/// Claude Code neither exports `COLUMNS` to the statusline command nor gives
+/// it a terminal on stdin or stdout, so on Unix the size of `/dev/tty` is
+/// read directly. Without either the line is not truncated.
+pub fn terminal_columns() -> Option<usize> {
+    std::env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.trim().parse().ok())
+        .or_else(tty_columns)
+}
+
+#[cfg(unix)]
+fn tty_columns() -> Option<usize> {
+    // No controlling terminal, e.g. under a daemon or in CI: nothing to ask
+    let tty = std::fs::File::open("/dev/tty").ok()?;
+    let (terminal_size::Width(columns), _) = terminal_size::terminal_size_of(&tty)?;
+    Some(usize::from(columns))
+}
+
+#[cfg(not(unix))]
+fn tty_columns() -> Option<usize> {
+    None
+}
+
+/// Columns taken by a single character: 0 for controls and combining marks,
+/// 2 for East Asian wide characters and emoji, 1 otherwise
+pub fn char_width(c: char) -> usize {
+    let code = c as u32;
+    if code < 0x20 || (0x7f..0xa0).contains(&code) || in_ranges(code, ZERO_WIDTH) {
+        0
+    } else if in_ranges(code, WIDE) {
+        2
+    } else {
+        1
+    }
+}
+
+fn in_ranges(code: u32, ranges: &[(u32, u32)]) -> bool {
+    ranges
+        .binary_search_by(|&(start, end)| {
+            if end < code {
+                std::cmp::Ordering::Less
+            } else if start > code {
+                std::cmp::Ordering::Greater
+            } else {
+                std::cmp::Ordering::Equal
+            }
+        })
+        .is_ok()
+}
+
+/// Combining marks, joiners and variation selectors
+const ZERO_WIDTH: &[(u32, u32)] = &[
+    (0x0300, 0x036f),
+    (0x0483, 0x0489),
+    (0x0591, 0x05bd),
+    (0x0610, 0x061a),
+    (0x064b, 0x065f),
+    (0x1ab0, 0x1aff),
+    (0x1dc0, 0x1dff),
+    (0x200b, 0x200f),
+    (0x2028, 0x202e),
+    (0x2060, 0x2064),
+    (0x20d0, 0x20ff),
+    (0xfe00, 0xfe0f),
+    (0xfe20, 0xfe2f),
+    (0xfeff, 0xfeff),
+    (0x1f3fb, 0x1f3ff),
+    (0xe0000, 0xe01ef),
+];
+
+/// East Asian wide/fullwidth blocks and emoji shown in emoji presentation
+const WIDE: &[(u32, u32)] = &[
+    (0x1100, 0x115f),
+    (0x231a, 0x231b),
+    (0x2329, 0x232a),
+    (0x23e9, 0x23ec),
+    (0x23f0, 0x23f0),
+    (0x23f3, 0x23f3),
+    (0x25fd, 0x25fe),
+    (0x2614, 0x2615),
+    (0x2648, 0x2653),
+    (0x267f, 0x267f),
+    (0x2693, 0x2693),
+    (0x26a1, 0x26a1),
+    (0x26aa, 0x26ab),
+    (0x26bd, 0x26be),
+    (0x26c4, 0x26c5),
+    (0x26ce, 0x26ce),
+    (0x26d4, 0x26d4),
+    (0x26ea, 0x26ea),
+    (0x26f2, 0x26f3),
+    (0x26f5, 0x26f5),
+    (0x26fa, 0x26fa),
+    (0x26fd, 0x26fd),
+    (0x2705, 0x2705),
+    (0x270a, 0x270b),
+    (0x2728, 0x2728),
+    (0x274c, 0x274c),
+    (0x274e, 0x274e),
+    (0x2753, 0x2755),
+    (0x2757, 0x2757),
+    (0x2795, 0x2797),
+    (0x27b0, 0x27b0),
+    (0x27bf, 0x27bf),
+    (0x2b1b, 0x2b1c),
+    (0x2b50, 0x2b50),
+    (0x2b55, 0x2b55),
+    (0x2e80, 0x303e),
+    (0x3041, 0x33ff),
+    (0x3400, 0x4dbf),
+    (0x4e00, 0x9fff),
+    (0xa000, 0xa4cf),
+    (0xa960, 0xa97f),
+    (0xac00, 0xd7a3),
+    (0xf900, 0xfaff),
+    (0xfe10, 0xfe19),
+    (0xfe30, 0xfe6f),
+    (0xff00, 0xff60),
+    (0xffe0, 0xffe6),
+    (0x1f004, 0x1f004),
+    (0x1f0cf, 0x1f0cf),
+    (0x1f18e, 0x1f18e),
+    (0x1f191, 0x1f19a),
+    (0x1f200, 0x1f2ff),
+    (0x1f300, 0x1f320),
+    (0x1f32d, 0x1f335),
+    (0x1f337, 0x1f37c),
+    (0x1f37e, 0x1f393),
+    (0x1f3a0, 0x1f3ca),
+    (0x1f3cf, 0x1f3d3),
+    (0x1f3e0, 0x1f3f0),
+    (0x1f3f4, 0x1f3f4),
+    (0x1f3f8, 0x1f3fa),
+    (0x1f400, 0x1f43e),
+    (0x1f440, 0x1f440),
+    (0x1f442, 0x1f4fc),
+    (0x1f4ff, 0x1f53d),
+    (0x1f54b, 0x1f54e),
+    (0x1f550, 0x1f567),
+    (0x1f57a, 0x1f57a),
+    (0x1f595, 0x1f596),
+    (0x1f5a4, 0x1f5a4),
+    (0x1f5fb, 0x1f64f),
+    (0x1f680, 0x1f6c5),
+    (0x1f6cc, 0x1f6cc),
+    (0x1f6d0, 0x1f6d2),
+    (0x1f6d5, 0x1f6d7),
+    (0x1f6eb, 0x1f6ec),
+    (0x1f6f4, 0x1f6fc),
+    (0x1f7e0, 0x1f7eb),
+    (0x1f90c, 0x1f93a),
+    (0x1f93c, 0x1f945),
+    (0x1f947, 0x1f9ff),
+    (0x1fa70, 0x1faff),
+    (0x20000, 0x2fffd),
+    (0x30000, 0x3fffd),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display_width_ignores_escapes_and_counts_wide() {
+        assert_eq!(display_width("\x1b[1;36mSonnet 4\x1b[0m"), 8);
+        assert_eq!(display_width("\x1b]8;;https://x\x1b\\link\x1b]8;;\x07"), 4);
+        assert_eq!(display_width("📁 crate"), 8);
+        assert_eq!(display_width("中文"), 4);
+        assert_eq!(display_width("e\u{301}"), 1);
+        // Nerd Font glyphs live in the private use area and take one column
+        assert_eq!(display_width("\u{f02a2} main"), 6);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("crate", 10), "crate");
+        assert_eq!(truncate("ccometixline", 8), "ccometi…");
+        assert_eq!(truncate("中文目录名", 6), "中文…");
+    }
+
+    #[test]
+    fn test_rows_count_wrapped_lines() {
+        assert_eq!(rows("ab\n\ncd", 80), 3);
+        assert_eq!(rows(&"x".repeat(80), 80), 1);
+        assert_eq!(rows(&format!("{}\nshort", "x".repeat(81)), 80), 3);
+        assert_eq!(rows("\x1b[1m中文中文\x1b[0m", 4), 2);
+    }
+}
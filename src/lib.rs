@@ -2,5 +2,7 @@ pub mod billing;
 pub mod cli;
 pub mod config;
 pub mod core;
+pub mod output;
+pub mod report;
 pub mod updater;
 pub mod utils;
@@ -1,8 +1,22 @@
-use ccometixline::cli::Cli;
-use ccometixline::config::{BlockOverrideManager, Config, ConfigLoader, InputData};
+use ccometixline::billing::calculator::{calculate_burn_rate, project_block_cost};
+use ccometixline::billing::pricing_cache::{self, PricingCache};
+use ccometixline::billing::resolver;
+use ccometixline::billing::table::{PricingOutput, PricingTable, ResolutionOutput};
+use ccometixline::billing::ModelPricing;
+use ccometixline::cli::{BlocksArgs, Cli, Command, PricingCommand, ReportCommand, WeeklyArgs};
+use ccometixline::config::{
+    validate_file, BlockOverrideManager, BlockStartChange, ConfigLoader, InputData,
+};
+use ccometixline::core::width::{rows, terminal_columns};
 use ccometixline::core::StatusLineGenerator;
+use ccometixline::output::OutputFormat;
+use ccometixline::report::output::{ReportOutput, RowKey};
+use ccometixline::report::{self, ReportOptions, Table};
+use ccometixline::utils::{transcript_index::TranscriptIndex, DataLoader};
 use chrono::{Local, NaiveDate, Utc};
-use std::io;
+use std::collections::HashMap;
+use std::io::{self, Write};
+use std::path::PathBuf;
 
 fn main() -> io::Result<()> {
     let cli = Cli::parse_args();
@@ -13,14 +27,53 @@ fn main() -> io::Result<()> {
         return Ok(());
     }
 
+    if cli.format != OutputFormat::Table && !cli.supports_format() {
+        eprintln!(
+            "Error: --format only applies to `ccline report`, `ccline pricing resolve`, --show-pricing and the block start commands"
+        );
+        std::process::exit(2);
+    }
+
+    if let Some(Command::Pricing {
+        action: PricingCommand::Resolve { model },
+    }) = &cli.command
+    {
+        handle_pricing_resolve(&cli, model);
+        return Ok(());
+    }
+
+    if let Some(Command::Report { kind }) = &cli.command {
+        handle_report(&cli, kind);
+        return Ok(());
+    }
+
     if cli.print_config {
-        let config = Config::default();
-        println!("{}", toml::to_string(&config).unwrap());
+        let loader = build_config_loader(&cli, std::env::current_dir().ok());
+        match loader.resolve() {
+            Ok(resolved) if cli.resolved => println!("{}", resolved.to_annotated_toml()),
+            Ok(resolved) => println!("{}", toml::to_string(&resolved.config).unwrap()),
+            Err(e) => {
+                eprintln!("Error: {}", e);
+                return Err(io::Error::other(e));
+            }
+        }
         return Ok(());
     }
 
     if cli.validate {
-        println!("Configuration validation not implemented yet");
+        return handle_validate(&cli);
+    }
+
+    if cli.rebuild_index {
+        return handle_rebuild_index();
+    }
+
+    if cli.refresh_pricing {
+        return handle_refresh_pricing(&cli);
+    }
+
+    if cli.show_pricing {
+        handle_show_pricing(&cli);
         return Ok(());
     }
 
@@ -129,13 +182,14 @@ fn main() -> io::Result<()> {
         return handle_block_management(&cli);
     }
 
-    // Load configuration
-    let config = ConfigLoader::load();
-
     // Read Claude Code data from stdin
     let stdin = io::stdin();
     let input = InputData::from_reader(stdin.lock())?;
 
+    // Load configuration (project layer is resolved from the workspace directory)
+    let workspace_dir = PathBuf::from(&input.workspace.current_dir);
+    let config = build_config_loader(&cli, Some(workspace_dir)).load();
+
     // Generate statusline
     let generator = StatusLineGenerator::new(config);
     let statusline = generator.generate(&input);
@@ -145,6 +199,408 @@ fn main() -> io::Result<()> {
     Ok(())
 }
 
+/// Build the layered configuration loader from CLI flags
+fn build_config_loader(cli: &Cli, workspace_dir: Option<PathBuf>) -> ConfigLoader {
+    let mut loader = ConfigLoader::new();
+    if let Some(path) = &cli.config {
+        loader = loader.with_config_path(path);
+    }
+    if let Some(dir) = workspace_dir {
+        loader = loader.with_workspace_dir(dir);
+    }
+    if let Some(theme) = &cli.theme {
+        loader = loader.with_cli_override("theme", theme.as_str(), "--theme");
+    }
+    if let Some(width) = cli.width {
+        loader = loader.with_cli_override("render.width", width as i64, "--width");
+    }
+    if cli.offline {
+        loader = loader.with_cli_override("pricing.offline", true, "--offline");
+    }
+    loader
+}
+
+/// Validate the user and project configuration files, exiting non-zero on errors
+fn handle_validate(cli: &Cli) -> io::Result<()> {
+    let loader = build_config_loader(cli, std::env::current_dir().ok());
+
+    let mut files = Vec::new();
+    let user_path = loader.user_config_path();
+    if cli.config.is_some() || user_path.exists() {
+        files.push(user_path.clone());
+    }
+    if let Some(project_path) = loader.project_config_path() {
+        if !files.contains(&project_path) {
+            files.push(project_path);
+        }
+    }
+
+    if files.is_empty() {
+        println!(
+            "No configuration files found (looked for {})",
+            user_path.display()
+        );
+        return Ok(());
+    }
+
+    let mut error_count = 0;
+    for file in &files {
+        match validate_file(file) {
+            Ok(diagnostics) if diagnostics.is_empty() => println!("✓ {}", file.display()),
+            Ok(diagnostics) => {
+                for diagnostic in &diagnostics {
+                    eprintln!("{}", diagnostic);
+                }
+                error_count += diagnostics.len();
+            }
+            Err(e) => {
+                eprintln!("{}: error: {}", file.display(), e);
+                error_count += 1;
+            }
+        }
+    }
+
+    if error_count > 0 {
+        eprintln!("{} error(s) found", error_count);
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Rebuild the transcript index from scratch
+fn handle_rebuild_index() -> io::Result<()> {
+    match DataLoader::new().rebuild_index() {
+        Ok((files, entries)) => {
+            println!(
+                "✓ Indexed {} usage entries from {} transcript files",
+                entries, files
+            );
+            println!("  Index saved to: {:?}", TranscriptIndex::default_path());
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Error: Failed to rebuild transcript index: {}", e);
+            Err(e)
+        }
+    }
+}
+
+/// Download the pricing table into the on-disk pricing cache
+fn handle_refresh_pricing(cli: &Cli) -> io::Result<()> {
+    let config = build_config_loader(cli, std::env::current_dir().ok()).load();
+    let path = PricingCache::default_path();
+    match pricing_cache::refresh(&config.pricing, &path) {
+        Ok(models) => {
+            println!(
+                "✓ Fetched pricing for {} models from {}",
+                models.len(),
+                config.pricing.url()
+            );
+            println!("  Cache saved to: {:?}", path);
+            Ok(())
+        }
+        Err(e) => {
+            eprintln!("Error: Failed to refresh pricing: {}", e);
+            Err(io::Error::other(e.to_string()))
+        }
+    }
+}
+
+/// Print the effective pricing table with the source of every price
+fn handle_show_pricing(cli: &Cli) {
+    let config = build_config_loader(cli, std::env::current_dir().ok()).load();
+    let loaded = pricing_cache::load(&config.pricing, &PricingCache::default_path());
+    let table = PricingTable::build(loaded, &config.pricing);
+
+    for (alias, target) in table.unresolved_aliases() {
+        eprintln!(
+            "Warning: alias `{}` -> `{}` matches no priced model",
+            alias, target
+        );
+    }
+
+    if let Some(text) = PricingOutput::new(&table, config.pricing.multiplier()).render(cli.format) {
+        println!("{}", text);
+        return;
+    }
+
+    let entries = table.entries();
+    println!("Prices in USD per million tokens");
+    if config.pricing.multiplier() != 1.0 {
+        println!(
+            "Fetched and built-in prices scaled by {}",
+            config.pricing.multiplier()
+        );
+    }
+    println!();
+
+    let name_width = entries
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max(5);
+    println!(
+        "{:<name_width$}  {:>9}  {:>9}  {:>11}  {:>10}  SOURCE",
+        "MODEL", "INPUT", "OUTPUT", "CACHE WRITE", "CACHE READ"
+    );
+    let per_million = |per_1k: f64| per_1k * 1000.0;
+    for (name, pricing, source) in entries {
+        println!(
+            "{:<name_width$}  {:>9.4}  {:>9.4}  {:>11.4}  {:>10.4}  {}",
+            name,
+            per_million(pricing.input_cost_per_1k),
+            per_million(pricing.output_cost_per_1k),
+            per_million(pricing.cache_creation_cost_per_1k),
+            per_million(pricing.cache_read_cost_per_1k),
+            source
+        );
+        for tier in &pricing.tiers {
+            println!(
+                "{:<name_width$}  {:>9.4}  {:>9.4}  {:>11.4}  {:>10.4}",
+                format!("  > {}k tokens", tier.above_tokens / 1000),
+                per_million(tier.input_cost_per_1k.unwrap_or(pricing.input_cost_per_1k)),
+                per_million(
+                    tier.output_cost_per_1k
+                        .unwrap_or(pricing.output_cost_per_1k)
+                ),
+                per_million(
+                    tier.cache_creation_cost_per_1k
+                        .unwrap_or(pricing.cache_creation_cost_per_1k)
+                ),
+                per_million(
+                    tier.cache_read_cost_per_1k
+                        .unwrap_or(pricing.cache_read_cost_per_1k)
+                ),
+            );
+        }
+    }
+}
+
+/// Explain which pricing entry `model` resolves to
+fn handle_pricing_resolve(cli: &Cli, model: &str) {
+    const SHOWN_CANDIDATES: usize = 10;
+
+    let config = build_config_loader(cli, std::env::current_dir().ok()).load();
+    let loaded = pricing_cache::load(&config.pricing, &PricingCache::default_path());
+    let table = PricingTable::build(loaded, &config.pricing);
+    let resolution = resolver::resolve(table.models(), model);
+
+    if let Some(text) = ResolutionOutput::new(&table, &resolution).render(cli.format) {
+        println!("{}", text);
+        if resolution.chosen().is_none() {
+            std::process::exit(1);
+        }
+        return;
+    }
+
+    println!("Model:       {}", resolution.model);
+    println!("Normalized:  {}", resolution.normalized);
+    let Some(chosen) = resolution.chosen() else {
+        println!("Chosen:      none (usage of this model is not costed)");
+        std::process::exit(1);
+    };
+
+    let pricing = chosen.entry;
+    println!("Chosen:      {} ({})", chosen.key, chosen.kind);
+    println!(
+        "Price:       ${:.2} input, ${:.2} output, ${:.2} cache write, ${:.2} cache read per 1M tokens",
+        pricing.input_cost_per_1k * 1000.0,
+        pricing.output_cost_per_1k * 1000.0,
+        pricing.cache_creation_cost_per_1k * 1000.0,
+        pricing.cache_read_cost_per_1k * 1000.0
+    );
+    if let Some(source) = table.source(chosen.key) {
+        println!("Source:      {}", source);
+    }
+
+    if resolution.candidates.len() > 1 {
+        println!();
+        println!("Candidates (best first):");
+        let width = resolution
+            .candidates
+            .iter()
+            .take(SHOWN_CANDIDATES)
+            .map(|c| c.key.len())
+            .max()
+            .unwrap_or(0);
+        for (i, candidate) in resolution
+            .candidates
+            .iter()
+            .take(SHOWN_CANDIDATES)
+            .enumerate()
+        {
+            println!(
+                "  {:>2}. {:<width$}  {}",
+                i + 1,
+                candidate.key,
+                candidate.kind
+            );
+        }
+        if resolution.candidates.len() > SHOWN_CANDIDATES {
+            println!(
+                "  … and {} more",
+                resolution.candidates.len() - SHOWN_CANDIDATES
+            );
+        }
+    }
+}
+
+/// Print a usage report over every transcript on disk
+fn handle_report(cli: &Cli, kind: &ReportCommand) {
+    let mut loader = build_config_loader(cli, std::env::current_dir().ok());
+    if let ReportCommand::Weekly(WeeklyArgs {
+        week_start: Some(day),
+        ..
+    }) = kind
+    {
+        loader = loader.with_cli_override("report.week_start", day.name(), "--week-start");
+    }
+    let config = loader.load();
+    let loaded = pricing_cache::load(&config.pricing, &PricingCache::default_path());
+    let pricing = PricingTable::build(loaded, &config.pricing).into_models();
+    let args = kind.args();
+    let options = args.options(config.report.week_start());
+    if let ReportCommand::Blocks(blocks) = kind {
+        if blocks.live {
+            if cli.format != OutputFormat::Table {
+                eprintln!("Error: --live only supports --format table");
+                std::process::exit(2);
+            }
+            watch_active_block(&pricing, blocks, &options);
+            return;
+        }
+    }
+
+    let entries = report::load_entries(&DataLoader::new(), &pricing);
+    let (format, breakdown) = (cli.format, args.breakdown);
+    match kind {
+        ReportCommand::Daily(_) => {
+            let report = report::daily(&entries, &options);
+            print_report(
+                format,
+                breakdown,
+                || report.table(breakdown),
+                || report.output(&options),
+            );
+        }
+        ReportCommand::Weekly(_) => {
+            let report = report::weekly(&entries, &options);
+            print_report(
+                format,
+                breakdown,
+                || report.table(breakdown),
+                || report.output(&options),
+            );
+        }
+        ReportCommand::Monthly(_) => {
+            let report = report::monthly(&entries, &options);
+            print_report(
+                format,
+                breakdown,
+                || report.table(breakdown),
+                || report.output(&options),
+            );
+        }
+        ReportCommand::Sessions(sessions) => {
+            let mut report = report::sessions(&entries, &options, sessions.project.as_deref());
+            report.sort(sessions.sort, sessions.reverse);
+            print_report(
+                format,
+                breakdown,
+                || report.table(&options, breakdown),
+                || report.output(&options),
+            );
+        }
+        ReportCommand::Blocks(blocks) => {
+            let report = report::blocks(&entries, &options, blocks.days);
+            print_report(
+                format,
+                breakdown,
+                || report.table(&options, breakdown),
+                || report.output(&options),
+            );
+        }
+    }
+}
+
+/// Print a report as a table, or in the machine-readable `format`
+fn print_report<K: RowKey>(
+    format: OutputFormat,
+    breakdown: bool,
+    table: impl FnOnce() -> Table,
+    output: impl FnOnce() -> ReportOutput<K>,
+) {
+    if let Some(text) = output().render(format, breakdown) {
+        println!("{}", text);
+        return;
+    }
+
+    let table = table();
+    if table.is_empty() {
+        println!("No usage data found.");
+    } else {
+        println!("{}", table.render());
+    }
+}
+
+/// Redraw the active billing block every `--interval` seconds until interrupted
+fn watch_active_block(
+    pricing: &HashMap<String, ModelPricing>,
+    args: &BlocksArgs,
+    options: &ReportOptions,
+) {
+    let mut drawn = 0;
+    loop {
+        let entries = report::load_entries(&DataLoader::new(), pricing);
+        let blocks = report::blocks(&entries, options, Some(1));
+
+        let mut text = format!(
+            "Active block · updated {} · Ctrl-C to quit\n",
+            Local::now().format("%H:%M:%S")
+        );
+        match blocks.active() {
+            Some(row) => {
+                let active = report::BlocksReport {
+                    blocks: vec![row.clone()],
+                    totals: row.usage.totals.clone(),
+                };
+                text.push_str(&active.table(options, args.report.breakdown).render());
+                let usage = report::provider_usage(&entries, options);
+                if let Some(rate) = calculate_burn_rate(&row.block, &usage) {
+                    text.push_str(&format!(
+                        "\nProjected: {} at {}/h",
+                        report::format_cost(project_block_cost(&row.block, &rate)),
+                        report::format_cost(rate.cost_per_hour)
+                    ));
+                }
+            }
+            None => text.push_str("No active block."),
+        }
+
+        // Move back to the first row of the previous frame and clear below it
+        let mut stdout = io::stdout().lock();
+        if drawn > 0 {
+            let _ = write!(stdout, "\x1b[{}A\x1b[J", drawn);
+        }
+        if writeln!(stdout, "{}", text)
+            .and_then(|_| stdout.flush())
+            .is_err()
+        {
+            return;
+        }
+        // Lines wider than the terminal wrap and take several rows
+        drawn = match terminal_columns() {
+            Some(columns) => rows(&text, columns),
+            None => text.lines().count(),
+        };
+        drop(stdout);
+
+        std::thread::sleep(std::time::Duration::from_secs(args.interval));
+    }
+}
+
 /// Handle block start time management CLI commands
 fn handle_block_management(cli: &Cli) -> io::Result<()> {
     let mut manager = match BlockOverrideManager::new() {
@@ -173,14 +629,25 @@ fn handle_block_management(cli: &Cli) -> io::Result<()> {
 
                 match manager.set_override(today, start_time, "manual".to_string(), notes) {
                     Ok(()) => {
-                        let local_start_time = start_time.with_timezone(&Local);
-                        println!(
-                            "✓ Block start time set to {} ({} local) for {}",
-                            start_time.format("%Y-%m-%d %H:%M UTC"),
-                            local_start_time.format("%H:%M %Z"),
-                            today.format("%Y-%m-%d")
-                        );
-                        println!("  Configuration saved to: {:?}", manager.get_config_path());
+                        let change = BlockStartChange {
+                            action: "set",
+                            date: today,
+                            start_time: Some(start_time),
+                            changed: true,
+                            config_path: manager.get_config_path().clone(),
+                        };
+                        if let Some(text) = change.render(cli.format) {
+                            println!("{}", text);
+                        } else {
+                            let local_start_time = start_time.with_timezone(&Local);
+                            println!(
+                                "✓ Block start time set to {} ({} local) for {}",
+                                start_time.format("%Y-%m-%d %H:%M UTC"),
+                                local_start_time.format("%H:%M %Z"),
+                                today.format("%Y-%m-%d")
+                            );
+                            println!("  Configuration saved to: {:?}", manager.get_config_path());
+                        }
                     }
                     Err(e) => {
                         eprintln!("Error: Failed to set block start time: {}", e);
@@ -206,6 +673,18 @@ fn handle_block_management(cli: &Cli) -> io::Result<()> {
     // Handle clear block start
     if cli.clear_block_start {
         match manager.clear_override(today) {
+            Ok(cleared) if cli.format != OutputFormat::Table => {
+                let change = BlockStartChange {
+                    action: "clear",
+                    date: today,
+                    start_time: None,
+                    changed: cleared,
+                    config_path: manager.get_config_path().clone(),
+                };
+                if let Some(text) = change.render(cli.format) {
+                    println!("{}", text);
+                }
+            }
             Ok(true) => {
                 println!(
                     "✓ Block start time override cleared for {}",
@@ -227,6 +706,11 @@ fn handle_block_management(cli: &Cli) -> io::Result<()> {
 
     // Handle show block status
     if cli.show_block_status {
+        if let Some(text) = manager.status(today).render(cli.format) {
+            println!("{}", text);
+            return Ok(());
+        }
+
         println!("Block Override Status:");
         println!("  Configuration file: {:?}", manager.get_config_path());
         println!("  Total overrides: {}", manager.override_count());
@@ -0,0 +1,188 @@
+//! Machine-readable output of reports and status commands
+//!
+//! JSON documents and NDJSON lines carry `schema_version` and `kind`; the
+//! fields of every kind are described in `docs/OUTPUT_FORMATS.md`.
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+use std::borrow::Cow;
+use std::fmt;
+use std::str::FromStr;
+
+/// Version of the JSON layout, bumped when a field is renamed, removed or
+/// changes meaning. Fields may be added without a bump.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// `--format` of reports and status commands
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text
+    #[default]
+    Table,
+    /// One pretty-printed JSON document
+    Json,
+    /// One compact JSON object per line
+    Ndjson,
+    /// Comma-separated values with a header row
+    Csv,
+}
+
+impl OutputFormat {
+    pub const NAMES: &'static [&'static str] = &["table", "json", "ndjson", "csv"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "table" | "text" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" | "jsonl" => Ok(OutputFormat::Ndjson),
+            "csv" => Ok(OutputFormat::Csv),
+            _ => Err(format!(
+                "invalid format `{}` (expected {})",
+                name,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(Self::NAMES[*self as usize])
+    }
+}
+
+/// Fields shared by every JSON document and NDJSON line
+#[derive(Serialize)]
+struct Envelope<'a, T> {
+    schema_version: u32,
+    kind: &'a str,
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    record_type: Option<&'a str>,
+    #[serde(flatten)]
+    body: &'a T,
+}
+
+/// Pretty-printed JSON document of `kind`; `body` must serialize as an object
+pub fn json<T: Serialize>(kind: &str, body: &T) -> String {
+    let envelope = Envelope {
+        schema_version: SCHEMA_VERSION,
+        kind,
+        record_type: None,
+        body,
+    };
+    serde_json::to_string_pretty(&envelope).expect("output records serialize to JSON")
+}
+
+/// One NDJSON line of `kind`, without the trailing newline
+///
+/// `record_type` tells the lines of one output apart, e.g. `row` and `totals`.
+pub fn ndjson_line<T: Serialize>(kind: &str, record_type: Option<&str>, record: &T) -> String {
+    let envelope = Envelope {
+        schema_version: SCHEMA_VERSION,
+        kind,
+        record_type,
+        body: record,
+    };
+    serde_json::to_string(&envelope).expect("output records serialize to JSON")
+}
+
+/// Text of a single-document output in `format`, or `None` for [`OutputFormat::Table`]
+///
+/// NDJSON is the JSON document on one line; CSV comes from `csv`.
+pub fn render_document<T: Serialize>(
+    format: OutputFormat,
+    kind: &str,
+    body: &T,
+    csv: impl FnOnce() -> Csv,
+) -> Option<String> {
+    match format {
+        OutputFormat::Table => None,
+        OutputFormat::Json => Some(json(kind, body)),
+        OutputFormat::Ndjson => Some(ndjson_line(kind, None, body)),
+        OutputFormat::Csv => Some(csv().render()),
+    }
+}
+
+/// CSV text with a header row, quoted as in RFC 4180
+#[derive(Debug, Clone, Default)]
+pub struct Csv {
+    lines: Vec<String>,
+}
+
+impl Csv {
+    pub fn new<S: AsRef<str>>(header: impl IntoIterator<Item = S>) -> Self {
+        let mut csv = Self::default();
+        csv.push(header);
+        csv
+    }
+
+    pub fn push<S: AsRef<str>>(&mut self, row: impl IntoIterator<Item = S>) {
+        let fields: Vec<String> = row
+            .into_iter()
+            .map(|field| escape(field.as_ref()).into_owned())
+            .collect();
+        self.lines.push(fields.join(","));
+    }
+
+    pub fn render(&self) -> String {
+        self.lines.join("\n")
+    }
+}
+
+fn escape(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\n', '\r']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
+
+/// Optional value as a CSV field; `None` is left empty
+pub fn csv_field<T: ToString>(value: Option<T>) -> String {
+    value.map(|value| value.to_string()).unwrap_or_default()
+}
+
+/// Timestamps in CSV output are RFC 3339 in UTC, as serde writes them in JSON
+pub fn timestamp(time: DateTime<Utc>) -> String {
+    time.to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Dollar amounts in machine output are rounded to a millionth of a dollar
+pub fn round_cost(cost: f64) -> f64 {
+    (cost * 1e6).round() / 1e6
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Serialize)]
+    struct Sample {
+        name: &'static str,
+    }
+
+    #[test]
+    fn test_envelopes_and_csv() {
+        assert_eq!(
+            ndjson_line("sample", Some("row"), &Sample { name: "a" }),
+            r#"{"schema_version":1,"kind":"sample","type":"row","name":"a"}"#
+        );
+        assert!(json("sample", &Sample { name: "a" }).starts_with("{\n  \"schema_version\": 1,"));
+
+        let mut csv = Csv::new(["name", "note"]);
+        csv.push(["a,b", "say \"hi\""]);
+        csv.push(["plain", ""]);
+        assert_eq!(
+            csv.render(),
+            "name,note\n\"a,b\",\"say \"\"hi\"\"\"\nplain,"
+        );
+
+        assert_eq!("JSONL".parse(), Ok(OutputFormat::Ndjson));
+        assert!("xml".parse::<OutputFormat>().is_err());
+        assert_eq!(round_cost(0.060000000000000005), 0.06);
+    }
+}
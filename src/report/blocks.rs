@@ -0,0 +1,284 @@
+//! 5-hour billing block history (`ccline report blocks`)
+
+use super::output::{ReportOutput, Row, RowKey};
+use super::table::UsageTable;
+use super::{aggregate, Bucket, ReportOptions, Table, Totals};
+use crate::billing::block::{
+    get_recent_blocks, identify_session_blocks_with_manager, identify_session_blocks_with_overrides,
+};
+use crate::billing::calculator::format_remaining_time;
+use crate::billing::{BillingBlock, UsageEntry};
+use crate::config::BlockOverrideManager;
+use crate::output::timestamp;
+use crate::utils::transcript_index::SourcedEntry;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+/// A billing block with the usage of the entries that fall into it
+///
+/// Gap blocks, the idle stretches between blocks, have no usage.
+#[derive(Debug, Clone)]
+pub struct BlockRow {
+    pub block: BillingBlock,
+    pub usage: Bucket<DateTime<Utc>>,
+}
+
+/// Billing blocks in start order, gaps included
+#[derive(Debug, Clone)]
+pub struct BlocksReport {
+    pub blocks: Vec<BlockRow>,
+    pub totals: Totals,
+}
+
+/// Billing blocks as the statusline computes them, honouring block start overrides
+///
+/// Blocks are built from the entries of `options.provider` and kept when they
+/// start within `options.since` and `options.until`; with `days`, only blocks
+/// that started in the last `days` days are kept.
+pub fn blocks(
+    entries: &[SourcedEntry],
+    options: &ReportOptions,
+    days: Option<i64>,
+) -> BlocksReport {
+    let all = identify_session_blocks_with_overrides(&provider_usage(entries, options));
+    select(entries, options, days, &all)
+}
+
+/// [`blocks`] with the block start overrides of `overrides` instead of the
+/// user's
+pub fn blocks_with_overrides(
+    entries: &[SourcedEntry],
+    options: &ReportOptions,
+    days: Option<i64>,
+    overrides: &BlockOverrideManager,
+) -> BlocksReport {
+    let all = identify_session_blocks_with_manager(&provider_usage(entries, options), overrides);
+    select(entries, options, days, &all)
+}
+
+/// Usage of the entries of `options.provider`, whatever their date
+pub fn provider_usage(entries: &[SourcedEntry], options: &ReportOptions) -> Vec<UsageEntry> {
+    entries
+        .iter()
+        .filter(|entry| options.includes_provider(entry))
+        .map(|entry| entry.usage.clone())
+        .collect()
+}
+
+fn select(
+    entries: &[SourcedEntry],
+    options: &ReportOptions,
+    days: Option<i64>,
+    all: &[BillingBlock],
+) -> BlocksReport {
+    let recent = match days {
+        Some(days) => get_recent_blocks(all, days),
+        None => all.iter().collect(),
+    };
+    let selected = recent.into_iter().filter(|block| {
+        let date = options.timezone.date(block.start_time);
+        options.since.is_none_or(|since| date >= since)
+            && options.until.is_none_or(|until| date <= until)
+    });
+    from_blocks(entries, selected, options)
+}
+
+/// Attach to each of `blocks` the usage of the entries between its start and end
+pub fn from_blocks<'a>(
+    entries: &[SourcedEntry],
+    blocks: impl IntoIterator<Item = &'a BillingBlock>,
+    options: &ReportOptions,
+) -> BlocksReport {
+    let blocks = close_gaps(blocks.into_iter().collect());
+    let busy: Vec<&BillingBlock> = blocks.iter().filter(|b| !b.is_gap).collect();
+
+    // Blocks are selected by start day, so entries are only filtered by provider
+    let unbounded = ReportOptions {
+        since: None,
+        until: None,
+        ..options.clone()
+    };
+    let mut usage: BTreeMap<_, _> = aggregate(entries, &unbounded, |entry| {
+        let timestamp = entry.usage.timestamp;
+        busy.iter()
+            .rev()
+            .find(|block| block.start_time <= timestamp)
+            .filter(|block| timestamp <= block.end_time)
+            .map(|block| block.start_time)
+    })
+    .into_iter()
+    .filter_map(|bucket| bucket.key.map(|key| (key, bucket)))
+    .collect();
+
+    let mut totals = Totals::default();
+    let blocks = blocks
+        .into_iter()
+        .map(|block| {
+            // A gap can start on the same hour as the block after it
+            let found = if block.is_gap {
+                None
+            } else {
+                usage.remove(&block.start_time)
+            };
+            let bucket = match found {
+                Some(bucket) => Bucket {
+                    key: block.start_time,
+                    totals: bucket.totals,
+                    models: bucket.models,
+                    providers: bucket.providers,
+                },
+                None => Bucket::new(block.start_time),
+            };
+            totals.merge(&bucket.totals);
+            BlockRow {
+                block,
+                usage: bucket,
+            }
+        })
+        .collect();
+    BlocksReport { blocks, totals }
+}
+
+/// Stretch each gap from the end of the block before it to the start of the
+/// block after it, so adjacent rows share their boundaries
+///
+/// Gaps are measured from entry times, the last entry plus the block length up
+/// to the next entry, while blocks start on the hour, so a raw gap overlaps or
+/// falls short of its neighbours. A gap left empty is dropped.
+fn close_gaps(blocks: Vec<&BillingBlock>) -> Vec<BillingBlock> {
+    let mut closed: Vec<BillingBlock> = Vec::with_capacity(blocks.len());
+    for (i, block) in blocks.iter().enumerate() {
+        let mut block = (*block).clone();
+        if block.is_gap {
+            if let Some(previous) = closed.last().filter(|b| !b.is_gap) {
+                block.start_time = previous.end_time;
+            }
+            if let Some(next) = blocks.get(i + 1).filter(|b| !b.is_gap) {
+                block.end_time = next.start_time;
+            }
+            if block.end_time <= block.start_time {
+                continue;
+            }
+        }
+        closed.push(block);
+    }
+    closed
+}
+
+impl BlocksReport {
+    /// The block still running, if any
+    pub fn active(&self) -> Option<&BlockRow> {
+        self.blocks.iter().find(|row| row.block.is_active)
+    }
+
+    /// Times are shown in the report timezone of `options`; with `breakdown`,
+    /// each block is followed by per-provider and per-model rows
+    pub fn table(&self, options: &ReportOptions, breakdown: bool) -> Table {
+        let mut table = UsageTable::new("Block", &["Sessions", "Source", "Status"], breakdown);
+        for row in &self.blocks {
+            let block = &row.block;
+            let (sessions, source) = if block.is_gap {
+                (String::new(), "—".to_string())
+            } else {
+                (
+                    block.session_count.to_string(),
+                    block.start_time_source.name().to_string(),
+                )
+            };
+            table.push(
+                span_label(block, options),
+                &row.usage,
+                vec![sessions, source, status(block)],
+            );
+        }
+        table.finish(&self.totals)
+    }
+
+    pub fn output(&self, options: &ReportOptions) -> ReportOutput<BlockKey> {
+        let rows = self
+            .blocks
+            .iter()
+            .map(|row| {
+                let block = &row.block;
+                let key = BlockKey {
+                    start: block.start_time,
+                    end: block.end_time,
+                    gap: block.is_gap,
+                    active: block.is_active,
+                    start_source: (!block.is_gap).then(|| block.start_time_source.name()),
+                    session_count: block.session_count,
+                    remaining_minutes: block.remaining_minutes,
+                };
+                Row::new(key, &row.usage)
+            })
+            .collect();
+        ReportOutput::new("blocks", options, rows, &self.totals)
+    }
+}
+
+/// Key of a block row in machine-readable output
+#[derive(Debug, Clone, Serialize)]
+pub struct BlockKey {
+    pub start: DateTime<Utc>,
+    pub end: DateTime<Utc>,
+    pub gap: bool,
+    pub active: bool,
+    /// `auto`, `manual` or `fixed`; `null` for gaps
+    pub start_source: Option<&'static str>,
+    pub session_count: usize,
+    pub remaining_minutes: i64,
+}
+
+impl RowKey for BlockKey {
+    const COLUMNS: &'static [&'static str] = &[
+        "start",
+        "end",
+        "gap",
+        "active",
+        "start_source",
+        "session_count",
+        "remaining_minutes",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            timestamp(self.start),
+            timestamp(self.end),
+            self.gap.to_string(),
+            self.active.to_string(),
+            self.start_source.unwrap_or_default().to_string(),
+            self.session_count.to_string(),
+            self.remaining_minutes.to_string(),
+        ]
+    }
+}
+
+/// `2025-10-01 10:00 – 15:00`, with the end date when the block crosses midnight
+fn span_label(block: &BillingBlock, options: &ReportOptions) -> String {
+    let start = options.timezone.naive(block.start_time);
+    let end = options.timezone.naive(block.end_time);
+    let end_format = if end.date() == start.date() {
+        "%H:%M"
+    } else {
+        "%m-%d %H:%M"
+    };
+    format!(
+        "{} – {}",
+        start.format("%Y-%m-%d %H:%M"),
+        end.format(end_format)
+    )
+}
+
+fn status(block: &BillingBlock) -> String {
+    if block.is_gap {
+        "gap".to_string()
+    } else if block.is_active {
+        format!(
+            "active, {} left",
+            format_remaining_time(block.remaining_minutes)
+        )
+    } else {
+        String::new()
+    }
+}
@@ -0,0 +1,57 @@
+//! Per-day usage report (`ccline report daily`)
+
+use super::output::{ReportOutput, Row, RowKey};
+use super::table::UsageTable;
+use super::{aggregate, grand_total, Bucket, ReportOptions, Table, Totals};
+use crate::utils::transcript_index::SourcedEntry;
+use chrono::NaiveDate;
+use serde::Serialize;
+
+/// Usage per calendar day of the report timezone, oldest first
+#[derive(Debug, Clone)]
+pub struct DailyReport {
+    pub days: Vec<Bucket<NaiveDate>>,
+    pub totals: Totals,
+}
+
+pub fn daily(entries: &[SourcedEntry], options: &ReportOptions) -> DailyReport {
+    let days = aggregate(entries, options, |entry| {
+        options.timezone.date(entry.usage.timestamp)
+    });
+    let totals = grand_total(&days);
+    DailyReport { days, totals }
+}
+
+impl DailyReport {
+    /// With `breakdown`, each day is followed by per-provider and per-model rows
+    pub fn table(&self, breakdown: bool) -> Table {
+        let mut table = UsageTable::new("Date", &[], breakdown);
+        for day in &self.days {
+            table.push(day.key.format("%Y-%m-%d").to_string(), day, Vec::new());
+        }
+        table.finish(&self.totals)
+    }
+
+    pub fn output(&self, options: &ReportOptions) -> ReportOutput<DayKey> {
+        let rows = self
+            .days
+            .iter()
+            .map(|day| Row::new(DayKey { date: day.key }, day))
+            .collect();
+        ReportOutput::new("daily", options, rows, &self.totals)
+    }
+}
+
+/// Key of a daily row in machine-readable output
+#[derive(Debug, Clone, Serialize)]
+pub struct DayKey {
+    pub date: NaiveDate,
+}
+
+impl RowKey for DayKey {
+    const COLUMNS: &'static [&'static str] = &["date"];
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.date.to_string()]
+    }
+}
@@ -0,0 +1,489 @@
+//! Usage reports built from every transcript on disk
+
+pub mod blocks;
+pub mod daily;
+pub mod monthly;
+pub mod output;
+pub mod sessions;
+pub mod table;
+pub mod weekly;
+
+use crate::billing::calculator::fill_costs;
+use crate::billing::resolver::normalize;
+use crate::billing::{ModelPricing, UsageEntry};
+use crate::config::ProviderKind;
+use crate::utils::transcript_index::SourcedEntry;
+use crate::utils::DataLoader;
+use chrono::{DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, Utc, Weekday};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt;
+use std::str::FromStr;
+
+pub use blocks::{blocks, blocks_with_overrides, provider_usage, BlockRow, BlocksReport};
+pub use daily::{daily, DailyReport};
+pub use monthly::{monthly, Change, MonthlyReport};
+pub use sessions::{sessions, Session, SessionSort, SessionsReport};
+pub use table::Table;
+pub use weekly::{weekly, WeeklyReport};
+
+/// Defaults for `ccline report`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ReportConfig {
+    /// First day of the week in weekly reports (default Monday, as in ISO weeks)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub week_start: Option<WeekStart>,
+}
+
+impl ReportConfig {
+    pub fn week_start(&self) -> WeekStart {
+        self.week_start.unwrap_or_default()
+    }
+}
+
+/// Day a report week begins on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Tuesday,
+    Wednesday,
+    Thursday,
+    Friday,
+    Saturday,
+    Sunday,
+}
+
+impl WeekStart {
+    /// Names accepted in configuration files
+    pub const NAMES: &'static [&'static str] = &[
+        "monday",
+        "tuesday",
+        "wednesday",
+        "thursday",
+        "friday",
+        "saturday",
+        "sunday",
+    ];
+
+    pub fn weekday(self) -> Weekday {
+        match self {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Tuesday => Weekday::Tue,
+            WeekStart::Wednesday => Weekday::Wed,
+            WeekStart::Thursday => Weekday::Thu,
+            WeekStart::Friday => Weekday::Fri,
+            WeekStart::Saturday => Weekday::Sat,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        Self::NAMES[self.weekday().num_days_from_monday() as usize]
+    }
+}
+
+impl FromStr for WeekStart {
+    type Err = String;
+
+    /// Full or abbreviated day name, e.g. `sunday` or `sun`
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        let weekday = name
+            .trim()
+            .parse::<Weekday>()
+            .map_err(|_| format!("invalid day `{}` (expected monday … sunday)", name))?;
+        Ok(match weekday {
+            Weekday::Mon => WeekStart::Monday,
+            Weekday::Tue => WeekStart::Tuesday,
+            Weekday::Wed => WeekStart::Wednesday,
+            Weekday::Thu => WeekStart::Thursday,
+            Weekday::Fri => WeekStart::Friday,
+            Weekday::Sat => WeekStart::Saturday,
+            Weekday::Sun => WeekStart::Sunday,
+        })
+    }
+}
+
+/// Timezone used to assign entries to days
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ReportTimezone {
+    /// The system timezone
+    #[default]
+    Local,
+    Utc,
+    /// A fixed offset from UTC, e.g. `+09:00`
+    Fixed(FixedOffset),
+}
+
+impl ReportTimezone {
+    /// Wall-clock time of `timestamp` in this timezone
+    pub fn naive(&self, timestamp: DateTime<Utc>) -> NaiveDateTime {
+        match self {
+            ReportTimezone::Local => timestamp.with_timezone(&Local).naive_local(),
+            ReportTimezone::Utc => timestamp.naive_utc(),
+            ReportTimezone::Fixed(offset) => timestamp.with_timezone(offset).naive_local(),
+        }
+    }
+
+    /// Calendar day of `timestamp` in this timezone
+    pub fn date(&self, timestamp: DateTime<Utc>) -> NaiveDate {
+        self.naive(timestamp).date()
+    }
+}
+
+impl FromStr for ReportTimezone {
+    type Err = String;
+
+    /// `local`, `utc` or an offset such as `+09:00`, `-0530` or `+2`
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        match spec.to_ascii_lowercase().as_str() {
+            "local" => return Ok(ReportTimezone::Local),
+            "utc" | "z" => return Ok(ReportTimezone::Utc),
+            _ => {}
+        }
+
+        let invalid = || {
+            format!(
+                "invalid timezone `{}` (expected local, utc or an offset such as +09:00)",
+                spec
+            )
+        };
+        let (sign, rest) = match spec.split_at_checked(1) {
+            Some(("+", rest)) => (1, rest),
+            Some(("-", rest)) => (-1, rest),
+            _ => return Err(invalid()),
+        };
+        let digits: String = rest.chars().filter(|c| *c != ':').collect();
+        if digits.is_empty() || digits.len() > 4 || !digits.chars().all(|c| c.is_ascii_digit()) {
+            return Err(invalid());
+        }
+        let (hours, minutes) = if digits.len() <= 2 {
+            (digits.parse::<i32>().map_err(|_| invalid())?, 0)
+        } else {
+            let split = digits.len() - 2;
+            (
+                digits[..split].parse::<i32>().map_err(|_| invalid())?,
+                digits[split..].parse::<i32>().map_err(|_| invalid())?,
+            )
+        };
+        if minutes >= 60 {
+            return Err(invalid());
+        }
+        FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+            .map(ReportTimezone::Fixed)
+            .ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for ReportTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReportTimezone::Local => write!(f, "local"),
+            ReportTimezone::Utc => write!(f, "utc"),
+            ReportTimezone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+/// Which entries a report covers and how they are bucketed
+#[derive(Debug, Clone, Default)]
+pub struct ReportOptions {
+    /// First day to include, inclusive
+    pub since: Option<NaiveDate>,
+    /// Last day to include, inclusive
+    pub until: Option<NaiveDate>,
+    pub timezone: ReportTimezone,
+    /// Only entries of this provider; entries of unknown provider are then left out
+    pub provider: Option<ProviderKind>,
+    /// First day of weeks in weekly reports
+    pub week_start: WeekStart,
+}
+
+impl ReportOptions {
+    pub fn includes(&self, entry: &SourcedEntry) -> bool {
+        let date = self.timezone.date(entry.usage.timestamp);
+        self.since.is_none_or(|since| date >= since)
+            && self.until.is_none_or(|until| date <= until)
+            && self.includes_provider(entry)
+    }
+
+    /// Whether `entry` belongs to the selected provider, ignoring dates
+    pub fn includes_provider(&self, entry: &SourcedEntry) -> bool {
+        self.provider
+            .is_none_or(|provider| entry.provider == Some(provider))
+    }
+}
+
+/// Token counts and cost summed over a set of entries
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Totals {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    /// USD of the priced entries only; see `unpriced_entries`
+    pub cost: f64,
+    pub entries: usize,
+    /// Entries whose model has no price, left out of `cost`
+    pub unpriced_entries: usize,
+    /// Normalized names of the models without a price
+    pub unpriced_models: BTreeSet<String>,
+}
+
+impl Totals {
+    pub fn add(&mut self, entry: &UsageEntry) {
+        self.input_tokens += entry.input_tokens as u64;
+        self.output_tokens += entry.output_tokens as u64;
+        self.cache_creation_tokens += entry.cache_creation_tokens as u64;
+        self.cache_read_tokens += entry.cache_read_tokens as u64;
+        match entry.cost {
+            Some(cost) => self.cost += cost,
+            None => {
+                self.unpriced_entries += 1;
+                self.unpriced_models.insert(model_name(&entry.model));
+            }
+        }
+        self.entries += 1;
+    }
+
+    pub fn merge(&mut self, other: &Totals) {
+        self.input_tokens += other.input_tokens;
+        self.output_tokens += other.output_tokens;
+        self.cache_creation_tokens += other.cache_creation_tokens;
+        self.cache_read_tokens += other.cache_read_tokens;
+        self.cost += other.cost;
+        self.entries += other.entries;
+        self.unpriced_entries += other.unpriced_entries;
+        self.unpriced_models
+            .extend(other.unpriced_models.iter().cloned());
+    }
+
+    /// Whether `cost` leaves out entries of unpriced models
+    pub fn is_partial(&self) -> bool {
+        self.unpriced_entries > 0
+    }
+
+    pub fn total_tokens(&self) -> u64 {
+        self.input_tokens + self.output_tokens + self.cache_creation_tokens + self.cache_read_tokens
+    }
+}
+
+/// Usage of one report row (a day, a session, ...) with a per-model breakdown
+#[derive(Debug, Clone, PartialEq)]
+pub struct Bucket<K> {
+    pub key: K,
+    pub totals: Totals,
+    /// Keyed by normalized model name
+    pub models: BTreeMap<String, Totals>,
+    /// Keyed by provider name, then by normalized model name
+    pub providers: BTreeMap<String, BTreeMap<String, Totals>>,
+}
+
+impl<K> Bucket<K> {
+    pub fn new(key: K) -> Self {
+        Self {
+            key,
+            totals: Totals::default(),
+            models: BTreeMap::new(),
+            providers: BTreeMap::new(),
+        }
+    }
+}
+
+/// Group the entries `options` includes by `key`, in key order
+pub fn aggregate<'a, K: Ord + Clone>(
+    entries: impl IntoIterator<Item = &'a SourcedEntry>,
+    options: &ReportOptions,
+    key: impl Fn(&SourcedEntry) -> K,
+) -> Vec<Bucket<K>> {
+    let mut buckets: BTreeMap<K, Bucket<K>> = BTreeMap::new();
+    for entry in entries.into_iter().filter(|entry| options.includes(entry)) {
+        let key = key(entry);
+        let bucket = buckets
+            .entry(key.clone())
+            .or_insert_with(|| Bucket::new(key));
+        let model = model_name(&entry.usage.model);
+        bucket.totals.add(&entry.usage);
+        bucket
+            .providers
+            .entry(provider_name(entry.provider).to_string())
+            .or_default()
+            .entry(model.clone())
+            .or_default()
+            .add(&entry.usage);
+        bucket.models.entry(model).or_default().add(&entry.usage);
+    }
+    buckets.into_values().collect()
+}
+
+/// Sum of every bucket's totals
+pub fn grand_total<K>(buckets: &[Bucket<K>]) -> Totals {
+    let mut totals = Totals::default();
+    for bucket in buckets {
+        totals.merge(&bucket.totals);
+    }
+    totals
+}
+
+/// Short model name for reports, e.g. `claude-sonnet-4` for `claude-sonnet-4-20250514`
+pub fn model_name(model: &str) -> String {
+    match normalize(model) {
+        name if name.is_empty() => "unknown".to_string(),
+        name => name,
+    }
+}
+
+/// Provider label for reports
+pub fn provider_name(provider: Option<ProviderKind>) -> &'static str {
+    provider.map_or("unknown", ProviderKind::name)
+}
+
+/// Every transcript entry `loader` finds, with costs from `pricing`
+pub fn load_entries(
+    loader: &DataLoader,
+    pricing: &HashMap<String, ModelPricing>,
+) -> Vec<SourcedEntry> {
+    let mut entries = loader.load_sourced();
+    fill_costs(entries.iter_mut().map(|entry| &mut entry.usage), pricing);
+    entries
+}
+
+/// `1234567` → `1,234,567`
+pub fn format_tokens(tokens: u64) -> String {
+    let digits = tokens.to_string();
+    let mut out = String::new();
+    for (i, c) in digits.chars().enumerate() {
+        if i > 0 && (digits.len() - i).is_multiple_of(3) {
+            out.push(',');
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Dollar amount with two decimals; never prints `-0.00`
+pub fn format_cost(cost: f64) -> String {
+    format!("${:.2}", if cost > 0.0 { cost } else { 0.0 })
+}
+
+/// Cost of `totals`, marked with `*` when it leaves out unpriced entries
+pub fn format_totals_cost(totals: &Totals) -> String {
+    let cost = format_cost(totals.cost);
+    if totals.is_partial() {
+        format!("{}*", cost)
+    } else {
+        cost
+    }
+}
+
+/// Footnote explaining the `*` of [`format_totals_cost`], if `totals` needs one
+pub fn unpriced_note(totals: &Totals) -> Option<String> {
+    totals.is_partial().then(|| {
+        let models: Vec<&str> = totals.unpriced_models.iter().map(String::as_str).collect();
+        format!(
+            "* Excludes {} {} of models without a price: {}",
+            totals.unpriced_entries,
+            if totals.unpriced_entries == 1 {
+                "entry"
+            } else {
+                "entries"
+            },
+            models.join(", ")
+        )
+    })
+}
+
+/// `12.5` → `+12.5%`
+pub fn format_percent(percent: f64) -> String {
+    format!("{:+.1}%", percent)
+}
+
+/// Signed dollar amount, e.g. `+$1.20` or `-$0.35`
+pub fn format_cost_change(change: f64) -> String {
+    let sign = if change < 0.0 { '-' } else { '+' };
+    format!("{}{}", sign, format_cost(change.abs()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_timezone_specs() {
+        assert_eq!("UTC".parse(), Ok(ReportTimezone::Utc));
+        assert_eq!("local".parse(), Ok(ReportTimezone::Local));
+        let offset = |seconds| {
+            Ok(ReportTimezone::Fixed(
+                FixedOffset::east_opt(seconds).unwrap(),
+            ))
+        };
+        assert_eq!("+09:00".parse(), offset(9 * 3600));
+        assert_eq!("-0530".parse(), offset(-(5 * 3600 + 30 * 60)));
+        assert_eq!("+2".parse(), offset(2 * 3600));
+        assert!("Europe/Paris".parse::<ReportTimezone>().is_err());
+        assert!("+05:75".parse::<ReportTimezone>().is_err());
+
+        let timestamp = "2025-10-01T23:30:00Z".parse::<DateTime<Utc>>().unwrap();
+        let tokyo: ReportTimezone = "+09:00".parse().unwrap();
+        assert_eq!(tokyo.date(timestamp).to_string(), "2025-10-02");
+        assert_eq!(
+            ReportTimezone::Utc.date(timestamp).to_string(),
+            "2025-10-01"
+        );
+    }
+
+    #[test]
+    fn test_number_formats() {
+        assert_eq!(format_tokens(0), "0");
+        assert_eq!(format_tokens(999), "999");
+        assert_eq!(format_tokens(1_234_567), "1,234,567");
+        assert_eq!(format_cost(-0.0), "$0.00");
+        assert_eq!(format_cost(12.345), "$12.35");
+        assert_eq!(format_cost_change(-0.354), "-$0.35");
+        assert_eq!(format_cost_change(0.0), "+$0.00");
+        assert_eq!(format_percent(12.54), "+12.5%");
+    }
+
+    #[test]
+    fn test_unpriced_entries_are_tracked() {
+        let entry = |model: &str, cost: Option<f64>| UsageEntry {
+            timestamp: "2025-10-01T10:00:00Z".parse().unwrap(),
+            input_tokens: 100,
+            output_tokens: 10,
+            cache_creation_tokens: 0,
+            cache_read_tokens: 0,
+            model: model.to_string(),
+            cost,
+            session_id: "s".to_string(),
+        };
+        let mut totals = Totals::default();
+        totals.add(&entry("claude-sonnet-4-20250514", Some(0.5)));
+        assert!(!totals.is_partial());
+        assert_eq!(format_totals_cost(&totals), "$0.50");
+        assert_eq!(unpriced_note(&totals), None);
+
+        let mut other = Totals::default();
+        other.add(&entry("my-gateway-model", None));
+        other.add(&entry("my-gateway-model", None));
+        totals.merge(&other);
+        assert_eq!(totals.entries, 3);
+        assert_eq!(totals.unpriced_entries, 2);
+        assert_eq!(totals.cost, 0.5);
+        assert_eq!(format_totals_cost(&totals), "$0.50*");
+        assert_eq!(
+            unpriced_note(&totals).unwrap(),
+            "* Excludes 2 entries of models without a price: my-gateway-model"
+        );
+    }
+
+    #[test]
+    fn test_week_start_names() {
+        assert_eq!("sun".parse(), Ok(WeekStart::Sunday));
+        assert_eq!("Wednesday".parse(), Ok(WeekStart::Wednesday));
+        assert!("someday".parse::<WeekStart>().is_err());
+        assert_eq!(WeekStart::Saturday.name(), "saturday");
+    }
+}
@@ -0,0 +1,189 @@
+//! Per-month usage report with month-over-month changes (`ccline report monthly`)
+
+use super::output::{ReportOutput, Row, RowKey};
+use super::table::UsageTable;
+use super::{
+    aggregate, format_cost_change, format_percent, grand_total, Bucket, ReportOptions, Table,
+    Totals,
+};
+use crate::output::{csv_field, round_cost};
+use crate::utils::transcript_index::SourcedEntry;
+use chrono::{Datelike, Months, NaiveDate};
+use serde::Serialize;
+
+/// Usage per calendar month, keyed by the month's first day, oldest first
+#[derive(Debug, Clone)]
+pub struct MonthlyReport {
+    pub months: Vec<Bucket<NaiveDate>>,
+    /// Change of each month against the calendar month before it; `None` for
+    /// the first month, whose predecessor lies outside the report
+    pub changes: Vec<Option<Change>>,
+    pub totals: Totals,
+}
+
+/// Difference between two periods' totals
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Change {
+    pub tokens: i64,
+    pub cost: f64,
+    /// Relative to the earlier period; `None` when it had no tokens
+    pub tokens_percent: Option<f64>,
+    /// Relative to the earlier period; `None` when it cost nothing
+    pub cost_percent: Option<f64>,
+}
+
+impl Change {
+    pub fn between(previous: &Totals, current: &Totals) -> Self {
+        let tokens = current.total_tokens() as i64 - previous.total_tokens() as i64;
+        let cost = current.cost - previous.cost;
+        Self {
+            tokens,
+            cost,
+            tokens_percent: (previous.total_tokens() > 0)
+                .then(|| tokens as f64 / previous.total_tokens() as f64 * 100.0),
+            cost_percent: (previous.cost > 0.0).then(|| cost / previous.cost * 100.0),
+        }
+    }
+}
+
+pub fn monthly(entries: &[SourcedEntry], options: &ReportOptions) -> MonthlyReport {
+    let months = aggregate(entries, options, |entry| {
+        month_of(options.timezone.date(entry.usage.timestamp))
+    });
+
+    // A month without usage between two reported months counts as zero
+    let changes = months
+        .iter()
+        .enumerate()
+        .map(|(i, month)| {
+            let previous = months.get(i.checked_sub(1)?)?;
+            let before = month.key - Months::new(1);
+            let previous = if previous.key == before {
+                previous.totals.clone()
+            } else {
+                Totals::default()
+            };
+            Some(Change::between(&previous, &month.totals))
+        })
+        .collect();
+
+    let totals = grand_total(&months);
+    MonthlyReport {
+        months,
+        changes,
+        totals,
+    }
+}
+
+fn month_of(date: NaiveDate) -> NaiveDate {
+    date.with_day(1).unwrap_or(date)
+}
+
+impl MonthlyReport {
+    pub fn table(&self, breakdown: bool) -> Table {
+        let mut table = UsageTable::new("Month", &["Δ Tokens", "Δ Cost"], breakdown);
+        for (month, change) in self.months.iter().zip(&self.changes) {
+            let extra = match change {
+                Some(change) => vec![
+                    change
+                        .tokens_percent
+                        .map_or_else(|| "n/a".to_string(), format_percent),
+                    match change.cost_percent {
+                        Some(percent) => format!(
+                            "{} ({})",
+                            format_cost_change(change.cost),
+                            format_percent(percent)
+                        ),
+                        None => format_cost_change(change.cost),
+                    },
+                ],
+                None => vec!["—".to_string(), "—".to_string()],
+            };
+            table.push(month.key.format("%Y-%m").to_string(), month, extra);
+        }
+        table.finish(&self.totals)
+    }
+
+    pub fn output(&self, options: &ReportOptions) -> ReportOutput<MonthKey> {
+        let rows = self
+            .months
+            .iter()
+            .zip(&self.changes)
+            .map(|(month, change)| {
+                let key = MonthKey {
+                    month: month.key.format("%Y-%m").to_string(),
+                    change: change.map(|change| ChangeRecord {
+                        tokens: change.tokens,
+                        cost_usd: round_cost(change.cost),
+                        tokens_percent: change.tokens_percent,
+                        cost_percent: change.cost_percent,
+                    }),
+                };
+                Row::new(key, month)
+            })
+            .collect();
+        ReportOutput::new("monthly", options, rows, &self.totals)
+    }
+}
+
+/// Key of a monthly row in machine-readable output
+#[derive(Debug, Clone, Serialize)]
+pub struct MonthKey {
+    /// `YYYY-MM`
+    pub month: String,
+    /// `null` for the first month of the report
+    pub change: Option<ChangeRecord>,
+}
+
+/// [`Change`] against the previous calendar month
+#[derive(Debug, Clone, Serialize)]
+pub struct ChangeRecord {
+    pub tokens: i64,
+    pub cost_usd: f64,
+    pub tokens_percent: Option<f64>,
+    pub cost_percent: Option<f64>,
+}
+
+impl RowKey for MonthKey {
+    const COLUMNS: &'static [&'static str] = &[
+        "month",
+        "change_tokens",
+        "change_cost_usd",
+        "change_tokens_percent",
+        "change_cost_percent",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        let change = self.change.as_ref();
+        vec![
+            self.month.clone(),
+            csv_field(change.map(|c| c.tokens)),
+            csv_field(change.map(|c| c.cost_usd)),
+            csv_field(change.and_then(|c| c.tokens_percent)),
+            csv_field(change.and_then(|c| c.cost_percent)),
+        ]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_between_months() {
+        let totals = |tokens: u64, cost: f64| Totals {
+            input_tokens: tokens,
+            cost,
+            ..Totals::default()
+        };
+
+        let change = Change::between(&totals(1000, 2.0), &totals(1500, 1.5));
+        assert_eq!(change.tokens, 500);
+        assert_eq!(change.tokens_percent, Some(50.0));
+        assert_eq!(change.cost_percent, Some(-25.0));
+
+        let change = Change::between(&Totals::default(), &totals(10, 0.5));
+        assert_eq!(change.tokens_percent, None);
+        assert_eq!(change.cost_percent, None);
+    }
+}
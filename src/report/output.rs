@@ -0,0 +1,212 @@
+//! Report rows for `--format json|ndjson|csv`
+
+use super::{provider_name, Bucket, ReportOptions, Totals};
+use crate::output::{self, round_cost, Csv, OutputFormat};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::Serialize;
+
+/// Fields identifying a report row, e.g. its day or session
+pub trait RowKey: Serialize {
+    /// CSV column names, in the order of [`RowKey::cells`]
+    const COLUMNS: &'static [&'static str];
+
+    fn cells(&self) -> Vec<String>;
+}
+
+const USAGE_COLUMNS: &[&str] = &[
+    "input_tokens",
+    "output_tokens",
+    "cache_creation_tokens",
+    "cache_read_tokens",
+    "total_tokens",
+    "cost_usd",
+    "entries",
+    "unpriced_entries",
+    "unpriced_models",
+];
+
+/// Token counts and cost of a row, a model or the whole report
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct UsageRecord {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cache_creation_tokens: u64,
+    pub cache_read_tokens: u64,
+    pub total_tokens: u64,
+    /// Cost of the priced entries only
+    pub cost_usd: f64,
+    pub entries: usize,
+    /// Entries left out of `cost_usd` because their model has no price
+    pub unpriced_entries: usize,
+    pub unpriced_models: Vec<String>,
+}
+
+impl From<&Totals> for UsageRecord {
+    fn from(totals: &Totals) -> Self {
+        Self {
+            input_tokens: totals.input_tokens,
+            output_tokens: totals.output_tokens,
+            cache_creation_tokens: totals.cache_creation_tokens,
+            cache_read_tokens: totals.cache_read_tokens,
+            total_tokens: totals.total_tokens(),
+            cost_usd: round_cost(totals.cost),
+            entries: totals.entries,
+            unpriced_entries: totals.unpriced_entries,
+            unpriced_models: totals.unpriced_models.iter().cloned().collect(),
+        }
+    }
+}
+
+impl UsageRecord {
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.input_tokens.to_string(),
+            self.output_tokens.to_string(),
+            self.cache_creation_tokens.to_string(),
+            self.cache_read_tokens.to_string(),
+            self.total_tokens.to_string(),
+            self.cost_usd.to_string(),
+            self.entries.to_string(),
+            self.unpriced_entries.to_string(),
+            self.unpriced_models.join(";"),
+        ]
+    }
+}
+
+/// Usage of one model of one provider within a row
+#[derive(Debug, Clone, Serialize)]
+pub struct ModelRecord {
+    pub provider: String,
+    pub model: String,
+    #[serde(flatten)]
+    pub usage: UsageRecord,
+}
+
+/// One report row: its key fields, its usage and a per-model breakdown
+#[derive(Debug, Clone, Serialize)]
+pub struct Row<K> {
+    #[serde(flatten)]
+    pub key: K,
+    #[serde(flatten)]
+    pub usage: UsageRecord,
+    pub models: Vec<ModelRecord>,
+}
+
+impl<K> Row<K> {
+    pub fn new<B>(key: K, bucket: &Bucket<B>) -> Self {
+        let models = bucket
+            .providers
+            .iter()
+            .flat_map(|(provider, models)| {
+                models.iter().map(move |(model, totals)| ModelRecord {
+                    provider: provider.clone(),
+                    model: model.clone(),
+                    usage: totals.into(),
+                })
+            })
+            .collect();
+        Self {
+            key,
+            usage: (&bucket.totals).into(),
+            models,
+        }
+    }
+}
+
+/// Options a report was built with, echoed in JSON documents
+#[derive(Debug, Clone, Serialize)]
+struct Filters {
+    since: Option<NaiveDate>,
+    until: Option<NaiveDate>,
+    timezone: String,
+    provider: Option<&'static str>,
+}
+
+/// A report in machine-readable form
+#[derive(Debug, Clone, Serialize)]
+pub struct ReportOutput<K> {
+    #[serde(skip)]
+    kind: &'static str,
+    generated_at: DateTime<Utc>,
+    filters: Filters,
+    pub rows: Vec<Row<K>>,
+    pub totals: UsageRecord,
+}
+
+impl<K: RowKey> ReportOutput<K> {
+    /// `kind` names the report in the `kind` field, e.g. `daily`
+    pub fn new(
+        kind: &'static str,
+        options: &ReportOptions,
+        rows: Vec<Row<K>>,
+        totals: &Totals,
+    ) -> Self {
+        Self {
+            kind,
+            generated_at: Utc::now(),
+            filters: Filters {
+                since: options.since,
+                until: options.until,
+                timezone: options.timezone.to_string(),
+                provider: options
+                    .provider
+                    .map(|provider| provider_name(Some(provider))),
+            },
+            rows,
+            totals: totals.into(),
+        }
+    }
+
+    /// Text in `format`, or `None` for [`OutputFormat::Table`]
+    ///
+    /// NDJSON has a `row` line per row followed by a `totals` line. CSV has no
+    /// totals row; with `breakdown` it has one row per provider and model
+    /// instead of one per report row.
+    pub fn render(&self, format: OutputFormat, breakdown: bool) -> Option<String> {
+        match format {
+            OutputFormat::Table => None,
+            OutputFormat::Json => Some(output::json(self.kind, self)),
+            OutputFormat::Ndjson => {
+                let mut lines: Vec<String> = self
+                    .rows
+                    .iter()
+                    .map(|row| output::ndjson_line(self.kind, Some("row"), row))
+                    .collect();
+                lines.push(output::ndjson_line(self.kind, Some("totals"), &self.totals));
+                Some(lines.join("\n"))
+            }
+            OutputFormat::Csv => Some(self.csv(breakdown).render()),
+        }
+    }
+
+    fn csv(&self, breakdown: bool) -> Csv {
+        let mut header = K::COLUMNS.to_vec();
+        if breakdown {
+            header.extend(["model_provider", "model"]);
+        } else {
+            header.push("models");
+        }
+        header.extend(USAGE_COLUMNS);
+
+        let mut csv = Csv::new(header);
+        for row in &self.rows {
+            if breakdown {
+                for model in &row.models {
+                    let mut cells = row.key.cells();
+                    cells.extend([model.provider.clone(), model.model.clone()]);
+                    cells.extend(model.usage.cells());
+                    csv.push(cells);
+                }
+            } else {
+                let mut cells = row.key.cells();
+                let mut models: Vec<&str> = row.models.iter().map(|m| m.model.as_str()).collect();
+                models.sort_unstable();
+                models.dedup();
+                cells.push(models.join(";"));
+                cells.extend(row.usage.cells());
+                csv.push(cells);
+            }
+        }
+        csv
+    }
+}
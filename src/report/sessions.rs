@@ -0,0 +1,361 @@
+//! Per-session usage report (`ccline report sessions`)
+
+use super::output::{ReportOutput, Row, RowKey};
+use super::provider_name;
+use super::{
+    aggregate, format_tokens, format_totals_cost, unpriced_note, Bucket, ReportOptions, Table,
+    Totals,
+};
+use crate::config::ProviderKind;
+use crate::core::render::Align;
+use crate::output::timestamp;
+use crate::utils::transcript_index::SourcedEntry;
+use chrono::{DateTime, Duration, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Component, Path};
+use std::str::FromStr;
+
+/// One transcript session with its project and active span
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub usage: Bucket<String>,
+    /// Working directory of the session, when it could be determined
+    pub project: Option<String>,
+    pub provider: Option<ProviderKind>,
+    /// First and last usage entry in the report range
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+}
+
+impl Session {
+    pub fn id(&self) -> &str {
+        &self.usage.key
+    }
+
+    pub fn duration(&self) -> Duration {
+        self.last - self.first
+    }
+}
+
+/// Usage per session, most recently active first unless sorted otherwise
+#[derive(Debug, Clone)]
+pub struct SessionsReport {
+    pub sessions: Vec<Session>,
+    pub totals: Totals,
+}
+
+/// Column a sessions report is ordered by
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SessionSort {
+    /// Most recently active first
+    #[default]
+    Last,
+    /// Most recently started first
+    First,
+    /// Longest first
+    Duration,
+    /// Most tokens first
+    Tokens,
+    /// Most expensive first
+    Cost,
+    /// Project path in alphabetical order
+    Project,
+}
+
+impl SessionSort {
+    pub const NAMES: &'static [&'static str] =
+        &["last", "first", "duration", "tokens", "cost", "project"];
+}
+
+impl FromStr for SessionSort {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        match name.trim().to_ascii_lowercase().as_str() {
+            "last" => Ok(SessionSort::Last),
+            "first" => Ok(SessionSort::First),
+            "duration" => Ok(SessionSort::Duration),
+            "tokens" => Ok(SessionSort::Tokens),
+            "cost" => Ok(SessionSort::Cost),
+            "project" => Ok(SessionSort::Project),
+            _ => Err(format!(
+                "invalid sort `{}` (expected {})",
+                name,
+                Self::NAMES.join(", ")
+            )),
+        }
+    }
+}
+
+impl fmt::Display for SessionSort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(Self::NAMES[*self as usize])
+    }
+}
+
+/// Sessions with usage in the report range
+///
+/// With `project`, only sessions whose project path contains it are kept.
+pub fn sessions(
+    entries: &[SourcedEntry],
+    options: &ReportOptions,
+    project: Option<&str>,
+) -> SessionsReport {
+    let mut spans: BTreeMap<&str, (&SourcedEntry, DateTime<Utc>)> = BTreeMap::new();
+    for entry in entries.iter().filter(|entry| options.includes(entry)) {
+        let timestamp = entry.usage.timestamp;
+        spans
+            .entry(entry.usage.session_id.as_str())
+            .and_modify(|(_, last)| *last = (*last).max(timestamp))
+            .or_insert((entry, timestamp));
+    }
+
+    let mut sessions: Vec<Session> =
+        aggregate(entries, options, |entry| entry.usage.session_id.clone())
+            .into_iter()
+            .map(|usage| {
+                let (first, last) = spans[usage.key.as_str()];
+                Session {
+                    project: project_dir(first),
+                    provider: first.provider,
+                    first: first.usage.timestamp,
+                    last,
+                    usage,
+                }
+            })
+            .filter(|session| {
+                project.is_none_or(|filter| {
+                    session
+                        .project
+                        .as_deref()
+                        .is_some_and(|path| path.contains(filter))
+                })
+            })
+            .collect();
+    sessions.sort_by_key(|session| std::cmp::Reverse(session.last));
+
+    let mut totals = Totals::default();
+    for session in &sessions {
+        totals.merge(&session.usage.totals);
+    }
+    SessionsReport { sessions, totals }
+}
+
+impl SessionsReport {
+    /// Order sessions by `sort`; `reverse` flips the order
+    pub fn sort(&mut self, sort: SessionSort, reverse: bool) {
+        self.sessions.sort_by(|a, b| {
+            let order = match sort {
+                SessionSort::Last => b.last.cmp(&a.last),
+                SessionSort::First => b.first.cmp(&a.first),
+                SessionSort::Duration => b.duration().cmp(&a.duration()),
+                SessionSort::Tokens => b
+                    .usage
+                    .totals
+                    .total_tokens()
+                    .cmp(&a.usage.totals.total_tokens()),
+                SessionSort::Cost => b.usage.totals.cost.total_cmp(&a.usage.totals.cost),
+                SessionSort::Project => a.project.cmp(&b.project),
+            };
+            order.then_with(|| b.last.cmp(&a.last))
+        });
+        if reverse {
+            self.sessions.reverse();
+        }
+    }
+
+    /// Times are shown in the report timezone of `options`; with `breakdown`,
+    /// each session is followed by per-provider and per-model rows
+    pub fn table(&self, options: &ReportOptions, breakdown: bool) -> Table {
+        let mut table = Table::new([
+            ("Session", Align::Left),
+            ("Project", Align::Left),
+            ("First", Align::Left),
+            ("Last", Align::Left),
+            ("Duration", Align::Right),
+            ("Models", Align::Left),
+            ("Total Tokens", Align::Right),
+            ("Cost (USD)", Align::Right),
+        ]);
+        let time = |timestamp| {
+            options
+                .timezone
+                .naive(timestamp)
+                .format("%Y-%m-%d %H:%M")
+                .to_string()
+        };
+
+        for session in &self.sessions {
+            let usage = &session.usage;
+            table.push_row(vec![
+                short_id(session.id()).to_string(),
+                session
+                    .project
+                    .clone()
+                    .unwrap_or_else(|| "unknown".to_string()),
+                time(session.first),
+                time(session.last),
+                format_duration(session.duration()),
+                usage.models.keys().cloned().collect::<Vec<_>>().join("\n"),
+                format_tokens(usage.totals.total_tokens()),
+                format_totals_cost(&usage.totals),
+            ]);
+
+            if breakdown {
+                for (provider, models) in &usage.providers {
+                    for (model, totals) in models {
+                        let mut row = vec![format!("  └ {}", provider)];
+                        row.extend(std::iter::repeat_n(String::new(), 4));
+                        row.extend([
+                            model.clone(),
+                            format_tokens(totals.total_tokens()),
+                            format_totals_cost(totals),
+                        ]);
+                        table.push_row(row);
+                    }
+                }
+            }
+        }
+
+        let mut totals = vec![format!("Total ({})", self.sessions.len())];
+        totals.extend(std::iter::repeat_n(String::new(), 5));
+        totals.extend([
+            format_tokens(self.totals.total_tokens()),
+            format_totals_cost(&self.totals),
+        ]);
+        table
+            .with_totals(totals)
+            .with_note(unpriced_note(&self.totals))
+    }
+
+    pub fn output(&self, options: &ReportOptions) -> ReportOutput<SessionKey> {
+        let rows = self
+            .sessions
+            .iter()
+            .map(|session| {
+                let key = SessionKey {
+                    session_id: session.id().to_string(),
+                    project: session.project.clone(),
+                    provider: session.provider.map(|p| provider_name(Some(p))),
+                    first: session.first,
+                    last: session.last,
+                    duration_minutes: session.duration().num_minutes(),
+                };
+                Row::new(key, &session.usage)
+            })
+            .collect();
+        ReportOutput::new("sessions", options, rows, &self.totals)
+    }
+}
+
+/// Key of a session row in machine-readable output
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionKey {
+    pub session_id: String,
+    pub project: Option<String>,
+    pub provider: Option<&'static str>,
+    pub first: DateTime<Utc>,
+    pub last: DateTime<Utc>,
+    pub duration_minutes: i64,
+}
+
+impl RowKey for SessionKey {
+    const COLUMNS: &'static [&'static str] = &[
+        "session_id",
+        "project",
+        "provider",
+        "first",
+        "last",
+        "duration_minutes",
+    ];
+
+    fn cells(&self) -> Vec<String> {
+        vec![
+            self.session_id.clone(),
+            self.project.clone().unwrap_or_default(),
+            self.provider.unwrap_or_default().to_string(),
+            timestamp(self.first),
+            timestamp(self.last),
+            self.duration_minutes.to_string(),
+        ]
+    }
+}
+
+/// Project directory of the session `entry` belongs to
+///
+/// The working directory recorded in the transcript wins. Claude transcripts
+/// without one fall back to their `projects/<encoded-path>` folder name.
+pub fn project_dir(entry: &SourcedEntry) -> Option<String> {
+    entry
+        .cwd
+        .clone()
+        .or_else(|| claude_project_folder(&entry.path).map(decode_project_folder))
+}
+
+/// Name of the folder directly below a `projects` directory, if `path` has one
+fn claude_project_folder(path: &Path) -> Option<&str> {
+    let components: Vec<Component> = path.components().collect();
+    components.windows(2).find_map(|pair| match pair {
+        [Component::Normal(parent), Component::Normal(folder)] if *parent == "projects" => {
+            folder.to_str()
+        }
+        _ => None,
+    })
+}
+
+/// Undo Claude's project folder encoding, which replaces path separators with `-`
+///
+/// The encoding is lossy: dashes and dots in the original path are decoded
+/// as separators too. `C--Users-me` decodes to `C:/Users/me`.
+pub fn decode_project_folder(folder: &str) -> String {
+    let mut chars = folder.chars();
+    match (chars.next(), folder.get(1..3)) {
+        (Some(drive), Some("--")) if drive.is_ascii_alphabetic() => {
+            format!("{}:/{}", drive, folder[3..].replace('-', "/"))
+        }
+        _ => folder.replace('-', "/"),
+    }
+}
+
+/// Session ids are shown without the `rollout-<time>-` prefix of Codex file names
+fn short_id(id: &str) -> &str {
+    const UUID_LEN: usize = 36;
+    match id.len().checked_sub(UUID_LEN) {
+        Some(start) if start > 0 && id.is_char_boundary(start) => &id[start..],
+        _ => id,
+    }
+}
+
+/// `1h 05m`, or `12m` below an hour
+pub fn format_duration(duration: Duration) -> String {
+    let minutes = duration.num_minutes().max(0);
+    if minutes >= 60 {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    } else {
+        format!("{}m", minutes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_project_folder_decoding() {
+        assert_eq!(decode_project_folder("-home-user-demo"), "/home/user/demo");
+        assert_eq!(decode_project_folder("C--Users-me-app"), "C:/Users/me/app");
+
+        let path = Path::new("/root/.claude/projects/-home-user-demo/abc.jsonl");
+        assert_eq!(claude_project_folder(path), Some("-home-user-demo"));
+        assert_eq!(claude_project_folder(Path::new("/tmp/abc.jsonl")), None);
+
+        assert_eq!(
+            short_id("rollout-2025-10-02T15-00-00-0199a000-0000-7000-8000-000000000002"),
+            "0199a000-0000-7000-8000-000000000002"
+        );
+        assert_eq!(format_duration(Duration::minutes(65)), "1h 05m");
+        assert_eq!(format_duration(Duration::seconds(30)), "0m");
+    }
+}
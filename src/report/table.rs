@@ -0,0 +1,214 @@
+//! Plain-text tables for report output
+
+use super::{format_tokens, format_totals_cost, unpriced_note, Bucket, Totals};
+use crate::core::render::Align;
+use crate::core::width::display_width;
+
+/// Column-aligned table with an optional totals row
+#[derive(Debug, Clone, Default)]
+pub struct Table {
+    columns: Vec<(String, Align)>,
+    rows: Vec<Vec<String>>,
+    totals: Option<Vec<String>>,
+    note: Option<String>,
+}
+
+impl Table {
+    pub fn new<S: Into<String>>(columns: impl IntoIterator<Item = (S, Align)>) -> Self {
+        Self {
+            columns: columns
+                .into_iter()
+                .map(|(title, align)| (title.into(), align))
+                .collect(),
+            ..Self::default()
+        }
+    }
+
+    pub fn push_row(&mut self, row: Vec<String>) {
+        self.rows.push(row);
+    }
+
+    pub fn with_totals(mut self, totals: Vec<String>) -> Self {
+        self.totals = Some(totals);
+        self
+    }
+
+    /// Line printed below the table, e.g. a footnote
+    pub fn with_note(mut self, note: Option<String>) -> Self {
+        self.note = note;
+        self
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Cell text may span several lines; each line is aligned on its own
+    pub fn render(&self) -> String {
+        let mut widths: Vec<usize> = self
+            .columns
+            .iter()
+            .map(|(title, _)| display_width(title))
+            .collect();
+        for row in self.rows.iter().chain(&self.totals) {
+            for (width, cell) in widths.iter_mut().zip(row) {
+                for line in cell.lines() {
+                    *width = (*width).max(display_width(line));
+                }
+            }
+        }
+
+        let rule = widths
+            .iter()
+            .map(|width| "─".repeat(*width))
+            .collect::<Vec<_>>()
+            .join("──");
+        let titles: Vec<String> = self
+            .columns
+            .iter()
+            .map(|(title, _)| title.clone())
+            .collect();
+
+        let mut out = Vec::new();
+        out.extend(self.render_row(&titles, &widths));
+        out.push(rule.clone());
+        for row in &self.rows {
+            out.extend(self.render_row(row, &widths));
+        }
+        if let Some(totals) = &self.totals {
+            out.push(rule);
+            out.extend(self.render_row(totals, &widths));
+        }
+        if let Some(note) = &self.note {
+            out.push(String::new());
+            out.push(note.clone());
+        }
+        out.join("\n")
+    }
+
+    fn render_row(&self, row: &[String], widths: &[usize]) -> Vec<String> {
+        let height = row
+            .iter()
+            .map(|cell| cell.lines().count())
+            .max()
+            .unwrap_or(0);
+        (0..height.max(1))
+            .map(|line| {
+                let cells: Vec<String> = self
+                    .columns
+                    .iter()
+                    .zip(widths)
+                    .enumerate()
+                    .map(|(column, ((_, align), width))| {
+                        let text = row
+                            .get(column)
+                            .and_then(|cell| cell.lines().nth(line))
+                            .unwrap_or("");
+                        let padding = " ".repeat(width.saturating_sub(display_width(text)));
+                        match align {
+                            Align::Left => format!("{}{}", text, padding),
+                            Align::Right => format!("{}{}", padding, text),
+                        }
+                    })
+                    .collect();
+                cells.join("  ").trim_end().to_string()
+            })
+            .collect()
+    }
+}
+
+/// Token and cost table with one row per report bucket
+///
+/// Extra columns (e.g. month-over-month changes) follow the cost column. With
+/// `breakdown`, every bucket is followed by one row per provider and model.
+pub(crate) struct UsageTable {
+    table: Table,
+    extra: usize,
+    breakdown: bool,
+}
+
+impl UsageTable {
+    pub fn new(title: &str, extra: &[&str], breakdown: bool) -> Self {
+        let mut columns = vec![
+            (title, Align::Left),
+            ("Models", Align::Left),
+            ("Input", Align::Right),
+            ("Output", Align::Right),
+            ("Cache Create", Align::Right),
+            ("Cache Read", Align::Right),
+            ("Total Tokens", Align::Right),
+            ("Cost (USD)", Align::Right),
+        ];
+        columns.extend(extra.iter().map(|title| (*title, Align::Right)));
+        Self {
+            table: Table::new(columns),
+            extra: extra.len(),
+            breakdown,
+        }
+    }
+
+    pub fn push<K>(&mut self, label: String, bucket: &Bucket<K>, extra: Vec<String>) {
+        let models = bucket.models.keys().cloned().collect::<Vec<_>>().join("\n");
+        let mut row = usage_row(label, models, &bucket.totals);
+        row.extend(extra);
+        self.table.push_row(row);
+
+        if self.breakdown {
+            for (provider, models) in &bucket.providers {
+                for (model, totals) in models {
+                    let mut row = usage_row(format!("  └ {}", provider), model.clone(), totals);
+                    row.extend(std::iter::repeat_n(String::new(), self.extra));
+                    self.table.push_row(row);
+                }
+            }
+        }
+    }
+
+    pub fn finish(self, totals: &Totals) -> Table {
+        self.table
+            .with_totals(usage_row("Total".to_string(), String::new(), totals))
+            .with_note(unpriced_note(totals))
+    }
+}
+
+fn usage_row(label: String, models: String, totals: &Totals) -> Vec<String> {
+    vec![
+        label,
+        models,
+        format_tokens(totals.input_tokens),
+        format_tokens(totals.output_tokens),
+        format_tokens(totals.cache_creation_tokens),
+        format_tokens(totals.cache_read_tokens),
+        format_tokens(totals.total_tokens()),
+        format_totals_cost(totals),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_alignment() {
+        let mut table = Table::new([("Name", Align::Left), ("Cost", Align::Right)]);
+        table.push_row(vec!["a\nbb".to_string(), "$1.00".to_string()]);
+        let table = table
+            .with_totals(vec!["Total".to_string(), "$10.00".to_string()])
+            .with_note(Some("* note".to_string()));
+
+        assert_eq!(
+            table.render(),
+            [
+                "Name     Cost",
+                "─────────────",
+                "a       $1.00",
+                "bb",
+                "─────────────",
+                "Total  $10.00",
+                "",
+                "* note",
+            ]
+            .join("\n")
+        );
+    }
+}
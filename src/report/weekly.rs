@@ -0,0 +1,88 @@
+//! Per-week usage report (`ccline report weekly`)
+
+use super::output::{ReportOutput, Row, RowKey};
+use super::table::UsageTable;
+use super::{aggregate, grand_total, Bucket, ReportOptions, Table, Totals};
+use crate::utils::transcript_index::SourcedEntry;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use serde::Serialize;
+
+/// Usage per week, keyed by the week's first day, oldest first
+#[derive(Debug, Clone)]
+pub struct WeeklyReport {
+    pub weeks: Vec<Bucket<NaiveDate>>,
+    pub totals: Totals,
+}
+
+pub fn weekly(entries: &[SourcedEntry], options: &ReportOptions) -> WeeklyReport {
+    let start = options.week_start.weekday();
+    let weeks = aggregate(entries, options, |entry| {
+        week_of(options.timezone.date(entry.usage.timestamp), start)
+    });
+    let totals = grand_total(&weeks);
+    WeeklyReport { weeks, totals }
+}
+
+/// First day of the week containing `date`, for weeks beginning on `start`
+pub fn week_of(date: NaiveDate, start: Weekday) -> NaiveDate {
+    let offset = (date.weekday().num_days_from_monday() + 7 - start.num_days_from_monday()) % 7;
+    date - Duration::days(offset as i64)
+}
+
+impl WeeklyReport {
+    /// Weeks are labelled by their first and last day, e.g. `2025-09-29 – 10-05`
+    pub fn table(&self, breakdown: bool) -> Table {
+        let mut table = UsageTable::new("Week", &[], breakdown);
+        for week in &self.weeks {
+            let last = week.key + Duration::days(6);
+            let label = format!("{} – {}", week.key.format("%Y-%m-%d"), last.format("%m-%d"));
+            table.push(label, week, Vec::new());
+        }
+        table.finish(&self.totals)
+    }
+
+    pub fn output(&self, options: &ReportOptions) -> ReportOutput<WeekKey> {
+        let rows = self
+            .weeks
+            .iter()
+            .map(|week| {
+                let key = WeekKey {
+                    week_start: week.key,
+                    week_end: week.key + Duration::days(6),
+                };
+                Row::new(key, week)
+            })
+            .collect();
+        ReportOutput::new("weekly", options, rows, &self.totals)
+    }
+}
+
+/// Key of a weekly row in machine-readable output: the week's first and last day
+#[derive(Debug, Clone, Serialize)]
+pub struct WeekKey {
+    pub week_start: NaiveDate,
+    pub week_end: NaiveDate,
+}
+
+impl RowKey for WeekKey {
+    const COLUMNS: &'static [&'static str] = &["week_start", "week_end"];
+
+    fn cells(&self) -> Vec<String> {
+        vec![self.week_start.to_string(), self.week_end.to_string()]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_week_of() {
+        let day = |text: &str| text.parse::<NaiveDate>().unwrap();
+        // 2025-10-01 is a Wednesday
+        assert_eq!(week_of(day("2025-10-01"), Weekday::Mon), day("2025-09-29"));
+        assert_eq!(week_of(day("2025-10-01"), Weekday::Sun), day("2025-09-28"));
+        assert_eq!(week_of(day("2025-10-01"), Weekday::Wed), day("2025-10-01"));
+        assert_eq!(week_of(day("2025-10-01"), Weekday::Thu), day("2025-09-25"));
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 
+use crate::core::icons::{Icon, IconPack};
 use crate::utils::resolve_config_dir;
 
 #[cfg(feature = "self-update")]
@@ -46,13 +47,25 @@ pub struct UpdateState {
 impl UpdateState {
     /// Get status bar display text
     pub fn status_text(&self) -> Option<String> {
+        self.status_display()
+            .map(|(icon, text)| format!("{} {}", IconPack::NerdFont.glyph(icon), text))
+    }
+
+    /// Status bar icon and text, for callers that pick their own icon pack
+    pub fn status_display(&self) -> Option<(Icon, String)> {
         match &self.status {
             #[cfg(feature = "self-update")]
-            UpdateStatus::Ready { version, .. } => Some(format!("\u{f06b0} Update v{}!", version)),
+            UpdateStatus::Ready { version, .. } => {
+                Some((Icon::UpdateReady, format!("Update v{}!", version)))
+            }
             #[cfg(not(feature = "self-update"))]
-            UpdateStatus::Ready { version, .. } => Some(format!("\u{f06b0} Update v{}!", version)),
-            UpdateStatus::Downloading { progress } => Some(format!("\u{f01da} {}%", progress)),
-            UpdateStatus::Installing => Some("\u{f01da} Installing...".to_string()),
+            UpdateStatus::Ready { version, .. } => {
+                Some((Icon::UpdateReady, format!("Update v{}!", version)))
+            }
+            UpdateStatus::Downloading { progress } => {
+                Some((Icon::Updating, format!("{}%", progress)))
+            }
+            UpdateStatus::Installing => Some((Icon::Updating, "Installing...".to_string())),
             #[cfg(feature = "self-update")]
             UpdateStatus::Completed {
                 version,
@@ -62,14 +75,14 @@ impl UpdateState {
                 let now = Utc::now();
                 let seconds_passed = now.signed_duration_since(*completed_at).num_seconds();
                 if seconds_passed < 10 {
-                    Some(format!("\u{f058} Updated v{}!", version))
+                    Some((Icon::Updated, format!("Updated v{}!", version)))
                 } else {
                     None
                 }
             }
             #[cfg(not(feature = "self-update"))]
             UpdateStatus::Completed { version, .. } => {
-                Some(format!("\u{f058} Updated v{}!", version))
+                Some((Icon::Updated, format!("Updated v{}!", version)))
             }
             _ => None,
         }
@@ -1,23 +1,35 @@
 use crate::billing::UsageEntry;
 use crate::config::ProviderKind;
-use crate::utils::transcript::{parse_line_to_usage, TranscriptState};
+use crate::utils::transcript_index::{SourcedEntry, TranscriptIndex};
 use glob::glob;
-use std::collections::HashSet;
 use std::fs;
-use std::io::{Read, Seek, SeekFrom};
+use std::io;
 use std::path::{Path, PathBuf};
 
 pub struct DataLoader {
     transcript_dirs: Vec<PathBuf>,
+    index_path: PathBuf,
 }
 
 impl DataLoader {
     pub fn new() -> Self {
+        Self::with_dirs(Self::find_transcript_dirs())
+    }
+
+    /// Read transcripts from `dirs` only, e.g. fixture directories in tests
+    pub fn with_dirs(dirs: Vec<PathBuf>) -> Self {
         Self {
-            transcript_dirs: Self::find_transcript_dirs(),
+            transcript_dirs: dirs,
+            index_path: TranscriptIndex::default_path(),
         }
     }
 
+    /// Keep the transcript index in directory `path` instead of the config directory
+    pub fn with_index_path(mut self, path: PathBuf) -> Self {
+        self.index_path = path;
+        self
+    }
+
     /// Find all transcript directories for supported providers
     fn find_transcript_dirs() -> Vec<PathBuf> {
         let mut dirs: Vec<PathBuf> = Vec::new();
@@ -70,95 +82,56 @@ impl DataLoader {
         dirs
     }
 
-    /// Load all usage data from all projects (fresh read every time)
+    /// Load all usage data from all projects
+    ///
+    /// Parsed entries are kept in the transcript index, so only files that
+    /// changed since the last run are read again.
     pub fn load_all_projects(&self) -> Vec<UsageEntry> {
-        let mut all_entries = Vec::new();
-        let mut seen_hashes = HashSet::new();
-
-        // Scan all project directories
-        for dir in &self.transcript_dirs {
-            let pattern = format!("{}/**/*.jsonl", dir.display());
-            if let Ok(paths) = glob(&pattern) {
-                for path in paths.flatten() {
-                    // Parse individual file
-                    let entries = self.parse_jsonl_file(&path, &mut seen_hashes);
-                    all_entries.extend(entries);
-                }
-            }
-        }
-
-        // Sort by timestamp
-        all_entries.sort_by_key(|e| e.timestamp);
-
-        all_entries
+        let files = self.transcript_files();
+        self.refreshed_index(&files).entries(&files)
     }
 
-    /// Parse a single JSONL file
-    fn parse_jsonl_file(&self, path: &Path, seen: &mut HashSet<String>) -> Vec<UsageEntry> {
-        let mut entries = Vec::new();
-
-        // Extract session_id from filename (UUID)
-        let session_id = path
-            .file_stem()
-            .and_then(|s| s.to_str())
-            .unwrap_or("unknown")
-            .to_string();
-
-        // Read file content (handle large files)
-        let content = match fs::metadata(path) {
-            Ok(metadata) if metadata.len() > 100 * 1024 * 1024 => {
-                // File > 100MB, only read last 10MB
-                self.read_last_n_bytes(path, 10 * 1024 * 1024)
-            }
-            _ => fs::read_to_string(path).unwrap_or_default(),
-        };
-
-        let provider_hint = detect_provider_from_path(path);
-        let mut state = TranscriptState::with_provider(provider_hint);
-
-        // Parse each line
-        for line in content.lines() {
-            if line.trim().is_empty() {
-                continue;
-            }
+    /// Load all usage data with the transcript file and provider of every entry
+    pub fn load_sourced(&self) -> Vec<SourcedEntry> {
+        let files = self.transcript_files();
+        self.refreshed_index(&files).sourced_entries(&files)
+    }
 
-            // Parse transcript entry and extract usage
-            if let Some(usage_entry) = parse_line_to_usage(line, &session_id, seen, &mut state) {
-                entries.push(usage_entry);
-            }
+    fn refreshed_index(&self, files: &[PathBuf]) -> TranscriptIndex {
+        let mut index = TranscriptIndex::load(&self.index_path);
+        index.refresh(files);
+        if let Err(e) = index.save(&self.index_path) {
+            eprintln!("Warning: failed to save transcript index: {}", e);
         }
-
-        entries
+        index
     }
 
-    /// Read the last N bytes of a file
-    fn read_last_n_bytes(&self, path: &Path, n: usize) -> String {
-        let mut file = match fs::File::open(path) {
-            Ok(f) => f,
-            Err(_) => return String::new(),
-        };
-
-        let file_len = match file.metadata() {
-            Ok(m) => m.len(),
-            Err(_) => return String::new(),
-        };
-
-        let start_pos = file_len.saturating_sub(n as u64);
-
-        // Seek to start position
-        if file.seek(SeekFrom::Start(start_pos)).is_err() {
-            return String::new();
+    /// Discard the transcript index and parse every file again
+    ///
+    /// Returns the number of indexed files and entries.
+    pub fn rebuild_index(&self) -> io::Result<(usize, usize)> {
+        let files = self.transcript_files();
+        match fs::remove_dir_all(&self.index_path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+            _ => {}
         }
 
-        let mut buffer = Vec::new();
-        let _ = file.read_to_end(&mut buffer);
+        let mut index = TranscriptIndex::default();
+        index.refresh(&files);
+        index.save(&self.index_path)?;
+        Ok((index.file_count(), index.entries(&files).len()))
+    }
 
-        // Find first complete line (skip partial line at beginning)
-        if let Some(pos) = buffer.iter().position(|&b| b == b'\n') {
-            buffer.drain(..=pos);
+    /// Every `*.jsonl` file under the transcript roots, in scan order
+    fn transcript_files(&self) -> Vec<PathBuf> {
+        let mut files = Vec::new();
+        for dir in &self.transcript_dirs {
+            let pattern = format!("{}/**/*.jsonl", dir.display());
+            if let Ok(paths) = glob(&pattern) {
+                files.extend(paths.flatten());
+            }
         }
-
-        String::from_utf8_lossy(&buffer).to_string()
+        files
     }
 }
 
@@ -174,7 +147,7 @@ fn push_unique(list: &mut Vec<PathBuf>, path: PathBuf) {
     }
 }
 
-fn detect_provider_from_path(path: &Path) -> Option<ProviderKind> {
+pub(crate) fn detect_provider_from_path(path: &Path) -> Option<ProviderKind> {
     let lowered = path.display().to_string().to_lowercase();
     if lowered.contains("/.codex/") || lowered.contains("\\.codex\\") {
         Some(ProviderKind::Codex)
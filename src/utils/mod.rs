@@ -1,6 +1,7 @@
 pub mod data_loader;
 pub mod paths;
 pub mod transcript;
+pub mod transcript_index;
 
 pub use data_loader::DataLoader;
 pub use paths::resolve_config_dir;
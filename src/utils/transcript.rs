@@ -1,6 +1,7 @@
 use crate::billing::UsageEntry;
 use crate::config::{NormalizedUsage, ProviderKind, TokenCountInfo, TranscriptEntry};
 use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 
 /// Extract session ID from file path (the UUID part)
@@ -11,10 +12,19 @@ pub fn extract_session_id(path: &std::path::Path) -> String {
         .to_string()
 }
 
-#[derive(Debug, Default)]
+/// Parser state carried from one transcript line to the next
+///
+/// Serializable so the transcript index can resume parsing where it stopped.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
 pub struct TranscriptState {
+    #[serde(default)]
     provider: Option<ProviderKind>,
+    #[serde(default)]
     current_model: Option<String>,
+    /// First working directory recorded in the transcript
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    cwd: Option<String>,
+    #[serde(skip)]
     pub last_normalized: Option<NormalizedUsage>,
 }
 
@@ -34,6 +44,10 @@ impl TranscriptState {
         self.provider
     }
 
+    pub fn cwd(&self) -> Option<&str> {
+        self.cwd.as_deref()
+    }
+
     fn update_provider(&mut self, entry: &TranscriptEntry) {
         if self.provider.is_some() {
             return;
@@ -42,6 +56,19 @@ impl TranscriptState {
         self.provider = detect_provider_from_entry(entry);
     }
 
+    fn update_cwd(&mut self, entry: &TranscriptEntry) {
+        if self.cwd.is_some() {
+            return;
+        }
+
+        self.cwd = entry
+            .cwd
+            .as_ref()
+            .or_else(|| entry.payload.as_ref().and_then(|p| p.cwd.as_ref()))
+            .filter(|cwd| !cwd.is_empty())
+            .cloned();
+    }
+
     fn update_model_from_entry(&mut self, entry: &TranscriptEntry) {
         if let Some(message) = entry.message.as_ref() {
             if let Some(model) = message.model.as_ref() {
@@ -68,10 +95,21 @@ pub fn parse_line_to_usage(
     seen: &mut HashSet<String>,
     state: &mut TranscriptState,
 ) -> Option<UsageEntry> {
+    parse_line_to_keyed_usage(line, session_id, seen, state).map(|(entry, _)| entry)
+}
+
+/// Like [`parse_line_to_usage`], also returning the deduplication key recorded in `seen`
+pub fn parse_line_to_keyed_usage(
+    line: &str,
+    session_id: &str,
+    seen: &mut HashSet<String>,
+    state: &mut TranscriptState,
+) -> Option<(UsageEntry, Option<String>)> {
     // Parse the JSON line
     let entry: TranscriptEntry = serde_json::from_str(line).ok()?;
 
     state.update_provider(&entry);
+    state.update_cwd(&entry);
     state.update_model_from_entry(&entry);
 
     match state.provider() {
@@ -86,7 +124,7 @@ fn parse_claude_entry(
     session_id: &str,
     seen: &mut HashSet<String>,
     state: &mut TranscriptState,
-) -> Option<UsageEntry> {
+) -> Option<(UsageEntry, Option<String>)> {
     if entry.r#type.as_deref() != Some("assistant") {
         return None;
     }
@@ -94,12 +132,14 @@ fn parse_claude_entry(
     let message = entry.message.as_ref()?;
     let raw_usage = message.usage.as_ref()?;
 
+    let mut key = None;
     if let (Some(msg_id), Some(req_id)) = (message.id.as_ref(), entry.request_id.as_ref()) {
         let hash = format!("claude:{}:{}:{}", session_id, msg_id, req_id);
         if seen.contains(&hash) {
             return None;
         }
-        seen.insert(hash);
+        seen.insert(hash.clone());
+        key = Some(hash);
     }
 
     let normalized = raw_usage.clone().normalize();
@@ -113,6 +153,7 @@ fn parse_claude_entry(
         entry.timestamp.as_deref(),
         model_ref,
     )
+    .map(|usage| (usage, key))
 }
 
 fn parse_codex_entry(
@@ -120,7 +161,7 @@ fn parse_codex_entry(
     session_id: &str,
     seen: &mut HashSet<String>,
     state: &mut TranscriptState,
-) -> Option<UsageEntry> {
+) -> Option<(UsageEntry, Option<String>)> {
     let payload = entry.payload.as_ref()?;
     let payload_type = payload.r#type.as_deref()?;
 
@@ -135,7 +176,7 @@ fn parse_codex_entry(
     if seen.contains(&hash) {
         return None;
     }
-    seen.insert(hash);
+    seen.insert(hash.clone());
 
     let normalized = normalize_codex_usage(info);
     state.last_normalized = Some(normalized.clone());
@@ -148,6 +189,7 @@ fn parse_codex_entry(
         entry.timestamp.as_deref(),
         Some(model.as_str()),
     )
+    .map(|usage| (usage, Some(hash)))
 }
 
 /// Convert NormalizedUsage to UsageEntry
@@ -0,0 +1,539 @@
+use crate::billing::UsageEntry;
+use crate::config::ProviderKind;
+use crate::utils::data_loader::detect_provider_from_path;
+use crate::utils::resolve_config_dir;
+use crate::utils::transcript::{parse_line_to_keyed_usage, TranscriptState};
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::fs;
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+const INDEX_DIR: &str = "transcript_index";
+/// Single-file index of earlier versions, removed when found
+const LEGACY_INDEX_FILE: &str = ".transcript_index.json";
+
+/// Bumped whenever the on-disk layout or the parsing rules change
+const INDEX_VERSION: u32 = 3;
+
+/// Bytes at the start and at the end of the parsed part of a file that are
+/// compared to tell an append from a rewrite
+const FINGERPRINT_WINDOW: u64 = 4096;
+
+/// Files larger than this are only indexed from their last `LARGE_FILE_TAIL` bytes
+const LARGE_FILE_LIMIT: u64 = 100 * 1024 * 1024;
+const LARGE_FILE_TAIL: u64 = 10 * 1024 * 1024;
+
+/// Parsed usage entries of every transcript file, kept on disk between runs
+///
+/// Each file is recorded with its size, modification time, inode, the byte
+/// offset parsing stopped at and a fingerprint of the parsed bytes. A file that
+/// only grew is parsed from that offset; one that shrank, was replaced or was
+/// rewritten in place is parsed again from the start.
+///
+/// Records are stored one file each in a directory, so a refresh only rewrites
+/// the records of transcripts that changed. Records that cannot be read or
+/// have another version are discarded.
+#[derive(Debug, Clone, Default)]
+pub struct TranscriptIndex {
+    files: BTreeMap<PathBuf, FileRecord>,
+    /// Records to write and to delete on the next save
+    changed: BTreeSet<PathBuf>,
+    removed: BTreeSet<PathBuf>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct FileRecord {
+    size: u64,
+    mtime_ns: u64,
+    inode: u64,
+    /// End of the last complete line parsed; a partially written line is re-read next time
+    offset: u64,
+    /// Hash of the bytes around the start and the end of the parsed part
+    fingerprint: u64,
+    state: TranscriptState,
+    entries: Vec<IndexedEntry>,
+}
+
+/// One record file of the index directory
+#[derive(Serialize, Deserialize)]
+struct StoredRecord<'a> {
+    version: u32,
+    path: Cow<'a, Path>,
+    record: Cow<'a, FileRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedEntry {
+    #[serde(flatten)]
+    usage: UsageEntry,
+    /// Deduplication key, shared by copies of the same message across files
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    key: Option<String>,
+}
+
+/// A usage entry together with the transcript it was read from
+#[derive(Debug, Clone)]
+pub struct SourcedEntry {
+    pub usage: UsageEntry,
+    /// Provider detected for the transcript, when it could be determined
+    pub provider: Option<ProviderKind>,
+    pub path: PathBuf,
+    /// Working directory recorded in the transcript, if any
+    pub cwd: Option<String>,
+}
+
+/// Identity of a file on disk, compared against its record
+struct FileStamp {
+    size: u64,
+    mtime_ns: u64,
+    inode: u64,
+}
+
+impl TranscriptIndex {
+    /// Location of the index directory under the config directory
+    pub fn default_path() -> PathBuf {
+        resolve_config_dir().join(INDEX_DIR)
+    }
+
+    /// Load the index in directory `dir`, skipping records that are corrupt or
+    /// from another version
+    pub fn load(dir: &Path) -> Self {
+        let mut index = Self::default();
+        let Ok(listing) = fs::read_dir(dir) else {
+            if let Some(parent) = dir.parent() {
+                let _ = fs::remove_file(parent.join(LEGACY_INDEX_FILE));
+            }
+            return index;
+        };
+
+        for file in listing.flatten() {
+            let file = file.path();
+            if file.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            let stored = fs::read(&file)
+                .ok()
+                .and_then(|bytes| serde_json::from_slice::<StoredRecord>(&bytes).ok())
+                .filter(|stored| stored.version == INDEX_VERSION);
+            match stored {
+                Some(stored) if record_file(dir, &stored.path) == file => {
+                    index
+                        .files
+                        .insert(stored.path.into_owned(), stored.record.into_owned());
+                }
+                _ => {
+                    let _ = fs::remove_file(&file);
+                }
+            }
+        }
+        index
+    }
+
+    /// Write the records that changed and delete those of forgotten files
+    ///
+    /// Each record replaces its old file atomically; nothing is written when no
+    /// transcript changed.
+    pub fn save(&mut self, dir: &Path) -> io::Result<()> {
+        if self.changed.is_empty() && self.removed.is_empty() {
+            return Ok(());
+        }
+
+        fs::create_dir_all(dir)?;
+        for path in std::mem::take(&mut self.removed) {
+            match fs::remove_file(record_file(dir, &path)) {
+                Err(e) if e.kind() != io::ErrorKind::NotFound => return Err(e),
+                _ => {}
+            }
+        }
+        for path in std::mem::take(&mut self.changed) {
+            let Some(record) = self.files.get(&path) else {
+                continue;
+            };
+            let stored = StoredRecord {
+                version: INDEX_VERSION,
+                path: Cow::Borrowed(&path),
+                record: Cow::Borrowed(record),
+            };
+            let file = record_file(dir, &path);
+            let temp = file.with_extension(format!("json.{}", std::process::id()));
+            fs::write(&temp, serde_json::to_vec(&stored)?)?;
+            fs::rename(&temp, &file)?;
+        }
+        Ok(())
+    }
+
+    /// Number of indexed files
+    pub fn file_count(&self) -> usize {
+        self.files.len()
+    }
+
+    /// Bring the index up to date with `files`, forgetting files no longer listed
+    pub fn refresh(&mut self, files: &[PathBuf]) {
+        let listed: HashSet<&PathBuf> = files.iter().collect();
+        let forgotten: Vec<PathBuf> = self
+            .files
+            .keys()
+            .filter(|path| !listed.contains(path))
+            .cloned()
+            .collect();
+        for path in forgotten {
+            self.forget(path);
+        }
+
+        for path in files {
+            let Some(stamp) = FileStamp::read(path) else {
+                self.forget(path.clone());
+                continue;
+            };
+
+            match self.files.get_mut(path) {
+                Some(record) if record.matches(&stamp) => continue,
+                Some(record) if record.appended(path, &stamp) => {
+                    record.parse(path, stamp, false);
+                }
+                _ => {
+                    self.files
+                        .insert(path.clone(), FileRecord::scan(path, stamp));
+                }
+            }
+            self.removed.remove(path);
+            self.changed.insert(path.clone());
+        }
+    }
+
+    fn forget(&mut self, path: PathBuf) {
+        if self.files.remove(&path).is_some() {
+            self.changed.remove(&path);
+            self.removed.insert(path);
+        }
+    }
+
+    /// Entries of `files` in order, deduplicated across files and sorted by timestamp
+    pub fn entries(&self, files: &[PathBuf]) -> Vec<UsageEntry> {
+        let mut entries: Vec<UsageEntry> = self
+            .deduplicated(files)
+            .map(|(_, _, indexed)| indexed.usage.clone())
+            .collect();
+        entries.sort_by_key(|e| e.timestamp);
+        entries
+    }
+
+    /// Like [`TranscriptIndex::entries`], keeping each entry's file and provider
+    pub fn sourced_entries(&self, files: &[PathBuf]) -> Vec<SourcedEntry> {
+        let mut entries: Vec<SourcedEntry> = self
+            .deduplicated(files)
+            .map(|(path, record, indexed)| SourcedEntry {
+                usage: indexed.usage.clone(),
+                provider: record.state.provider(),
+                path: path.clone(),
+                cwd: record.state.cwd().map(str::to_string),
+            })
+            .collect();
+        entries.sort_by_key(|e| e.usage.timestamp);
+        entries
+    }
+
+    /// Indexed entries of `files` in file order, skipping keys already seen in an earlier file
+    fn deduplicated<'a>(
+        &'a self,
+        files: &'a [PathBuf],
+    ) -> impl Iterator<Item = (&'a PathBuf, &'a FileRecord, &'a IndexedEntry)> {
+        let mut seen = HashSet::new();
+        files
+            .iter()
+            .filter_map(|path| self.files.get(path).map(|record| (path, record)))
+            .flat_map(|(path, record)| {
+                record
+                    .entries
+                    .iter()
+                    .map(move |indexed| (path, record, indexed))
+            })
+            .filter(move |(_, _, indexed)| match &indexed.key {
+                Some(key) => seen.insert(key.as_str()),
+                None => true,
+            })
+    }
+}
+
+impl FileStamp {
+    fn read(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let mtime_ns = metadata
+            .modified()
+            .ok()
+            .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+            .map(|duration| duration.as_nanos() as u64)
+            .unwrap_or(0);
+
+        #[cfg(unix)]
+        let inode = std::os::unix::fs::MetadataExt::ino(&metadata);
+        #[cfg(not(unix))]
+        let inode = 0;
+
+        Some(Self {
+            size: metadata.len(),
+            mtime_ns,
+            inode,
+        })
+    }
+}
+
+impl FileRecord {
+    /// Parse a file from the start (or from its tail when it is very large)
+    fn scan(path: &Path, stamp: FileStamp) -> Self {
+        let start = if stamp.size > LARGE_FILE_LIMIT {
+            stamp.size - LARGE_FILE_TAIL
+        } else {
+            0
+        };
+
+        let mut record = FileRecord {
+            size: 0,
+            mtime_ns: 0,
+            inode: stamp.inode,
+            offset: start,
+            fingerprint: 0,
+            state: TranscriptState::with_provider(detect_provider_from_path(path)),
+            entries: Vec::new(),
+        };
+        // A tail read starts mid-line; skip to the first complete one
+        record.parse(path, stamp, start > 0);
+        record
+    }
+
+    fn matches(&self, stamp: &FileStamp) -> bool {
+        self.size == stamp.size && self.mtime_ns == stamp.mtime_ns && self.inode == stamp.inode
+    }
+
+    /// The same file, only longer: everything before `offset` is still valid
+    ///
+    /// A file rewritten in place keeps its inode and may grow, so the bytes
+    /// around the start and the end of the parsed part must be unchanged too.
+    fn appended(&self, path: &Path, stamp: &FileStamp) -> bool {
+        self.inode == stamp.inode
+            && stamp.size > self.size
+            && stamp.size >= self.offset
+            && fingerprint(path, self.offset).is_ok_and(|hash| hash == self.fingerprint)
+    }
+
+    /// Parse complete lines from `offset` onwards and advance past them
+    fn parse(&mut self, path: &Path, stamp: FileStamp, skip_partial_line: bool) {
+        self.size = stamp.size;
+        self.mtime_ns = stamp.mtime_ns;
+
+        let Ok(mut bytes) = read_from(path, self.offset) else {
+            return;
+        };
+
+        let mut consumed = 0;
+        if skip_partial_line {
+            match bytes.iter().position(|&b| b == b'\n') {
+                Some(pos) => consumed = pos + 1,
+                None => return,
+            }
+        }
+        // Stop at the last newline so a line still being written is picked up later
+        let end = match bytes.iter().rposition(|&b| b == b'\n') {
+            Some(pos) if pos >= consumed => pos + 1,
+            _ => consumed,
+        };
+        bytes.truncate(end);
+
+        let session_id = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("unknown")
+            .to_string();
+        let mut seen: HashSet<String> = self
+            .entries
+            .iter()
+            .filter_map(|indexed| indexed.key.clone())
+            .collect();
+
+        let content = String::from_utf8_lossy(&bytes[consumed..]);
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if let Some((usage, key)) =
+                parse_line_to_keyed_usage(line, &session_id, &mut seen, &mut self.state)
+            {
+                self.entries.push(IndexedEntry { usage, key });
+            }
+        }
+        self.offset += end as u64;
+        self.fingerprint = fingerprint(path, self.offset).unwrap_or_default();
+    }
+}
+
+/// File in the index directory `dir` that holds the record of `path`
+fn record_file(dir: &Path, path: &Path) -> PathBuf {
+    let name = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy())
+        .unwrap_or_default();
+    let name: String = name
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == '_')
+        .take(64)
+        .collect();
+    // The hash tells apart transcripts with the same name in different folders
+    let hash = fnv1a(path.to_string_lossy().as_bytes(), FNV_OFFSET);
+    dir.join(format!("{}-{:016x}.json", name, hash))
+}
+
+/// Hash of the first and the last `FINGERPRINT_WINDOW` bytes before `offset`
+fn fingerprint(path: &Path, offset: u64) -> io::Result<u64> {
+    let mut file = fs::File::open(path)?;
+    let mut head = vec![0; offset.min(FINGERPRINT_WINDOW) as usize];
+    file.read_exact(&mut head)?;
+    let tail_start = offset.saturating_sub(FINGERPRINT_WINDOW);
+    let mut tail = vec![0; (offset - tail_start) as usize];
+    file.seek(SeekFrom::Start(tail_start))?;
+    file.read_exact(&mut tail)?;
+    Ok(fnv1a(&tail, fnv1a(&head, FNV_OFFSET)))
+}
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+
+/// 64-bit FNV-1a, which unlike the standard hasher is stable across releases
+fn fnv1a(bytes: &[u8], seed: u64) -> u64 {
+    bytes.iter().fold(seed, |hash, &byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+fn read_from(path: &Path, offset: u64) -> io::Result<Vec<u8>> {
+    let mut file = fs::File::open(path)?;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    fn assistant_line(message_id: &str, timestamp: &str, output_tokens: u32) -> String {
+        format!(
+            "{{\"type\":\"assistant\",\"timestamp\":\"{}\",\"requestId\":\"req-{}\",\"message\":{{\"id\":\"{}\",\"model\":\"claude-sonnet-4\",\"usage\":{{\"input_tokens\":10,\"output_tokens\":{}}}}}}}\n",
+            timestamp, message_id, message_id, output_tokens
+        )
+    }
+
+    fn output_tokens(index: &TranscriptIndex, files: &[PathBuf]) -> Vec<u32> {
+        index
+            .entries(files)
+            .iter()
+            .map(|e| e.output_tokens)
+            .collect()
+    }
+
+    #[test]
+    fn test_incremental_refresh() {
+        let root = std::env::temp_dir().join(format!("ccline-index-{}", std::process::id()));
+        let dir = root.join(".claude").join("projects");
+        fs::create_dir_all(&dir).unwrap();
+        let file = dir.join("session.jsonl");
+        let files = vec![file.clone()];
+
+        fs::write(&file, assistant_line("m1", "2025-10-01T10:00:00Z", 1)).unwrap();
+        let mut index = TranscriptIndex::default();
+        index.refresh(&files);
+        assert_eq!(output_tokens(&index, &files), vec![1]);
+
+        // Appended lines are parsed; a partial trailing line waits for its newline
+        let mut handle = fs::OpenOptions::new().append(true).open(&file).unwrap();
+        handle
+            .write_all(assistant_line("m2", "2025-10-01T10:05:00Z", 2).as_bytes())
+            .unwrap();
+        let partial = assistant_line("m3", "2025-10-01T10:10:00Z", 3);
+        let (head, tail) = partial.split_at(40);
+        handle.write_all(head.as_bytes()).unwrap();
+        index.refresh(&files);
+        assert_eq!(output_tokens(&index, &files), vec![1, 2]);
+
+        handle.write_all(tail.as_bytes()).unwrap();
+        // Repeated message ids are still deduplicated across refreshes
+        handle
+            .write_all(assistant_line("m1", "2025-10-01T10:00:00Z", 1).as_bytes())
+            .unwrap();
+        index.refresh(&files);
+        assert_eq!(output_tokens(&index, &files), vec![1, 2, 3]);
+
+        // The index survives a save/load round trip
+        let index_dir = root.join(INDEX_DIR);
+        index.save(&index_dir).unwrap();
+        let mut reloaded = TranscriptIndex::load(&index_dir);
+        assert_eq!(output_tokens(&reloaded, &files), vec![1, 2, 3]);
+
+        // A file rewritten in place that grew is not mistaken for an append
+        fs::write(
+            &file,
+            assistant_line("m7", "2025-10-02T08:00:00Z", 7).repeat(2)
+                + &assistant_line("m8", "2025-10-02T08:30:00Z", 8).repeat(3),
+        )
+        .unwrap();
+        reloaded.refresh(&files);
+        assert_eq!(output_tokens(&reloaded, &files), vec![7, 8]);
+
+        // A truncated file is parsed again from the start
+        fs::write(&file, assistant_line("m9", "2025-10-02T09:00:00Z", 9)).unwrap();
+        reloaded.refresh(&files);
+        assert_eq!(output_tokens(&reloaded, &files), vec![9]);
+
+        // Deleted files are dropped, and corrupt records are discarded
+        reloaded.refresh(&[]);
+        assert_eq!(reloaded.file_count(), 0);
+        reloaded.save(&index_dir).unwrap();
+        assert_eq!(fs::read_dir(&index_dir).unwrap().count(), 0);
+        fs::write(index_dir.join("broken.json"), "{\"version\":3,\"path\":").unwrap();
+        assert_eq!(TranscriptIndex::load(&index_dir).file_count(), 0);
+        assert_eq!(fs::read_dir(&index_dir).unwrap().count(), 0);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_save_writes_changed_records_only() {
+        let root = std::env::temp_dir().join(format!("ccline-index-save-{}", std::process::id()));
+        let dir = root.join(".claude").join("projects");
+        fs::create_dir_all(&dir).unwrap();
+        let files = vec![dir.join("a.jsonl"), dir.join("b.jsonl")];
+        fs::write(&files[0], assistant_line("a1", "2025-10-01T10:00:00Z", 1)).unwrap();
+        fs::write(&files[1], assistant_line("b1", "2025-10-01T11:00:00Z", 2)).unwrap();
+
+        let index_dir = root.join(INDEX_DIR);
+        let mut index = TranscriptIndex::load(&index_dir);
+        index.refresh(&files);
+        index.save(&index_dir).unwrap();
+        assert_eq!(fs::read_dir(&index_dir).unwrap().count(), 2);
+
+        // Nothing changed, so nothing is marked for writing
+        let mut index = TranscriptIndex::load(&index_dir);
+        index.refresh(&files);
+        assert!(index.changed.is_empty() && index.removed.is_empty());
+
+        // Only the record of the transcript that grew is rewritten
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&files[1])
+            .unwrap()
+            .write_all(assistant_line("b2", "2025-10-01T11:05:00Z", 3).as_bytes())
+            .unwrap();
+        index.refresh(&files);
+        assert_eq!(index.changed, BTreeSet::from([files[1].clone()]));
+        index.save(&index_dir).unwrap();
+        assert_eq!(
+            output_tokens(&TranscriptIndex::load(&index_dir), &files),
+            vec![1, 2, 3]
+        );
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
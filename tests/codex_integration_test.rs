@@ -135,7 +135,7 @@ fn test_codex_path_detection() {
         );
 
         let input_data = InputData::from_reader(json_input.as_bytes())
-            .expect(&format!("Should parse input with path: {}", display_path));
+            .unwrap_or_else(|_| panic!("Should parse input with path: {}", display_path));
 
         assert_eq!(
             input_data.provider,
@@ -0,0 +1,420 @@
+use ccometixline::billing::ModelPricing;
+use ccometixline::config::{BlockOverrideManager, ProviderKind};
+use ccometixline::output::{self, OutputFormat};
+use ccometixline::report::{self, ReportOptions, ReportTimezone, SessionSort, WeekStart};
+use ccometixline::utils::transcript_index::SourcedEntry;
+use ccometixline::utils::DataLoader;
+use chrono::NaiveDate;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Fixture homes the daily report tests read: two sessions in October
+const RECENT: &[&str] = &["home"];
+/// `home` plus a session in September, for reports spanning several weeks and months
+const HISTORY: &[&str] = &["home", "history"];
+
+/// Entries of the fixture transcripts under `tests/fixtures/<name>` for every name
+fn fixture_entries(names: &[&str]) -> Vec<SourcedEntry> {
+    static LOADS: AtomicUsize = AtomicUsize::new(0);
+    let fixtures = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures");
+    let dirs = names
+        .iter()
+        .flat_map(|name| {
+            let home = fixtures.join(name);
+            [home.join(".claude/projects"), home.join(".codex/sessions")]
+        })
+        .collect();
+    // Tests run in parallel, so every load gets its own index
+    let index = std::env::temp_dir().join(format!(
+        "ccline-report-{}-{}",
+        std::process::id(),
+        LOADS.fetch_add(1, Ordering::SeqCst)
+    ));
+    let loader = DataLoader::with_dirs(dirs).with_index_path(index.clone());
+    let entries = report::load_entries(&loader, &ModelPricing::fallback_pricing());
+    let _ = std::fs::remove_dir_all(index);
+    entries
+}
+
+fn utc() -> ReportOptions {
+    ReportOptions {
+        timezone: ReportTimezone::Utc,
+        ..ReportOptions::default()
+    }
+}
+
+fn day(text: &str) -> NaiveDate {
+    text.parse().unwrap()
+}
+
+#[test]
+fn test_daily_report_buckets_by_day() {
+    let entries = fixture_entries(RECENT);
+    let report = report::daily(&entries, &utc());
+
+    let days: Vec<String> = report.days.iter().map(|d| d.key.to_string()).collect();
+    assert_eq!(days, ["2025-10-01", "2025-10-02"]);
+
+    // The duplicated Claude message is only counted once
+    let first = &report.days[0];
+    assert_eq!(first.totals.entries, 2);
+    assert_eq!(first.totals.input_tokens, 1200);
+    assert_eq!(first.totals.cache_creation_tokens, 2000);
+    assert_eq!(first.totals.cache_read_tokens, 10000);
+    assert_eq!(
+        first.models.keys().collect::<Vec<_>>(),
+        ["claude-opus-4", "claude-sonnet-4"]
+    );
+    // Opus 4 at $15/$75 per million tokens: 200 in, 100 out
+    let opus = &first.models["claude-opus-4"];
+    assert_eq!(opus.unpriced_entries, 0);
+    assert!((opus.cost - 0.0105).abs() < 1e-9, "{}", opus.cost);
+    // Sonnet 4: 1000 in, 500 out, 2000 cache writes and 10000 cache reads
+    assert!((first.totals.cost - (0.0105 + 0.021)).abs() < 1e-9);
+    assert_eq!(first.totals.unpriced_entries, 0);
+
+    let second = &report.days[1];
+    assert_eq!(
+        second.models.keys().collect::<Vec<_>>(),
+        ["claude-sonnet-4", "gpt-5-codex"]
+    );
+
+    assert_eq!(report.totals.entries, 4);
+    assert_eq!(
+        report.totals.total_tokens(),
+        report
+            .days
+            .iter()
+            .map(|d| d.totals.total_tokens())
+            .sum::<u64>()
+    );
+
+    let rendered = report.table(false).render();
+    assert!(rendered.starts_with("Date"));
+    assert!(rendered.contains("2025-10-01"));
+    assert!(rendered.lines().last().unwrap().starts_with("Total"));
+}
+
+#[test]
+fn test_daily_report_filters() {
+    let entries = fixture_entries(RECENT);
+
+    let codex = report::daily(
+        &entries,
+        &ReportOptions {
+            provider: Some(ProviderKind::Codex),
+            ..utc()
+        },
+    );
+    assert_eq!(codex.days.len(), 1);
+    assert_eq!(
+        codex.days[0].models.keys().collect::<Vec<_>>(),
+        ["gpt-5-codex"]
+    );
+
+    let claude = report::daily(
+        &entries,
+        &ReportOptions {
+            provider: Some(ProviderKind::Claude),
+            ..utc()
+        },
+    );
+    assert_eq!(claude.totals.entries, 3);
+
+    let since = report::daily(
+        &entries,
+        &ReportOptions {
+            since: Some(day("2025-10-02")),
+            ..utc()
+        },
+    );
+    assert_eq!(since.days.len(), 1);
+    assert_eq!(since.totals.entries, 2);
+
+    let until = report::daily(
+        &entries,
+        &ReportOptions {
+            until: Some(day("2025-09-30")),
+            ..utc()
+        },
+    );
+    assert!(until.days.is_empty());
+    assert!(until.table(false).is_empty());
+}
+
+#[test]
+fn test_daily_report_timezone() {
+    let entries = fixture_entries(RECENT);
+    let tokyo = report::daily(
+        &entries,
+        &ReportOptions {
+            timezone: "+09:00".parse().unwrap(),
+            ..ReportOptions::default()
+        },
+    );
+
+    // The 23:30 UTC Opus request falls on the next day in UTC+9
+    assert_eq!(tokyo.days[0].key, day("2025-10-01"));
+    assert_eq!(tokyo.days[0].totals.entries, 1);
+    assert!(tokyo.days[1].models.contains_key("claude-opus-4"));
+}
+
+#[test]
+fn test_weekly_report_week_start() {
+    let entries = fixture_entries(HISTORY);
+
+    let iso = report::weekly(&entries, &utc());
+    let weeks: Vec<String> = iso.weeks.iter().map(|w| w.key.to_string()).collect();
+    assert_eq!(weeks, ["2025-09-15", "2025-09-29"]);
+    assert_eq!(iso.totals, report::daily(&entries, &utc()).totals);
+
+    // 2025-09-20 is a Saturday, so a Saturday week starts on it
+    let saturday = report::weekly(
+        &entries,
+        &ReportOptions {
+            week_start: WeekStart::Saturday,
+            ..utc()
+        },
+    );
+    let weeks: Vec<String> = saturday.weeks.iter().map(|w| w.key.to_string()).collect();
+    assert_eq!(weeks, ["2025-09-20", "2025-09-27"]);
+
+    let week = &iso.weeks[1];
+    assert_eq!(
+        week.providers.keys().collect::<Vec<_>>(),
+        ["claude", "codex"]
+    );
+    assert_eq!(week.providers["claude"].len(), 2);
+    let rendered = iso.table(true).render();
+    assert!(rendered.contains("2025-09-29 – 10-05"));
+    assert!(rendered.contains("└ codex"));
+}
+
+#[test]
+fn test_monthly_report_changes() {
+    let entries = fixture_entries(HISTORY);
+    let report = report::monthly(&entries, &utc());
+
+    let months: Vec<String> = report.months.iter().map(|m| m.key.to_string()).collect();
+    assert_eq!(months, ["2025-09-01", "2025-10-01"]);
+    assert_eq!(report.changes[0], None);
+
+    let september = &report.months[0].totals;
+    let october = &report.months[1].totals;
+    let change = report.changes[1].expect("October has a predecessor");
+    assert_eq!(
+        change.tokens,
+        october.total_tokens() as i64 - september.total_tokens() as i64
+    );
+    assert!((change.cost - (october.cost - september.cost)).abs() < 1e-9);
+    assert!(change.cost_percent.is_some());
+
+    let rendered = report.table(false).render();
+    assert!(rendered.contains("Δ Cost"));
+    assert!(rendered.contains("2025-10"));
+}
+
+#[test]
+fn test_report_flags_unpriced_models() {
+    let mut entries = fixture_entries(RECENT);
+    let mut gateway = entries
+        .iter()
+        .find(|entry| entry.usage.timestamp.to_rfc3339() == "2025-10-02T12:00:00+00:00")
+        .unwrap()
+        .clone();
+    gateway.usage.model = "my-gateway-model".to_string();
+    gateway.usage.cost = None;
+    entries.push(gateway);
+
+    let report = report::daily(&entries, &utc());
+    let day = &report.days[1];
+    assert_eq!(day.totals.unpriced_entries, 1);
+    assert_eq!(
+        day.totals.unpriced_models.iter().collect::<Vec<_>>(),
+        ["my-gateway-model"]
+    );
+    assert_eq!(day.models["my-gateway-model"].cost, 0.0);
+    assert_eq!(report.days[0].totals.unpriced_entries, 0);
+    assert_eq!(report.totals.unpriced_entries, 1);
+
+    let rendered = report.table(false).render();
+    assert!(rendered
+        .lines()
+        .any(|line| line.starts_with("2025-10-02") && line.ends_with('*')));
+    assert!(rendered.ends_with("* Excludes 1 entry of models without a price: my-gateway-model"));
+
+    let output = report.output(&utc());
+    let json: serde_json::Value =
+        serde_json::from_str(&output.render(OutputFormat::Json, false).unwrap()).unwrap();
+    assert_eq!(json["rows"][0]["unpriced_entries"], 0);
+    assert_eq!(json["rows"][1]["unpriced_entries"], 1);
+    assert_eq!(
+        json["totals"]["unpriced_models"],
+        serde_json::json!(["my-gateway-model"])
+    );
+    let csv = output.render(OutputFormat::Csv, false).unwrap();
+    assert!(csv.lines().last().unwrap().ends_with(",1,my-gateway-model"));
+}
+
+#[test]
+fn test_sessions_report_projects() {
+    let entries = fixture_entries(HISTORY);
+    let report = report::sessions(&entries, &utc(), None);
+
+    // Most recently active first
+    let projects: Vec<&str> = report
+        .sessions
+        .iter()
+        .map(|s| s.project.as_deref().unwrap())
+        .collect();
+    assert_eq!(
+        projects,
+        ["/home/user/tool", "/home/user/demo", "/home/user/api"]
+    );
+    assert_eq!(report.totals, report::daily(&entries, &utc()).totals);
+
+    let demo = &report.sessions[1];
+    assert_eq!(demo.id(), "5f1c2d3e-0000-4000-8000-000000000001");
+    assert_eq!(demo.provider, Some(ProviderKind::Claude));
+    assert_eq!(demo.first.to_rfc3339(), "2025-10-01T10:00:00+00:00");
+    assert_eq!(demo.last.to_rfc3339(), "2025-10-02T12:00:00+00:00");
+    assert_eq!(demo.duration().num_hours(), 26);
+    assert_eq!(
+        demo.usage.models.keys().collect::<Vec<_>>(),
+        ["claude-opus-4", "claude-sonnet-4"]
+    );
+
+    let mut by_project = report.clone();
+    by_project.sort(SessionSort::Project, false);
+    assert_eq!(
+        by_project.sessions[0].project.as_deref(),
+        Some("/home/user/api")
+    );
+    by_project.sort(SessionSort::Duration, true);
+    assert_eq!(by_project.sessions.last().unwrap().id(), demo.id());
+
+    let filtered = report::sessions(&entries, &utc(), Some("user/de"));
+    assert_eq!(filtered.sessions.len(), 1);
+    assert_eq!(filtered.totals, demo.usage.totals);
+
+    let rendered = report.table(&utc(), true).render();
+    assert!(rendered.contains("0199a000-0000-7000-8000-000000000002"));
+    assert!(rendered.contains("26h 00m"));
+    assert!(rendered.contains("└ codex"));
+}
+
+#[test]
+fn test_blocks_report_history() {
+    let entries = fixture_entries(HISTORY);
+    // An empty override set, never loaded, keeps the user's block starts out of the test
+    let overrides = BlockOverrideManager::with_path(PathBuf::from("block_overrides.json"));
+    let blocks = |options: &ReportOptions, days| {
+        report::blocks_with_overrides(&entries, options, days, &overrides)
+    };
+    let report = blocks(&utc(), None);
+
+    let gaps: Vec<bool> = report.blocks.iter().map(|row| row.block.is_gap).collect();
+    assert_eq!(gaps, [false, true, false, true, false, true, false]);
+    // Gaps run from the end of one block to the start of the next
+    for pair in report.blocks.windows(2) {
+        assert_eq!(pair[0].block.end_time, pair[1].block.start_time);
+    }
+    assert_eq!(report.totals, report::daily(&entries, &utc()).totals);
+    assert!(report.active().is_none());
+
+    let last = report.blocks.last().unwrap();
+    assert_eq!(
+        last.block.start_time.to_rfc3339(),
+        "2025-10-02T12:00:00+00:00"
+    );
+    assert_eq!(last.block.session_count, 2);
+    assert_eq!(
+        last.usage.models.keys().collect::<Vec<_>>(),
+        ["claude-sonnet-4", "gpt-5-codex"]
+    );
+    assert_eq!(report.blocks[1].usage.totals, Default::default());
+
+    let codex = blocks(
+        &ReportOptions {
+            provider: Some(ProviderKind::Codex),
+            ..utc()
+        },
+        None,
+    );
+    assert_eq!(codex.blocks.len(), 1);
+    assert_eq!(codex.blocks[0].block.session_count, 1);
+
+    let since = blocks(
+        &ReportOptions {
+            since: Some(day("2025-10-02")),
+            ..utc()
+        },
+        None,
+    );
+    assert_eq!(since.blocks.len(), 2);
+    assert!(blocks(&utc(), Some(1)).blocks.is_empty());
+
+    let rendered = report.table(&utc(), false).render();
+    assert!(rendered.contains("2025-10-01 23:00 – 10-02 04:00"));
+    assert!(rendered.contains("2025-10-01 15:00 – 23:00"));
+    assert!(rendered.contains("2025-10-02 04:00 – 12:00"));
+    assert!(rendered.contains("gap"));
+    assert!(rendered.contains("auto"));
+}
+
+#[test]
+fn test_report_machine_output() {
+    let entries = fixture_entries(HISTORY);
+    let daily = report::daily(&entries, &utc()).output(&utc());
+
+    let json: serde_json::Value =
+        serde_json::from_str(&daily.render(OutputFormat::Json, false).unwrap()).unwrap();
+    assert_eq!(json["schema_version"], output::SCHEMA_VERSION);
+    assert_eq!(json["kind"], "daily");
+    assert_eq!(json["filters"]["timezone"], "utc");
+    assert_eq!(json["rows"].as_array().unwrap().len(), 3);
+    assert_eq!(json["rows"][1]["date"], "2025-10-01");
+    assert_eq!(json["rows"][1]["models"][0]["model"], "claude-opus-4");
+    assert_eq!(json["totals"]["total_tokens"], 36300);
+
+    let ndjson = daily.render(OutputFormat::Ndjson, false).unwrap();
+    let types: Vec<String> = ndjson
+        .lines()
+        .map(|line| {
+            let line: serde_json::Value = serde_json::from_str(line).unwrap();
+            line["type"].as_str().unwrap().to_string()
+        })
+        .collect();
+    assert_eq!(types, ["row", "row", "row", "totals"]);
+    assert!(daily.render(OutputFormat::Table, false).is_none());
+
+    let csv = daily.render(OutputFormat::Csv, false).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next().unwrap(),
+        "date,models,input_tokens,output_tokens,cache_creation_tokens,cache_read_tokens,total_tokens,cost_usd,entries,unpriced_entries,unpriced_models"
+    );
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("2025-09-20,claude-sonnet-4,6000,3000,0,4000,13000,"));
+    assert!(lines
+        .next()
+        .unwrap()
+        .starts_with("2025-10-01,claude-opus-4;claude-sonnet-4,"));
+
+    let sessions = report::sessions(&entries, &utc(), None).output(&utc());
+    let csv = sessions.render(OutputFormat::Csv, true).unwrap();
+    assert!(csv.starts_with(
+        "session_id,project,provider,first,last,duration_minutes,model_provider,model,"
+    ));
+    assert!(csv.contains(
+        "5f1c2d3e-0000-4000-8000-000000000001,/home/user/demo,claude,2025-10-01T10:00:00Z,2025-10-02T12:00:00Z,1560,claude,claude-opus-4,"
+    ));
+
+    let monthly = report::monthly(&entries, &utc()).output(&utc());
+    let json: serde_json::Value =
+        serde_json::from_str(&monthly.render(OutputFormat::Json, false).unwrap()).unwrap();
+    assert!(json["rows"][0]["change"].is_null());
+    assert_eq!(json["rows"][1]["change"]["tokens"], 10300);
+}